@@ -1,61 +1,333 @@
-use bevy::{math::Vec3A, prelude::*, render::primitives::Aabb};
+use std::path::PathBuf;
 
-use crate::{BluePrintsConfig, Spawned};
+use bevy::{
+    gltf::Gltf,
+    math::Affine3A,
+    prelude::*,
+    render::{mesh::skinning::SkinnedMesh, primitives::Aabb},
+    utils::HashMap,
+};
 
-/// helper system that computes the compound aabbs of the scenes/blueprints
-pub fn compute_scene_aabbs(
-    root_entities: Query<(Entity, &Name), (With<Spawned>, Without<Aabb>)>,
-    children: Query<&Children>,
-    existing_aabbs: Query<&Aabb>,
+use crate::{
+    BluePrintsConfig, BlueprintAabbCache, BlueprintInstanceAssets, BlueprintName,
+    BlueprintSpawnState, BlueprintVariant, Library,
+};
 
-    mut blueprints_config: ResMut<BluePrintsConfig>,
+/// how the aabbs of blueprint instances are computed, when `aabbs` is enabled in the plugin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AabbSettings {
+    /// include skinned meshes (in their bind pose, see `recompute_after_animation`)
+    pub include_skinned_meshes: bool,
+    /// compute the aabb again once the first frames of the animations of the instance have been applied (ie for animated characters),
+    /// including the joints of skinned meshes
+    pub recompute_after_animation: bool,
+}
+
+impl Default for AabbSettings {
+    fn default() -> Self {
+        AabbSettings {
+            include_skinned_meshes: true,
+            recompute_after_animation: false,
+        }
+    }
+}
+
+/// per blueprint aabb settings, added to the blueprint in Blender or to the instance (values left to None use the ones of the plugin)
+#[derive(Component, Reflect, Default, Debug, Clone)]
+#[reflect(Component)]
+pub struct BlueprintAabbSettings {
+    /// compute the aabb of the instances of this blueprint, regardless of `aabbs` in the plugin
+    pub enabled: Option<bool>,
+    pub include_skinned_meshes: Option<bool>,
+    pub recompute_after_animation: Option<bool>,
+}
+
+/// key of the aabb cache: the path of the gltf file of a blueprint & the variant of its instances
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct AabbCacheKey {
+    pub(crate) path: PathBuf,
+    pub(crate) variant: Option<String>,
+}
+
+/// an aabb computed for the instances of a blueprint, shared by all the instances spawned after it
+#[derive(Debug, Clone)]
+pub(crate) struct CachedAabb {
+    pub(crate) aabb: Aabb,
+    pub(crate) include_skinned_meshes: bool,
+    /// the files the aabb depends on (the gltf files of the blueprint & of its nested blueprints)
+    pub(crate) assets: Vec<PathBuf>,
+}
+
+impl BluePrintsConfig {
+    pub(crate) fn aabb_cache_key(
+        &self,
+        blueprint_name: &str,
+        library_override: Option<&Library>,
+        variant: Option<&BlueprintVariant>,
+    ) -> AabbCacheKey {
+        AabbCacheKey {
+            path: self.blueprint_path(blueprint_name, library_override),
+            variant: variant.map(|variant| variant.0.clone()),
+        }
+    }
+}
+
+/// flag component for instances whose aabb is computed once they & all the blueprint instances nested inside them are ready
+#[derive(Component, Debug)]
+pub(crate) struct PendingAabb;
+
+fn is_spawning(spawn_state: Option<&BlueprintSpawnState>) -> bool {
+    !matches!(
+        spawn_state,
+        Some(BlueprintSpawnState::Ready | BlueprintSpawnState::Failed)
+    )
+}
+
+/// frames left before the aabb of an instance is computed again
+#[derive(Component, Debug)]
+pub(crate) struct RecomputeAabb(u32);
+
+/// the aabb of the whole hierarchy of a blueprint instance (nested instances included), in the local space of the instance
+fn instance_aabb(
+    instance: Entity,
+    all_children: &Query<&Children>,
+    transforms: &Query<&Transform>,
+    mesh_entities: &Query<(&Handle<Mesh>, Option<&SkinnedMesh>)>,
+    meshes: &Assets<Mesh>,
+    include_skinned_meshes: bool,
+    include_joints: bool,
+) -> Option<Aabb> {
+    let mut min = Vec3::splat(f32::MAX);
+    let mut max = Vec3::splat(f32::MIN);
+    let mut add_point = |point: Vec3| {
+        min = min.min(point);
+        max = max.max(point);
+    };
+
+    let mut affines: HashMap<Entity, Affine3A> = HashMap::new();
+    let mut joints: Vec<Entity> = vec![];
+    let mut stack = vec![(instance, Affine3A::IDENTITY)];
+    while let Some((entity, affine)) = stack.pop() {
+        affines.insert(entity, affine);
+        for child in all_children.get(entity).into_iter().flatten() {
+            let child_affine = transforms
+                .get(*child)
+                .map_or(Affine3A::IDENTITY, Transform::compute_affine);
+            stack.push((*child, affine * child_affine));
+        }
+        let Ok((mesh, skinned_mesh)) = mesh_entities.get(entity) else {
+            continue;
+        };
+        if let Some(skinned_mesh) = skinned_mesh {
+            if !include_skinned_meshes {
+                continue;
+            }
+            if include_joints {
+                joints.extend(skinned_mesh.joints.iter().copied());
+            }
+        }
+        let Some(mesh_aabb) = meshes.get(mesh).and_then(Mesh::compute_aabb) else {
+            continue;
+        };
+        let (center, half_extents) = (
+            Vec3::from(mesh_aabb.center),
+            Vec3::from(mesh_aabb.half_extents),
+        );
+        for corner in [
+            Vec3::new(-1.0, -1.0, -1.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(-1.0, 1.0, -1.0),
+            Vec3::new(-1.0, 1.0, 1.0),
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(1.0, -1.0, 1.0),
+            Vec3::new(1.0, 1.0, -1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+        ] {
+            add_point(affine.transform_point3(center + corner * half_extents));
+        }
+    }
+    for joint in joints {
+        if let Some(affine) = affines.get(&joint) {
+            add_point(affine.translation.into());
+        }
+    }
+    min.cmple(max).all().then(|| Aabb::from_min_max(min, max))
+}
+
+/// the aabb settings of an instance: its own, falling back to the ones of the plugin
+fn instance_settings(
+    settings: Option<&BlueprintAabbSettings>,
+    blueprints_config: &BluePrintsConfig,
+) -> (bool, AabbSettings) {
+    let global = blueprints_config.aabb_settings;
+    let Some(settings) = settings else {
+        return (blueprints_config.aabbs, global);
+    };
+    (
+        settings.enabled.unwrap_or(blueprints_config.aabbs),
+        AabbSettings {
+            include_skinned_meshes: settings
+                .include_skinned_meshes
+                .unwrap_or(global.include_skinned_meshes),
+            recompute_after_animation: settings
+                .recompute_after_animation
+                .unwrap_or(global.recompute_after_animation),
+        },
+    )
+}
+
+/// computes the aabb of blueprint instances once they are ready, and stores it on the instance
+/// (for culling, selection, streaming decisions etc)
+/// * instances do not wait for the blueprint instances nested inside them to be ready, so the aabb is only computed once these are ready too
+/// * the aabb is computed once per blueprint (& variant): the following instances reuse the cached one
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(crate) fn compute_instance_aabbs(
+    ready_instances: Query<
+        (Entity, &BlueprintSpawnState),
+        (With<BlueprintName>, Changed<BlueprintSpawnState>),
+    >,
+    instances: Query<
+        (
+            Entity,
+            &BlueprintName,
+            Option<&Library>,
+            Option<&BlueprintVariant>,
+            Option<&BlueprintInstanceAssets>,
+            Option<&BlueprintAabbSettings>,
+        ),
+        With<PendingAabb>,
+    >,
+    nested_instances: Query<Option<&BlueprintSpawnState>, With<BlueprintName>>,
+    all_children: Query<&Children>,
+    transforms: Query<&Transform>,
+    mesh_entities: Query<(&Handle<Mesh>, Option<&SkinnedMesh>)>,
+    meshes: Res<Assets<Mesh>>,
+    blueprints_config: Res<BluePrintsConfig>,
+    mut aabb_cache: ResMut<BlueprintAabbCache>,
     mut commands: Commands,
 ) {
-    // compute compound aabb
-    for (root_entity, name) in root_entities.iter() {
-        // info!("generating aabb for {:?}", name);
-
-        // only recompute aabb if it has not already been done before
-        if blueprints_config.aabb_cache.contains_key(&name.to_string()) {
-            let aabb = blueprints_config
-                .aabb_cache
-                .get(&name.to_string())
-                .expect("we should have the aabb available");
-            commands.entity(root_entity).insert(*aabb);
-        } else {
-            let aabb = compute_descendant_aabb(root_entity, &children, &existing_aabbs);
-            commands.entity(root_entity).insert(aabb);
-            blueprints_config.aabb_cache.insert(name.to_string(), aabb);
-        }
-    }
-}
-
-pub fn compute_descendant_aabb(
-    root_entity: Entity,
-    children: &Query<&Children>,
-    existing_aabbs: &Query<&Aabb>,
-) -> Aabb {
-    if let Ok(children_list) = children.get(root_entity) {
-        let mut chilren_aabbs: Vec<Aabb> = vec![];
-        for child in children_list.iter() {
-            if let Ok(aabb) = existing_aabbs.get(*child) {
-                chilren_aabbs.push(*aabb);
-            } else {
-                let aabb = compute_descendant_aabb(*child, children, existing_aabbs);
-                chilren_aabbs.push(aabb);
-            }
+    for (instance, spawn_state) in ready_instances.iter() {
+        if *spawn_state == BlueprintSpawnState::Ready {
+            commands.entity(instance).insert(PendingAabb);
         }
+    }
 
-        let mut min = Vec3A::splat(f32::MAX);
-        let mut max = Vec3A::splat(f32::MIN);
-        for aabb in chilren_aabbs.iter() {
-            min = min.min(aabb.min());
-            max = max.max(aabb.max());
+    for (instance, blueprint_name, library, variant, instance_assets, settings) in instances.iter()
+    {
+        if all_children
+            .iter_descendants(instance)
+            .any(|entity| nested_instances.get(entity).is_ok_and(is_spawning))
+        {
+            continue;
+        }
+        commands.entity(instance).remove::<PendingAabb>();
+        let (enabled, settings) = instance_settings(settings, &blueprints_config);
+        if !enabled {
+            continue;
+        }
+        if settings.recompute_after_animation {
+            commands.entity(instance).insert(RecomputeAabb(2));
         }
-        let aabb = Aabb::from_min_max(Vec3::from(min), Vec3::from(max));
 
-        return aabb;
+        let key = blueprints_config.aabb_cache_key(&blueprint_name.0, library, variant);
+        if let Some(cached) = aabb_cache.aabbs.get(&key) {
+            if cached.include_skinned_meshes == settings.include_skinned_meshes {
+                commands.entity(instance).insert(cached.aabb);
+                continue;
+            }
+        }
+        let Some(aabb) = instance_aabb(
+            instance,
+            &all_children,
+            &transforms,
+            &mesh_entities,
+            &meshes,
+            settings.include_skinned_meshes,
+            false,
+        ) else {
+            continue;
+        };
+        commands.entity(instance).insert(aabb);
+        let mut assets: Vec<PathBuf> = instance_assets
+            .into_iter()
+            .flat_map(|instance_assets| instance_assets.0.iter())
+            .map(|asset| PathBuf::from(&asset.path))
+            .collect();
+        assets.push(key.path.clone());
+        aabb_cache.aabbs.insert(
+            key,
+            CachedAabb {
+                aabb,
+                include_skinned_meshes: settings.include_skinned_meshes,
+                assets,
+            },
+        );
     }
+}
+
+/// removes the cached aabbs of the blueprints whose gltf files (or the ones of their nested blueprints) changed on disk
+pub(crate) fn invalidate_aabb_cache(
+    mut gltf_events: EventReader<AssetEvent<Gltf>>,
+    asset_server: Res<AssetServer>,
+    mut aabb_cache: ResMut<BlueprintAabbCache>,
+) {
+    let changed: Vec<PathBuf> = gltf_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => asset_server.get_path(*id),
+            _ => None,
+        })
+        .map(|path| path.path().to_path_buf())
+        .collect();
+    if changed.is_empty() {
+        return;
+    }
+    aabb_cache.aabbs.retain(|key, cached| {
+        let stale = cached.assets.iter().any(|asset| changed.contains(asset));
+        if stale {
+            debug!("invalidating cached aabb of {:?}", key.path);
+        }
+        !stale
+    });
+}
 
-    Aabb::default()
+/// computes the aabb of instances with `recompute_after_animation` again, once they are ready & their animations have been applied
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn recompute_instance_aabbs(
+    mut instances: Query<(
+        Entity,
+        &mut RecomputeAabb,
+        &BlueprintSpawnState,
+        Option<&BlueprintAabbSettings>,
+    )>,
+    all_children: Query<&Children>,
+    transforms: Query<&Transform>,
+    mesh_entities: Query<(&Handle<Mesh>, Option<&SkinnedMesh>)>,
+    meshes: Res<Assets<Mesh>>,
+    blueprints_config: Res<BluePrintsConfig>,
+    mut commands: Commands,
+) {
+    for (instance, mut recompute, spawn_state, settings) in instances.iter_mut() {
+        if *spawn_state != BlueprintSpawnState::Ready {
+            continue;
+        }
+        if recompute.0 > 0 {
+            recompute.0 -= 1;
+            continue;
+        }
+        let (_, settings) = instance_settings(settings, &blueprints_config);
+        let mut instance_commands = commands.entity(instance);
+        instance_commands.remove::<RecomputeAabb>();
+        if let Some(aabb) = instance_aabb(
+            instance,
+            &all_children,
+            &transforms,
+            &mesh_entities,
+            &meshes,
+            settings.include_skinned_meshes,
+            true,
+        ) {
+            instance_commands.insert(aabb);
+        }
+    }
 }
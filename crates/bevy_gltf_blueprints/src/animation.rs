@@ -1,3 +1,6 @@
+use std::time::Duration;
+
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 
@@ -14,3 +17,130 @@ pub struct Animations {
 /// this is for convenience, because currently , Bevy's gltf parsing inserts `AnimationPlayers` "one level down"
 /// ie armature/root for animated models, which means more complex queries to trigger animations that we want to avoid
 pub struct AnimationPlayerLink(pub Entity);
+
+/// controls the animations of blueprint instances directly from the instance entity, without having to look for their `AnimationPlayer`:
+/// ie `instance_animations.get_mut(instance)?.play("Run").repeat();`
+#[derive(SystemParam)]
+pub struct InstanceAnimations<'w, 's> {
+    instances: Query<'w, 's, (&'static Animations, &'static AnimationPlayerLink)>,
+    players: Query<'w, 's, &'static mut AnimationPlayer>,
+}
+
+impl InstanceAnimations<'_, '_> {
+    /// read only access to the animations of the given instance, None if it has no animations (yet)
+    pub fn get(&self, instance: Entity) -> Option<InstanceAnimation<&AnimationPlayer>> {
+        let (animations, link) = self.instances.get(instance).ok()?;
+        let player = self.players.get(link.0).ok()?;
+        Some(InstanceAnimation {
+            instance,
+            animations,
+            player,
+        })
+    }
+
+    /// controls the animations of the given instance, None if it has no animations (yet)
+    pub fn get_mut(&mut self, instance: Entity) -> Option<InstanceAnimation<Mut<AnimationPlayer>>> {
+        let (animations, link) = self.instances.get(instance).ok()?;
+        let player = self.players.get_mut(link.0).ok()?;
+        Some(InstanceAnimation {
+            instance,
+            animations,
+            player,
+        })
+    }
+}
+
+/// the animations of a blueprint instance together with its `AnimationPlayer`, see [`InstanceAnimations`]
+pub struct InstanceAnimation<'a, P> {
+    instance: Entity,
+    animations: &'a Animations,
+    player: P,
+}
+
+impl<P: std::ops::Deref<Target = AnimationPlayer>> InstanceAnimation<'_, P> {
+    /// names of the animations of the instance
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.animations.named_animations.keys().map(String::as_str)
+    }
+
+    /// name of the current animation, None if nothing was played yet
+    pub fn current(&self) -> Option<&str> {
+        let clip = self.player.animation_clip();
+        self.animations
+            .named_animations
+            .iter()
+            .find(|(_, handle)| *handle == clip)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// time elapsed in the current animation, in seconds
+    pub fn elapsed(&self) -> f32 {
+        self.player.elapsed()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.player.is_paused()
+    }
+
+    /// the underlying `AnimationPlayer`
+    pub fn player(&self) -> &AnimationPlayer {
+        &self.player
+    }
+}
+
+impl<'a> InstanceAnimation<'a, Mut<'a, AnimationPlayer>> {
+    fn clip(&self, name: &str) -> Option<Handle<AnimationClip>> {
+        let clip = self.animations.named_animations.get(name).cloned();
+        if clip.is_none() {
+            warn!(
+                "no animation named {} for {:?}, available animations: {:?}",
+                name,
+                self.instance,
+                self.animations.named_animations.keys()
+            );
+        }
+        clip
+    }
+
+    /// plays the animation with the given name (does nothing if it does not exist)
+    pub fn play(&mut self, name: &str) -> &mut Self {
+        if let Some(clip) = self.clip(name) {
+            self.player.play(clip);
+        }
+        self
+    }
+
+    /// plays the animation `from` (if it is not already playing), then blends into the animation `to` over `duration` seconds
+    pub fn crossfade(&mut self, from: &str, to: &str, duration: f32) -> &mut Self {
+        let (Some(from), Some(to)) = (self.clip(from), self.clip(to)) else {
+            return self;
+        };
+        self.player.play(from);
+        self.player
+            .play_with_transition(to, Duration::from_secs_f32(duration));
+        self
+    }
+
+    /// stops the current animation: pauses it & goes back to its start
+    pub fn stop_all(&mut self) -> &mut Self {
+        self.player.pause();
+        self.player.seek_to(0.0);
+        self
+    }
+
+    /// repeats the current animation forever
+    pub fn repeat(&mut self) -> &mut Self {
+        self.player.repeat();
+        self
+    }
+
+    pub fn set_speed(&mut self, speed: f32) -> &mut Self {
+        self.player.set_speed(speed);
+        self
+    }
+
+    /// the underlying `AnimationPlayer`, for anything not covered here
+    pub fn player_mut(&mut self) -> &mut AnimationPlayer {
+        &mut self.player
+    }
+}
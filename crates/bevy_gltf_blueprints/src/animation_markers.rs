@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use bevy_gltf_components::GltfMeshMaterialExtras;
+
+use crate::{AnimationPlayerLink, Animations, SourceGltf};
+
+/// sent when the playback of an animation of a blueprint instance crosses one of its markers
+/// (authored as pose markers on the Blender action, ie "footstep", "hit_frame")
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct BlueprintAnimationMarkerEvent {
+    /// the blueprint instance
+    pub entity: Entity,
+    /// name of the animation
+    pub clip: String,
+    /// name of the marker
+    pub marker: String,
+    /// time of the marker in the animation, in seconds
+    pub time: f32,
+}
+
+/// where the playback of the animation of an instance was when markers were last checked
+#[derive(Component, Debug)]
+pub(crate) struct AnimationMarkerCursor {
+    clip: AssetId<AnimationClip>,
+    time: f32,
+}
+
+/// whether the marker at `time` was crossed by moving from `from` to `to`
+fn crossed(time: f32, from: f32, to: f32, speed: f32) -> bool {
+    if speed >= 0.0 {
+        if to >= from {
+            time > from && time <= to
+        } else {
+            // looped back to the start
+            time > from || time <= to
+        }
+    } else if to <= from {
+        time < from && time >= to
+    } else {
+        time < from || time >= to
+    }
+}
+
+/// sends a [`BlueprintAnimationMarkerEvent`] for each marker crossed by the current animation of blueprint instances since the last frame
+pub(crate) fn fire_animation_markers(
+    mut instances: Query<(
+        Entity,
+        &Animations,
+        &AnimationPlayerLink,
+        &SourceGltf,
+        Option<&mut AnimationMarkerCursor>,
+    )>,
+    players: Query<&AnimationPlayer>,
+    gltf_extras: Res<GltfMeshMaterialExtras>,
+    mut commands: Commands,
+    mut marker_events: EventWriter<BlueprintAnimationMarkerEvent>,
+) {
+    for (entity, animations, link, source_gltf, cursor) in instances.iter_mut() {
+        let Ok(player) = players.get(link.0) else {
+            continue;
+        };
+        let clip = player.animation_clip().id();
+        let now = player.seek_time();
+        let speed = player.speed();
+
+        let from = match cursor {
+            Some(mut cursor) => {
+                if cursor.clip == clip && cursor.time == now {
+                    continue;
+                }
+                // markers at the very start of a newly played animation should fire too
+                let from = if cursor.clip == clip {
+                    cursor.time
+                } else if speed >= 0.0 {
+                    f32::NEG_INFINITY
+                } else {
+                    f32::INFINITY
+                };
+                cursor.clip = clip;
+                cursor.time = now;
+                from
+            }
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(AnimationMarkerCursor { clip, time: now });
+                continue;
+            }
+        };
+        if player.is_paused() {
+            continue;
+        }
+
+        let Some((clip_name, markers)) = animations
+            .named_animations
+            .iter()
+            .find(|(_, handle)| handle.id() == clip)
+            .and_then(|(name, _)| {
+                let markers = gltf_extras
+                    .get(source_gltf.0.id())?
+                    .animation_markers
+                    .get(name)?;
+                Some((name, markers))
+            })
+        else {
+            continue;
+        };
+        for (marker, time) in markers {
+            if crossed(*time, from, now, speed) {
+                marker_events.send(BlueprintAnimationMarkerEvent {
+                    entity,
+                    clip: clip_name.clone(),
+                    marker: marker.clone(),
+                    time: *time,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use std::fmt;
+
+use bevy::{
+    asset::{LoadState, UntypedAssetId},
+    prelude::*,
+    reflect::{reflect_trait, ReflectMut, TypeRegistry},
+};
+
+use crate::{
+    fail_blueprint_spawn, teardown_blueprint_instance, BluePrintsConfig, BlueprintAssetInfo,
+    BlueprintAssetKind, BlueprintEvent, BlueprintInstanceAssets, BlueprintName,
+    BlueprintReadyForFinalizing, BlueprintSpawnError, OriginalChildren, Strictness,
+};
+
+/// path to an asset, usable as a field of components exported from Blender (where handles cannot be authored directly):
+/// ie `AssetRef<Image>` authored as `("textures/icon.png")`
+/// * the asset gets loaded once the components of the blueprint instance have been transfered & injected,
+///   and the instance is only marked as ready once it is loaded
+/// * use `get()` to get the handle, or register your own asset types with [`BlueprintAssetRefAppExt::register_blueprint_asset_ref`]
+#[derive(Reflect)]
+pub struct AssetRef<T: Asset>(pub String, #[reflect(ignore)] pub Handle<T>);
+
+impl<T: Asset> AssetRef<T> {
+    pub fn new(path: impl Into<String>) -> Self {
+        AssetRef(path.into(), Handle::default())
+    }
+
+    /// the handle of the asset, the default (empty) handle until the instance has loaded it
+    pub fn get(&self) -> &Handle<T> {
+        &self.1
+    }
+}
+
+impl<T: Asset> Default for AssetRef<T> {
+    fn default() -> Self {
+        AssetRef(String::new(), Handle::default())
+    }
+}
+
+impl<T: Asset> Clone for AssetRef<T> {
+    fn clone(&self) -> Self {
+        AssetRef(self.0.clone(), self.1.clone())
+    }
+}
+
+impl<T: Asset> fmt::Debug for AssetRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AssetRef")
+            .field(&self.0)
+            .field(&self.1)
+            .finish()
+    }
+}
+
+/// loads the asset of an [`AssetRef`], regardless of its asset type
+#[reflect_trait]
+pub trait LoadAssetRef {
+    /// loads the asset (if the path is not empty) & stores its handle, returns the path & the (untyped) handle
+    fn load(&mut self, asset_server: &AssetServer) -> Option<(String, UntypedHandle)>;
+}
+
+impl<T: Asset> LoadAssetRef for AssetRef<T> {
+    fn load(&mut self, asset_server: &AssetServer) -> Option<(String, UntypedHandle)> {
+        if self.0.is_empty() {
+            return None;
+        }
+        self.1 = asset_server.load(self.0.clone());
+        Some((self.0.clone(), self.1.clone().untyped()))
+    }
+}
+
+/// extension trait for [`App`], to use [`AssetRef`]s of more asset types in components
+pub trait BlueprintAssetRefAppExt {
+    /// makes `AssetRef<T>` fields of components load their assets (ie `app.register_blueprint_asset_ref::<AudioSource>()`)
+    fn register_blueprint_asset_ref<T: Asset>(&mut self) -> &mut Self;
+}
+
+impl BlueprintAssetRefAppExt for App {
+    fn register_blueprint_asset_ref<T: Asset>(&mut self) -> &mut Self {
+        self.register_type::<AssetRef<T>>()
+            .register_type_data::<AssetRef<T>, ReflectLoadAssetRef>()
+    }
+}
+
+/// the assets referenced by the components of a blueprint instance (with [`AssetRef`]s) that are not loaded yet:
+/// the instance only gets finalized once this is empty
+#[derive(Component, Debug, Default)]
+pub(crate) struct ComponentAssetsToLoad(pub(crate) Vec<(String, UntypedAssetId)>);
+
+fn load_asset_refs(
+    value: &mut dyn Reflect,
+    type_registry: &TypeRegistry,
+    asset_server: &AssetServer,
+    loaded: &mut Vec<(String, UntypedHandle)>,
+) {
+    if let Some(asset_ref) = type_registry
+        .get_type_data::<ReflectLoadAssetRef>(value.as_reflect().type_id())
+        .and_then(|reflect_load| reflect_load.get_mut(value))
+    {
+        loaded.extend(asset_ref.load(asset_server));
+        return;
+    }
+    match value.reflect_mut() {
+        ReflectMut::Struct(value) => {
+            for index in 0..value.field_len() {
+                if let Some(field) = value.field_at_mut(index) {
+                    load_asset_refs(field, type_registry, asset_server, loaded);
+                }
+            }
+        }
+        ReflectMut::TupleStruct(value) => {
+            for index in 0..value.field_len() {
+                if let Some(field) = value.field_mut(index) {
+                    load_asset_refs(field, type_registry, asset_server, loaded);
+                }
+            }
+        }
+        ReflectMut::Tuple(value) => {
+            for index in 0..value.field_len() {
+                if let Some(field) = value.field_mut(index) {
+                    load_asset_refs(field, type_registry, asset_server, loaded);
+                }
+            }
+        }
+        ReflectMut::List(value) => {
+            for index in 0..value.len() {
+                if let Some(item) = value.get_mut(index) {
+                    load_asset_refs(item, type_registry, asset_server, loaded);
+                }
+            }
+        }
+        ReflectMut::Array(value) => {
+            for index in 0..value.len() {
+                if let Some(item) = value.get_mut(index) {
+                    load_asset_refs(item, type_registry, asset_server, loaded);
+                }
+            }
+        }
+        ReflectMut::Map(value) => {
+            for index in 0..value.len() {
+                if let Some((_, item)) = value.get_at_mut(index) {
+                    load_asset_refs(item, type_registry, asset_server, loaded);
+                }
+            }
+        }
+        ReflectMut::Enum(value) => {
+            for index in 0..value.field_len() {
+                if let Some(field) = value.field_at_mut(index) {
+                    load_asset_refs(field, type_registry, asset_server, loaded);
+                }
+            }
+        }
+        ReflectMut::Value(_) => {}
+    }
+}
+
+/// loads the assets referenced by [`AssetRef`] fields in the components of blueprint instances (& their children),
+/// once their components have been transfered & injected from their gltf extras
+pub(crate) fn load_component_asset_refs(world: &mut World) {
+    let instances: Vec<Entity> = world
+        .query_filtered::<Entity, (
+            With<BlueprintReadyForFinalizing>,
+            Without<ComponentAssetsToLoad>,
+        )>()
+        .iter(world)
+        .collect();
+    if instances.is_empty() {
+        return;
+    }
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = type_registry.read();
+    let asset_server = world.resource::<AssetServer>().clone();
+
+    for instance in instances {
+        let mut entities = vec![instance];
+        let mut stack = vec![instance];
+        while let Some(entity) = stack.pop() {
+            if let Some(children) = world.get::<Children>(entity) {
+                entities.extend(children.iter());
+                stack.extend(children.iter());
+            }
+        }
+
+        let mut loaded: Vec<(String, UntypedHandle)> = vec![];
+        for entity in entities {
+            let type_ids: Vec<_> = world
+                .entity(entity)
+                .archetype()
+                .components()
+                .filter_map(|component_id| world.components().get_info(component_id))
+                .filter_map(|info| info.type_id())
+                .collect();
+            for type_id in type_ids {
+                let Some(reflect_component) = type_registry
+                    .get(type_id)
+                    .and_then(|registration| registration.data::<ReflectComponent>())
+                else {
+                    continue;
+                };
+                let mut entity_mut = world.entity_mut(entity);
+                if let Some(mut component) = reflect_component.reflect_mut(&mut entity_mut) {
+                    // only flag the component as changed if it actually contains asset references
+                    let mut component_loaded = vec![];
+                    load_asset_refs(
+                        component.bypass_change_detection(),
+                        &type_registry,
+                        &asset_server,
+                        &mut component_loaded,
+                    );
+                    if !component_loaded.is_empty() {
+                        component.set_changed();
+                        loaded.extend(component_loaded);
+                    }
+                }
+            }
+        }
+
+        let to_load: Vec<(String, UntypedAssetId)> = loaded
+            .iter()
+            .map(|(path, handle)| (path.clone(), handle.id()))
+            .collect();
+        let mut instance_mut = world.entity_mut(instance);
+        // keep track of the assets alongside the other ones of the instance (for hot reload, cache budgets etc)
+        if !loaded.is_empty() {
+            if let Some(mut instance_assets) = instance_mut.get_mut::<BlueprintInstanceAssets>() {
+                for (path, handle) in loaded {
+                    if !instance_assets.0.iter().any(|asset| asset.path == path) {
+                        instance_assets.0.push(BlueprintAssetInfo {
+                            kind: BlueprintAssetKind::from_path(&path),
+                            path,
+                            handle,
+                        });
+                    }
+                }
+            }
+        }
+        instance_mut.insert(ComponentAssetsToLoad(to_load));
+    }
+}
+
+/// tracks the loading of the assets referenced by the components of blueprint instances,
+/// handling failures the same way as for the other assets of the blueprints (see `strictness` in the plugin)
+pub(crate) fn check_component_assets_loaded(
+    mut instances: Query<(
+        Entity,
+        &BlueprintName,
+        &mut ComponentAssetsToLoad,
+        Option<&Children>,
+        Option<&OriginalChildren>,
+    )>,
+    asset_server: Res<AssetServer>,
+    blueprints_config: Res<BluePrintsConfig>,
+    mut commands: Commands,
+    mut blueprint_events: EventWriter<BlueprintEvent>,
+) {
+    for (entity, blueprint_name, mut assets_to_load, children, original_children) in
+        instances.iter_mut()
+    {
+        if assets_to_load.0.is_empty() {
+            continue;
+        }
+        let mut failed_asset: Option<String> = None;
+        assets_to_load.0.retain(|(path, id)| {
+            if asset_server.get_load_state(*id) != Some(LoadState::Failed) {
+                return !asset_server.is_loaded_with_dependencies(*id);
+            }
+            match blueprints_config.strictness {
+                Strictness::Panic => panic!(
+                    "failed to load asset {} referenced by a component of blueprint {} for entity {:?}",
+                    path, blueprint_name.0, entity
+                ),
+                Strictness::SkipAndWarn => warn!(
+                    "failed to load asset {} referenced by a component of blueprint {} for entity {:?}, skipping it",
+                    path, blueprint_name.0, entity
+                ),
+                Strictness::FailInstance => failed_asset = Some(path.clone()),
+            }
+            blueprint_events.send(BlueprintEvent::AssetLoadFailed {
+                entity,
+                blueprint_name: blueprint_name.0.clone(),
+                asset_path: path.clone(),
+            });
+            false
+        });
+        if let Some(path) = failed_asset {
+            teardown_blueprint_instance(&mut commands, entity, children, original_children);
+            commands
+                .entity(entity)
+                .remove::<(BlueprintReadyForFinalizing, ComponentAssetsToLoad)>();
+            fail_blueprint_spawn(
+                &mut commands,
+                &mut blueprint_events,
+                entity,
+                BlueprintSpawnError::AssetLoadFailed { path: path.into() },
+            );
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+use crate::{BlueprintEvent, BlueprintSpawnState};
+
+/// attaches a blueprint instance to a named child node or bone (the "socket", ie `hand_R`) of another entity (usually another blueprint instance)
+/// once both are fully spawned: for weapons, hats, vehicle parts etc
+/// * the transform of the instance becomes its offset relative to the socket
+/// * the component is removed once the instance is attached, and a `BlueprintEvent::Attached` event is sent
+///   (or a `BlueprintEvent::AttachmentFailed` one if the target or its socket do not exist)
+#[derive(Component, Debug, Clone)]
+pub struct AttachTo {
+    pub target: Entity,
+    pub socket: String,
+}
+
+fn is_ready(spawn_state: Option<&BlueprintSpawnState>) -> bool {
+    // entities that are not blueprint instances are always ready
+    !matches!(
+        spawn_state,
+        Some(
+            BlueprintSpawnState::LoadingAssets
+                | BlueprintSpawnState::SpawningScene
+                | BlueprintSpawnState::PostProcessing
+        )
+    )
+}
+
+pub(crate) fn attach_to_sockets(
+    attachments: Query<(Entity, &AttachTo, Option<&BlueprintSpawnState>)>,
+    targets: Query<Option<&BlueprintSpawnState>>,
+    names: Query<&Name>,
+    all_children: Query<&Children>,
+    mut commands: Commands,
+    mut blueprint_events: EventWriter<BlueprintEvent>,
+) {
+    for (entity, attach_to, spawn_state) in attachments.iter() {
+        let Ok(target_spawn_state) = targets.get(attach_to.target) else {
+            warn!(
+                "cannot attach {:?}: target {:?} does not exist",
+                entity, attach_to.target
+            );
+            commands.entity(entity).remove::<AttachTo>();
+            blueprint_events.send(BlueprintEvent::AttachmentFailed {
+                entity,
+                target: attach_to.target,
+                socket: attach_to.socket.clone(),
+            });
+            continue;
+        };
+        if !is_ready(spawn_state) || !is_ready(target_spawn_state) {
+            continue;
+        }
+
+        let socket = all_children
+            .iter_descendants(attach_to.target)
+            .find(|descendant| {
+                names
+                    .get(*descendant)
+                    .is_ok_and(|name| name.as_str() == attach_to.socket)
+            });
+        commands.entity(entity).remove::<AttachTo>();
+        match socket {
+            Some(socket) => {
+                debug!(
+                    "attaching {:?} to socket {} of {:?}",
+                    entity, attach_to.socket, attach_to.target
+                );
+                commands.entity(entity).set_parent(socket);
+                blueprint_events.send(BlueprintEvent::Attached {
+                    entity,
+                    target: attach_to.target,
+                    socket: attach_to.socket.clone(),
+                });
+            }
+            None => {
+                warn!(
+                    "cannot attach {:?}: no socket named {} in {:?}",
+                    entity, attach_to.socket, attach_to.target
+                );
+                blueprint_events.send(BlueprintEvent::AttachmentFailed {
+                    entity,
+                    target: attach_to.target,
+                    socket: attach_to.socket.clone(),
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use bevy::{
+    audio::{PlaybackMode, SpatialScale, Volume},
+    prelude::*,
+};
+
+use crate::AssetRef;
+
+/// a sound emitter placed in Blender (ie on an empty where a speaker would be): the sound is loaded with the other assets of the blueprint instance,
+/// and played (spatially by default, a `SpatialListener` is needed for that) once it is loaded
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct AudioEmitter {
+    /// path of the sound, relative to the assets folder (ie "audio/waterfall.ogg")
+    pub sound: AssetRef<AudioSource>,
+    pub volume: f32,
+    /// loop the sound instead of playing it once
+    pub looped: bool,
+    /// play the sound from the position of the entity, instead of at the same volume everywhere
+    pub spatial: bool,
+    /// scale of the distances used for spatial audio (ie to attenuate the sound faster), uses the global default if None
+    pub spatial_scale: Option<f32>,
+    /// start paused, to be started from code
+    pub paused: bool,
+}
+
+impl Default for AudioEmitter {
+    fn default() -> Self {
+        AudioEmitter {
+            sound: AssetRef::default(),
+            volume: 1.0,
+            looped: false,
+            spatial: true,
+            spatial_scale: None,
+            paused: false,
+        }
+    }
+}
+
+/// starts the [`AudioEmitter`]s once their sound is loaded
+pub(crate) fn spawn_audio_emitters(
+    emitters: Query<(Entity, &AudioEmitter), Without<Handle<AudioSource>>>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    for (entity, emitter) in emitters.iter() {
+        let sound = emitter.sound.get();
+        if !asset_server.is_loaded_with_dependencies(sound) {
+            continue;
+        }
+        commands.entity(entity).insert(AudioBundle {
+            source: sound.clone(),
+            settings: PlaybackSettings {
+                mode: if emitter.looped {
+                    PlaybackMode::Loop
+                } else {
+                    PlaybackMode::Once
+                },
+                volume: Volume::new(emitter.volume),
+                paused: emitter.paused,
+                spatial: emitter.spatial,
+                spatial_scale: emitter.spatial_scale.map(SpatialScale::new),
+                ..Default::default()
+            },
+        });
+    }
+}
@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+
+use crate::{
+    AddToGameWorld, BlueprintEvent, BlueprintSpawnState, HideUntilReady, Library,
+    SpawnBlueprintCommandsExt,
+};
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+/// component requesting to spawn many instances of the same blueprint at once (scattering rocks/trees, waves of enemies etc),
+/// one per transform: the assets are loaded only once & shared by all instances
+/// * the entity holding it is despawned once all the instances are spawned, and a `BlueprintEvent::BatchReady` event is sent
+/// * [`Library`], [`AddToGameWorld`] & [`HideUntilReady`] components on that entity are applied to all the instances
+pub struct SpawnBlueprintBatch {
+    pub blueprint: String,
+    pub transforms: Vec<Transform>,
+}
+
+#[derive(Component, Debug)]
+pub(crate) struct BlueprintBatchInstances(pub Vec<Entity>);
+
+/// extension trait for [`Commands`], to spawn many instances of the same blueprint at once
+pub trait SpawnBlueprintBatchCommandsExt {
+    /// spawn one instance of the blueprint with the given name per transform, returns the entity tracking the batch
+    ///
+    /// ```ignore
+    /// let batch = commands.spawn_blueprint_batch("Rock", rock_transforms);
+    /// commands.entity(batch).insert(AddToGameWorld);
+    /// ```
+    fn spawn_blueprint_batch(
+        &mut self,
+        name: impl Into<String>,
+        transforms: Vec<Transform>,
+    ) -> Entity;
+}
+
+impl<'w, 's> SpawnBlueprintBatchCommandsExt for Commands<'w, 's> {
+    fn spawn_blueprint_batch(
+        &mut self,
+        name: impl Into<String>,
+        transforms: Vec<Transform>,
+    ) -> Entity {
+        self.spawn((
+            Name::from("blueprint_batch"),
+            SpawnBlueprintBatch {
+                blueprint: name.into(),
+                transforms,
+            },
+        ))
+        .id()
+    }
+}
+
+pub(crate) fn spawn_blueprint_batches(
+    added_batches: Query<
+        (
+            Entity,
+            &SpawnBlueprintBatch,
+            Option<&Library>,
+            Option<&AddToGameWorld>,
+            Option<&HideUntilReady>,
+        ),
+        Added<SpawnBlueprintBatch>,
+    >,
+    mut commands: Commands,
+) {
+    for (entity, batch, library, add_to_world, hide_until_ready) in added_batches.iter() {
+        debug!(
+            "spawning a batch of {} instances of blueprint {}",
+            batch.transforms.len(),
+            batch.blueprint
+        );
+        let instances: Vec<Entity> = batch
+            .transforms
+            .iter()
+            .map(|transform| {
+                let mut instance = commands
+                    .spawn_blueprint(batch.blueprint.clone())
+                    .at(*transform);
+                if let Some(library) = library {
+                    instance = instance.library(library.0.clone());
+                }
+                if add_to_world.is_some() {
+                    instance = instance.add_to_world();
+                }
+                if hide_until_ready.is_some() {
+                    instance = instance.hide_until_ready();
+                }
+                instance.spawn()
+            })
+            .collect();
+        commands
+            .entity(entity)
+            .insert(BlueprintBatchInstances(instances));
+    }
+}
+
+/// sends a single `BlueprintEvent::BatchReady` event once all the instances of a batch are done spawning (successfully or not)
+pub(crate) fn check_blueprint_batches(
+    batches: Query<(Entity, &SpawnBlueprintBatch, &BlueprintBatchInstances)>,
+    spawn_states: Query<&BlueprintSpawnState>,
+    mut commands: Commands,
+    mut blueprint_events: EventWriter<BlueprintEvent>,
+) {
+    for (entity, batch, instances) in batches.iter() {
+        // despawned instances do not hold back the batch
+        let done = instances
+            .0
+            .iter()
+            .all(|instance| match spawn_states.get(*instance) {
+                Ok(state) => matches!(
+                    state,
+                    BlueprintSpawnState::Ready | BlueprintSpawnState::Failed
+                ),
+                Err(_) => true,
+            });
+        if done {
+            blueprint_events.send(BlueprintEvent::BatchReady {
+                batch: entity,
+                blueprint_name: batch.blueprint.clone(),
+                instances: instances.0.clone(),
+            });
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! offline validator for blueprints, to run in a build pipeline before packaging:
+//! checks the components of all the gltf files in a folder against the registry exported with `bevy_registry_export`,
+//! as well as the blueprints they reference & the external assets they use.
+//!
+//! usage: `validate-blueprints <registry.json> <folder> [--library <folder>] [--format glb|gltf] [--legacy]`
+//! - `--library`: the library folder of the blueprints referenced by others (defaults to `<folder>`)
+//! - `--format`: extension of the blueprint files (defaults to glb)
+//! - `--legacy`: the components use the simplified (legacy) syntax, only their types are checked
+//!
+//! exits with an error code if any issue was found
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use ron::Value as RonValue;
+use serde_json::{Map, Value};
+
+struct Options {
+    registry: PathBuf,
+    folder: PathBuf,
+    library: PathBuf,
+    format: String,
+    legacy: bool,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut positional = vec![];
+    let mut library = None;
+    let mut format = "glb".to_string();
+    let mut legacy = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--library" => {
+                library = Some(PathBuf::from(args.next().ok_or("missing --library value")?));
+            }
+            "--format" => format = args.next().ok_or("missing --format value")?,
+            "--legacy" => legacy = true,
+            _ => positional.push(PathBuf::from(arg)),
+        }
+    }
+    let [registry, folder] = <[PathBuf; 2]>::try_from(positional).map_err(|_| {
+        "usage: validate-blueprints <registry.json> <folder> [--library <folder>] [--format glb|gltf] [--legacy]".to_string()
+    })?;
+    Ok(Options {
+        library: library.unwrap_or_else(|| folder.clone()),
+        registry,
+        folder,
+        format,
+        legacy,
+    })
+}
+
+/// the json chunk of a glb file, or the content of a gltf file
+fn read_gltf_json(path: &Path) -> Result<Value, String> {
+    let bytes = fs::read(path).map_err(|error| error.to_string())?;
+    let json = if bytes.starts_with(b"glTF") {
+        // header (magic, version, length), followed by the json chunk (length, type, data)
+        let chunk_length = bytes
+            .get(12..16)
+            .map(|length| u32::from_le_bytes(length.try_into().unwrap()) as usize)
+            .ok_or("truncated glb file")?;
+        bytes
+            .get(20..20 + chunk_length)
+            .ok_or("truncated glb file")?
+            .to_vec()
+    } else {
+        bytes
+    };
+    serde_json::from_slice(&json).map_err(|error| error.to_string())
+}
+
+/// the schemas of the registry, keyed by short type name
+fn read_registry(path: &Path) -> Result<HashMap<String, Value>, String> {
+    let registry: Value =
+        serde_json::from_str(&fs::read_to_string(path).map_err(|error| error.to_string())?)
+            .map_err(|error| error.to_string())?;
+    let definitions = registry
+        .get("$defs")
+        .and_then(Value::as_object)
+        .ok_or("no $defs in registry")?;
+    Ok(definitions
+        .values()
+        .filter_map(|schema| {
+            let short_name = schema.get("short_name")?.as_str()?;
+            Some((short_name.to_string(), schema.clone()))
+        })
+        .collect())
+}
+
+fn capitalize_first_letter(s: &str) -> String {
+    s[0..1].to_uppercase() + &s[1..]
+}
+
+fn string_list(schema: &Value, key: &str) -> Vec<String> {
+    schema
+        .get(key)
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// checks the (ron) value of a component against its schema
+fn check_value(value: &RonValue, schema: &Value) -> Vec<String> {
+    let mut issues = vec![];
+    match schema.get("typeInfo").and_then(Value::as_str) {
+        Some("Struct") => {
+            let required = string_list(schema, "required");
+            let properties = schema.get("properties").and_then(Value::as_object);
+            match value {
+                RonValue::Map(map) => {
+                    let fields: HashSet<String> = map
+                        .keys()
+                        .filter_map(|key| match key {
+                            RonValue::String(key) => Some(key.clone()),
+                            _ => None,
+                        })
+                        .collect();
+                    for field in required.iter().filter(|field| !fields.contains(*field)) {
+                        issues.push(format!("missing field {}", field));
+                    }
+                    if let Some(properties) = properties {
+                        for field in fields
+                            .iter()
+                            .filter(|field| !properties.contains_key(*field))
+                        {
+                            issues.push(format!("unknown field {}", field));
+                        }
+                    }
+                }
+                RonValue::Unit if required.is_empty() => {}
+                _ => issues.push("expected a struct".to_string()),
+            }
+        }
+        Some("TupleStruct") => {
+            let expected = schema
+                .get("prefixItems")
+                .and_then(Value::as_array)
+                .map_or(0, Vec::len);
+            match value {
+                RonValue::Seq(items) if items.len() != expected => issues.push(format!(
+                    "expected {} fields, found {}",
+                    expected,
+                    items.len()
+                )),
+                RonValue::Map(_) => issues.push("expected a tuple struct".to_string()),
+                _ => {}
+            }
+        }
+        Some("Enum") if schema.get("type").and_then(Value::as_str) == Some("string") => {
+            let variants = string_list(schema, "oneOf");
+            if let RonValue::String(variant) = value {
+                if !variants.contains(variant) {
+                    issues.push(format!("unknown variant {}", variant));
+                }
+            }
+        }
+        _ => {}
+    }
+    issues
+}
+
+/// names of the blueprints referenced by a component (`BlueprintName` & `BlueprintsList`)
+fn blueprint_references(type_name: &str, value: &RonValue) -> Vec<String> {
+    // tuple structs with a single field are parsed either as a sequence or as the field itself
+    let inner = match value {
+        RonValue::Seq(items) if items.len() == 1 => &items[0],
+        _ => value,
+    };
+    match (type_name, inner) {
+        ("BlueprintName", RonValue::String(name)) => vec![name.clone()],
+        ("BlueprintsList", RonValue::Map(map)) => map
+            .keys()
+            .filter_map(|key| match key {
+                RonValue::String(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn validate_extras(
+    extras: &Map<String, Value>,
+    registry: &HashMap<String, Value>,
+    options: &Options,
+    issues: &mut Vec<String>,
+) {
+    for (key, value) in extras.iter() {
+        let type_name = capitalize_first_letter(key.replace("component: ", "").trim());
+        let Some(schema) = registry.get(&type_name) else {
+            issues.push(format!("unknown component type {}", type_name));
+            continue;
+        };
+        if options.legacy {
+            continue;
+        }
+        let Some(ron_string) = value.as_str() else {
+            continue;
+        };
+        let value: RonValue = match ron::from_str(ron_string) {
+            Ok(value) => value,
+            Err(error) => {
+                issues.push(format!(
+                    "{}: {:?} will not deserialize: {}",
+                    type_name, ron_string, error
+                ));
+                continue;
+            }
+        };
+        for issue in check_value(&value, schema) {
+            issues.push(format!("{}: {:?}: {}", type_name, ron_string, issue));
+        }
+        for blueprint_name in blueprint_references(&type_name, &value) {
+            let blueprint_path = options
+                .library
+                .join(format!("{}.{}", blueprint_name, options.format));
+            if !blueprint_path.exists() {
+                issues.push(format!(
+                    "unknown blueprint {} ({} does not exist)",
+                    blueprint_name,
+                    blueprint_path.display()
+                ));
+            }
+        }
+    }
+}
+
+/// all the issues of a gltf file, grouped by object name
+fn validate_file(
+    path: &Path,
+    registry: &HashMap<String, Value>,
+    options: &Options,
+) -> Vec<(String, Vec<String>)> {
+    let gltf = match read_gltf_json(path) {
+        Ok(gltf) => gltf,
+        Err(error) => return vec![("file".to_string(), vec![error])],
+    };
+    let mut objects = vec![];
+
+    for collection in ["scenes", "nodes", "meshes", "materials"] {
+        let Some(items) = gltf.get(collection).and_then(Value::as_array) else {
+            continue;
+        };
+        for (index, item) in items.iter().enumerate() {
+            let Some(extras) = item.get("extras").and_then(Value::as_object) else {
+                continue;
+            };
+            let mut issues = vec![];
+            validate_extras(extras, registry, options, &mut issues);
+            if !issues.is_empty() {
+                let name = item
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .map_or_else(|| format!("{}[{}]", collection, index), str::to_string);
+                objects.push((name, issues));
+            }
+        }
+    }
+
+    // external files (textures, binary buffers) must be present next to the gltf file
+    let folder = path.parent().unwrap_or(Path::new(""));
+    let mut missing_assets = vec![];
+    for collection in ["images", "buffers"] {
+        let uris = gltf
+            .get(collection)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|item| item.get("uri").and_then(Value::as_str));
+        for uri in uris.filter(|uri| !uri.starts_with("data:")) {
+            if !folder.join(uri).exists() {
+                missing_assets.push(format!("missing asset {}", uri));
+            }
+        }
+    }
+    if !missing_assets.is_empty() {
+        objects.push(("assets".to_string(), missing_assets));
+    }
+    objects
+}
+
+fn gltf_files(folder: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(folder) else {
+        return;
+    };
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        if path.is_dir() {
+            gltf_files(&path, files);
+        } else if matches!(
+            path.extension().and_then(|extension| extension.to_str()),
+            Some("glb" | "gltf")
+        ) {
+            files.push(path);
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let registry = match read_registry(&options.registry) {
+        Ok(registry) => registry,
+        Err(error) => {
+            eprintln!(
+                "could not read registry {}: {}",
+                options.registry.display(),
+                error
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut files = vec![];
+    gltf_files(&options.folder, &mut files);
+    files.sort();
+
+    let mut invalid_files = 0;
+    for file in files.iter() {
+        let objects = validate_file(file, &registry, &options);
+        if objects.is_empty() {
+            println!("{}: ok", file.display());
+            continue;
+        }
+        invalid_files += 1;
+        println!("{}:", file.display());
+        for (object_name, issues) in objects {
+            println!("  {}:", object_name);
+            for issue in issues {
+                println!("    - {}", issue);
+            }
+        }
+    }
+
+    println!(
+        "{} files checked, {} with issues",
+        files.len(),
+        invalid_files
+    );
+    if invalid_files > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
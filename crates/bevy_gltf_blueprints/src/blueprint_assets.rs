@@ -0,0 +1,58 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+
+/// the kind of an asset used by a blueprint, based on its file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect)]
+pub enum BlueprintAssetKind {
+    Gltf,
+    Texture,
+    Audio,
+    #[default]
+    Other,
+}
+
+impl BlueprintAssetKind {
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let extension = path
+            .as_ref()
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "glb" | "gltf" => BlueprintAssetKind::Gltf,
+            "png" | "jpg" | "jpeg" | "ktx2" | "dds" | "tga" | "hdr" | "exr" | "basis" => {
+                BlueprintAssetKind::Texture
+            }
+            "ogg" | "wav" | "mp3" | "flac" => BlueprintAssetKind::Audio,
+            _ => BlueprintAssetKind::Other,
+        }
+    }
+}
+
+/// an asset used by a blueprint instance
+#[derive(Debug, Clone)]
+pub struct BlueprintAssetInfo {
+    pub path: String,
+    pub kind: BlueprintAssetKind,
+    pub handle: UntypedHandle,
+}
+
+/// all the assets used by a blueprint instance (its gltf file & the ones of its nested blueprints etc), kept around once it is ready,
+/// so that gameplay code can grab them (ie all audio handles declared by the blueprint)
+#[derive(Component, Debug, Clone, Default)]
+pub struct BlueprintInstanceAssets(pub Vec<BlueprintAssetInfo>);
+
+impl BlueprintInstanceAssets {
+    /// all the assets of the given kind
+    pub fn of_kind(&self, kind: BlueprintAssetKind) -> impl Iterator<Item = &BlueprintAssetInfo> {
+        self.0.iter().filter(move |asset| asset.kind == kind)
+    }
+
+    /// handles of all the assets of the given type
+    pub fn typed<A: Asset>(&self) -> impl Iterator<Item = Handle<A>> + '_ {
+        self.0
+            .iter()
+            .filter_map(|asset| asset.handle.clone().try_typed::<A>().ok())
+    }
+}
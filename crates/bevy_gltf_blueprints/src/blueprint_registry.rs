@@ -0,0 +1,285 @@
+use std::path::{Path, PathBuf};
+
+use bevy::{
+    asset::LoadedFolder,
+    ecs::system::Command,
+    gltf::{Gltf, GltfExtras},
+    prelude::*,
+    render::primitives::Aabb,
+    utils::HashMap,
+};
+use ron::Value;
+
+use crate::{BluePrintsConfig, BlueprintAabbCache, BlueprintName, Library, SpawnHere};
+
+/// tags of a blueprint (ie "enemy", "prop", "vegetation"), added to its root object in Blender:
+/// listed in the [`BlueprintRegistry`], and added to the instances of the blueprint like any other component
+#[derive(Component, Reflect, Default, Debug, Clone)]
+#[reflect(Component)]
+pub struct BlueprintTags(pub Vec<String>);
+
+/// what is known about a blueprint of the [`BlueprintRegistry`], without spawning it
+#[derive(Debug, Clone)]
+pub struct BlueprintMetadata {
+    pub name: String,
+    /// path of the gltf file of the blueprint, relative to the assets folder
+    pub path: PathBuf,
+    pub handle: Handle<Gltf>,
+    /// the named scenes of the gltf file (scenes & variants of the blueprint)
+    pub scenes: Vec<String>,
+    /// names of the blueprints nested inside this one
+    pub nested_blueprints: Vec<String>,
+    /// see [`BlueprintTags`]
+    pub tags: Vec<String>,
+    /// the aabb of the blueprint, known once an instance of it has been spawned with `aabbs` enabled in the plugin
+    pub aabb: Option<Aabb>,
+}
+
+impl BlueprintMetadata {
+    /// the [`Library`] to spawn this blueprint with, if it is not in the default library folder
+    pub fn library(&self) -> Library {
+        Library(self.path.parent().unwrap_or(Path::new("")).to_path_buf())
+    }
+}
+
+/// all the blueprints found in the scanned folders (see `scan_blueprint_folders` in the plugin & [`ScanBlueprintsCommandsExt`]),
+/// by name: for spawning by name, listing the available blueprints in editors, checking blueprint names before loading them etc
+/// * scanning loads all the assets of the folders, and the registry keeps the blueprints loaded
+#[derive(Resource, Debug, Default)]
+pub struct BlueprintRegistry {
+    pub(crate) blueprints: HashMap<String, BlueprintMetadata>,
+    pub(crate) scanning: Vec<Handle<LoadedFolder>>,
+}
+
+impl BlueprintRegistry {
+    pub fn get(&self, name: &str) -> Option<&BlueprintMetadata> {
+        self.blueprints.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.blueprints.contains_key(name)
+    }
+
+    /// the blueprint whose gltf file is at the given path
+    pub fn get_by_path(&self, path: &Path) -> Option<&BlueprintMetadata> {
+        self.blueprints
+            .values()
+            .find(|blueprint| blueprint.path == path)
+    }
+
+    /// names of all the blueprints, sorted
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.blueprints.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &BlueprintMetadata> {
+        self.blueprints.values()
+    }
+
+    /// all the blueprints with the given tag
+    pub fn with_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a BlueprintMetadata> {
+        self.blueprints
+            .values()
+            .filter(move |blueprint| blueprint.tags.iter().any(|t| t == tag))
+    }
+
+    /// true while some folders are still being scanned
+    pub fn is_scanning(&self) -> bool {
+        !self.scanning.is_empty()
+    }
+}
+
+/// scans a folder (relative to the assets folder, subfolders included) for blueprints, and adds them to the [`BlueprintRegistry`]
+pub struct ScanBlueprintFolder {
+    pub folder: PathBuf,
+}
+
+impl Command for ScanBlueprintFolder {
+    fn apply(self, world: &mut World) {
+        debug!("scanning {:?} for blueprints", self.folder);
+        let folder = world.resource::<AssetServer>().load_folder(self.folder);
+        world
+            .resource_mut::<BlueprintRegistry>()
+            .scanning
+            .push(folder);
+    }
+}
+
+/// extension trait for [`Commands`], to (re)scan folders for blueprints on demand
+pub trait ScanBlueprintsCommandsExt {
+    fn scan_blueprint_folder(&mut self, folder: impl Into<PathBuf>);
+}
+
+impl ScanBlueprintsCommandsExt for Commands<'_, '_> {
+    fn scan_blueprint_folder(&mut self, folder: impl Into<PathBuf>) {
+        self.add(ScanBlueprintFolder {
+            folder: folder.into(),
+        });
+    }
+}
+
+/// scans the `scan_blueprint_folders` of the plugin at startup
+pub(crate) fn scan_blueprint_folders(
+    blueprints_config: Res<BluePrintsConfig>,
+    mut commands: Commands,
+) {
+    for folder in blueprints_config.scan_blueprint_folders.iter() {
+        commands.scan_blueprint_folder(folder.clone());
+    }
+}
+
+/// the string values of a custom property, ie `(["enemy", "boss"])`
+fn strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(value) => vec![value.clone()],
+        Value::Seq(values) => values.iter().flat_map(strings).collect(),
+        Value::Option(Some(value)) => strings(value),
+        _ => vec![],
+    }
+}
+
+/// the value of a component in gltf extras, ie `"([\"enemy\"])"`
+fn component_value(value: &Value) -> Value {
+    match value {
+        Value::String(value) => {
+            ron::from_str(value).unwrap_or_else(|_| Value::String(value.clone()))
+        }
+        _ => value.clone(),
+    }
+}
+
+fn blueprint_metadata(
+    path: PathBuf,
+    handle: Handle<Gltf>,
+    gltf: &Gltf,
+    scenes: &Assets<Scene>,
+) -> BlueprintMetadata {
+    let mut scene_names: Vec<String> = gltf.named_scenes.keys().cloned().collect();
+    scene_names.sort();
+    let mut nested_blueprints = vec![];
+    let mut tags = vec![];
+    for scene in gltf.scenes.iter().filter_map(|scene| scenes.get(scene)) {
+        for entity in scene.world.iter_entities() {
+            let Some(extras) = entity.get::<GltfExtras>() else {
+                continue;
+            };
+            let properties: HashMap<String, Value> =
+                ron::from_str(&extras.value).unwrap_or_default();
+            if let Some(name) = properties.get("BlueprintName") {
+                nested_blueprints.extend(strings(&component_value(name)));
+            }
+            // tags of the root objects of the blueprint only, not of the objects inside it
+            let is_root_object = entity
+                .get::<Parent>()
+                .is_some_and(|parent| !scene.world.entity(parent.get()).contains::<Parent>());
+            if let Some(value) = properties.get("BlueprintTags").filter(|_| is_root_object) {
+                tags.extend(strings(&component_value(value)));
+            }
+        }
+    }
+    nested_blueprints.sort();
+    nested_blueprints.dedup();
+    tags.sort();
+    tags.dedup();
+
+    BlueprintMetadata {
+        name: path
+            .file_stem()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        path,
+        handle,
+        scenes: scene_names,
+        nested_blueprints,
+        tags,
+        aabb: None,
+    }
+}
+
+/// adds the blueprints of the scanned folders to the [`BlueprintRegistry`] once they are loaded, updates them when they change on disk,
+/// and keeps their aabbs in sync with the aabb cache
+pub(crate) fn update_blueprint_registry(
+    mut gltf_events: EventReader<AssetEvent<Gltf>>,
+    asset_server: Res<AssetServer>,
+    loaded_folders: Res<Assets<LoadedFolder>>,
+    gltfs: Res<Assets<Gltf>>,
+    scenes: Res<Assets<Scene>>,
+    aabb_cache: Res<BlueprintAabbCache>,
+    mut registry: ResMut<BlueprintRegistry>,
+) {
+    let mut found: Vec<Handle<Gltf>> = vec![];
+    if registry.is_scanning() {
+        registry.scanning.retain(|folder| {
+            if !asset_server.is_loaded_with_dependencies(folder.id()) {
+                return true;
+            }
+            if let Some(loaded_folder) = loaded_folders.get(folder) {
+                found.extend(
+                    loaded_folder
+                        .handles
+                        .iter()
+                        .filter_map(|handle| handle.clone().try_typed::<Gltf>().ok()),
+                );
+            }
+            false
+        });
+    }
+    for event in gltf_events.read() {
+        if let AssetEvent::Modified { id } = event {
+            if let Some(blueprint) = registry
+                .blueprints
+                .values()
+                .find(|blueprint| blueprint.handle.id() == *id)
+            {
+                found.push(blueprint.handle.clone());
+            }
+        }
+    }
+
+    for handle in found {
+        let (Some(path), Some(gltf)) = (handle.path(), gltfs.get(&handle)) else {
+            continue;
+        };
+        let metadata = blueprint_metadata(path.path().to_path_buf(), handle.clone(), gltf, &scenes);
+        if let Some(existing) = registry.blueprints.get(&metadata.name) {
+            if existing.path != metadata.path {
+                warn!(
+                    "blueprint {} found both in {:?} and {:?}, keeping the latter",
+                    metadata.name, existing.path, metadata.path
+                );
+            }
+        }
+        debug!("registering blueprint {}", metadata.name);
+        registry.blueprints.insert(metadata.name.clone(), metadata);
+    }
+
+    if aabb_cache.is_changed() || registry.is_changed() {
+        let registry = registry.bypass_change_detection();
+        for blueprint in registry.blueprints.values_mut() {
+            blueprint.aabb = aabb_cache.get(&blueprint.path, None);
+        }
+    }
+}
+
+/// warns about blueprint instances whose blueprint is not in the [`BlueprintRegistry`], before trying to load it
+/// (only once the registry has been populated)
+pub(crate) fn check_blueprint_references(
+    added: Query<(Entity, &BlueprintName, Option<&Library>), Added<SpawnHere>>,
+    blueprints_config: Res<BluePrintsConfig>,
+    registry: Res<BlueprintRegistry>,
+) {
+    if registry.blueprints.is_empty() || registry.is_scanning() {
+        return;
+    }
+    for (entity, blueprint_name, library) in added.iter() {
+        let path = blueprints_config.blueprint_path(&blueprint_name.0, library);
+        if registry.get_by_path(&path).is_none() {
+            warn!(
+                "blueprint {} of {:?} is not in the blueprint registry (no such file: {:?})",
+                blueprint_name.0, entity, path
+            );
+        }
+    }
+}
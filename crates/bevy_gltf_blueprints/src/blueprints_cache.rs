@@ -0,0 +1,260 @@
+use std::path::{Path, PathBuf};
+
+use bevy::{
+    gltf::{Gltf, GltfMesh},
+    prelude::*,
+    utils::{HashMap, HashSet, Instant},
+};
+
+use bevy::render::primitives::Aabb;
+
+use crate::{
+    AabbCacheKey, BluePrintsConfig, BlueprintAssetKind, BlueprintInstanceAssets, CachedAabb,
+};
+
+/// cache of the blueprint gltf files, keyed by path:
+/// spawning many instances of the same blueprint shares the same handles instead of requesting them again for each instance
+/// * the cached handles are strong: they are kept until the plugin's `unload_unused_blueprints` grace period (opt-in, None by default)
+/// or `blueprint_cache_budget_mb` evict blueprints that no instance uses anymore (see [`BlueprintAssetRefs`])
+#[derive(Resource, Default, Debug)]
+pub struct BlueprintAssetsCache {
+    pub(crate) gltfs: HashMap<PathBuf, Handle<Gltf>>,
+}
+
+/// cache of the aabbs computed for blueprint instances (see `aabbs` in the plugin), keyed by blueprint path & variant:
+/// the instances spawned after the first one reuse its aabb, until the gltf files it depends on change on disk
+#[derive(Resource, Default, Debug)]
+pub struct BlueprintAabbCache {
+    pub(crate) aabbs: HashMap<AabbCacheKey, CachedAabb>,
+}
+
+impl BlueprintAabbCache {
+    /// the cached aabb of the blueprint at the given path, for the given variant (None for instances without a `BlueprintVariant`)
+    pub fn get(&self, path: &Path, variant: Option<&str>) -> Option<Aabb> {
+        self.aabbs
+            .iter()
+            .find(|(key, _)| key.path == path && key.variant.as_deref() == variant)
+            .map(|(_, cached)| cached.aabb)
+    }
+}
+
+impl BlueprintAssetsCache {
+    /// returns the handle of the blueprint gltf file at the given path, loading it only if it is not cached yet
+    pub fn gltf(&mut self, path: &Path, asset_server: &AssetServer) -> Handle<Gltf> {
+        self.gltfs
+            .entry(path.to_path_buf())
+            .or_insert_with(|| asset_server.load(path.to_path_buf()))
+            .clone()
+    }
+}
+
+/// reference counting of the blueprint gltf files used by the blueprint instances, keyed by path:
+/// once the last instance using a blueprint is despawned, its (cached) strong handle is dropped,
+/// after the `unload_unused_blueprints` grace period of the plugin, freeing up memory
+/// (preloaded blueprints that were never instanced are kept)
+#[derive(Resource, Default, Debug)]
+pub struct BlueprintAssetRefs {
+    pub(crate) counts: HashMap<PathBuf, usize>,
+    pub(crate) instances: HashMap<Entity, Vec<PathBuf>>,
+    pub(crate) unused_since: HashMap<PathBuf, Instant>,
+    /// estimated memory used by each cached blueprint
+    pub(crate) sizes: HashMap<PathBuf, usize>,
+}
+
+impl BlueprintAssetRefs {
+    /// amount of blueprint instances currently using the blueprint gltf file at the given path
+    pub fn count(&self, path: &Path) -> usize {
+        self.counts.get(path).copied().unwrap_or(0)
+    }
+
+    fn add_instance(&mut self, entity: Entity, paths: Vec<PathBuf>) {
+        self.remove_instance(entity);
+        for path in paths.iter() {
+            *self.counts.entry(path.clone()).or_insert(0) += 1;
+            self.unused_since.remove(path);
+        }
+        self.instances.insert(entity, paths);
+    }
+
+    fn remove_instance(&mut self, entity: Entity) {
+        let Some(paths) = self.instances.remove(&entity) else {
+            return;
+        };
+        for path in paths {
+            if let Some(count) = self.counts.get_mut(&path) {
+                *count -= 1;
+                if *count == 0 {
+                    self.counts.remove(&path);
+                    self.unused_since.insert(path, Instant::now());
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn update_blueprint_asset_refs(
+    changed_instances: Query<(Entity, &BlueprintInstanceAssets), Changed<BlueprintInstanceAssets>>,
+    mut removed_instances: RemovedComponents<BlueprintInstanceAssets>,
+    blueprint_instances: Query<(), With<BlueprintInstanceAssets>>,
+    blueprints_config: Res<BluePrintsConfig>,
+    mut asset_refs: ResMut<BlueprintAssetRefs>,
+    mut blueprints_cache: ResMut<BlueprintAssetsCache>,
+) {
+    for entity in removed_instances.read() {
+        if !blueprint_instances.contains(entity) {
+            asset_refs.remove_instance(entity);
+        }
+    }
+    for (entity, instance_assets) in changed_instances.iter() {
+        let paths = instance_assets
+            .of_kind(BlueprintAssetKind::Gltf)
+            .map(|asset| PathBuf::from(&asset.path))
+            .collect();
+        asset_refs.add_instance(entity, paths);
+    }
+
+    let Some(grace_period) = blueprints_config.unload_unused_blueprints else {
+        return;
+    };
+    let asset_refs = &mut *asset_refs;
+    asset_refs.unused_since.retain(|path, unused_since| {
+        if unused_since.elapsed() < grace_period {
+            return true;
+        }
+        debug!("unloading unused blueprint {:?}", path);
+        blueprints_cache.gltfs.remove(path);
+        asset_refs.sizes.remove(path);
+        false
+    });
+}
+
+/// diagnostics about a blueprint gltf file kept in memory, see [`BlueprintAssetRefs::resident_blueprints`]
+#[derive(Debug, Clone)]
+pub struct ResidentBlueprint {
+    pub path: PathBuf,
+    /// amount of blueprint instances using it
+    pub instances: usize,
+    /// rough estimate of the memory used by its meshes & textures, once loaded
+    pub estimated_bytes: Option<usize>,
+    /// when the last instance using it was despawned
+    pub unused_since: Option<Instant>,
+}
+
+impl BlueprintAssetRefs {
+    /// what blueprints are currently kept in memory, with how many instances use them & their estimated size
+    pub fn resident_blueprints(&self) -> Vec<ResidentBlueprint> {
+        let paths: HashSet<&PathBuf> = self.sizes.keys().chain(self.counts.keys()).collect();
+        paths
+            .into_iter()
+            .map(|path| ResidentBlueprint {
+                path: path.clone(),
+                instances: self.count(path),
+                estimated_bytes: self.sizes.get(path).copied(),
+                unused_since: self.unused_since.get(path).copied(),
+            })
+            .collect()
+    }
+
+    /// rough estimate of the memory used by all the blueprints currently kept in memory
+    pub fn estimated_resident_bytes(&self) -> usize {
+        self.sizes.values().sum()
+    }
+}
+
+/// rough estimate of the memory used by the meshes & textures of a gltf file
+fn estimate_gltf_size(
+    gltf: &Gltf,
+    gltf_meshes: &Assets<GltfMesh>,
+    meshes: &Assets<Mesh>,
+    materials: &Assets<StandardMaterial>,
+    images: &Assets<Image>,
+) -> usize {
+    let mut size = 0;
+    for gltf_mesh in gltf.meshes.iter().filter_map(|mesh| gltf_meshes.get(mesh)) {
+        for mesh in gltf_mesh
+            .primitives
+            .iter()
+            .filter_map(|primitive| meshes.get(&primitive.mesh))
+        {
+            size += mesh.count_vertices() * mesh.get_vertex_size() as usize;
+            size += mesh.indices().map_or(0, |indices| indices.len() * 4);
+        }
+    }
+    let mut counted_images = HashSet::new();
+    for material in gltf
+        .materials
+        .iter()
+        .filter_map(|material| materials.get(material))
+    {
+        for image in [
+            &material.base_color_texture,
+            &material.normal_map_texture,
+            &material.metallic_roughness_texture,
+            &material.emissive_texture,
+            &material.occlusion_texture,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if counted_images.insert(image.id()) {
+                size += images.get(image).map_or(0, |image| image.data.len());
+            }
+        }
+    }
+    size
+}
+
+/// keeps the estimated memory used by cached blueprints under the `blueprint_cache_budget_mb` of the plugin,
+/// by unloading the least recently used blueprints that are not used by any instance
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn enforce_blueprint_cache_budget(
+    blueprints_config: Res<BluePrintsConfig>,
+    mut asset_refs: ResMut<BlueprintAssetRefs>,
+    mut blueprints_cache: ResMut<BlueprintAssetsCache>,
+    gltfs: Res<Assets<Gltf>>,
+    gltf_meshes: Res<Assets<GltfMesh>>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    images: Res<Assets<Image>>,
+) {
+    let asset_refs = &mut *asset_refs;
+    asset_refs
+        .sizes
+        .retain(|path, _| blueprints_cache.gltfs.contains_key(path));
+    for (path, handle) in blueprints_cache.gltfs.iter() {
+        if asset_refs.sizes.contains_key(path) {
+            continue;
+        }
+        if let Some(gltf) = gltfs.get(handle) {
+            asset_refs.sizes.insert(
+                path.clone(),
+                estimate_gltf_size(gltf, &gltf_meshes, &meshes, &materials, &images),
+            );
+        }
+    }
+
+    let Some(budget_mb) = blueprints_config.blueprint_cache_budget_mb else {
+        return;
+    };
+    let budget = budget_mb * 1024 * 1024;
+    let mut resident = asset_refs.estimated_resident_bytes();
+    if resident <= budget {
+        return;
+    }
+
+    let mut unused: Vec<(PathBuf, Instant)> = asset_refs
+        .unused_since
+        .iter()
+        .map(|(path, unused_since)| (path.clone(), *unused_since))
+        .collect();
+    unused.sort_by_key(|(_, unused_since)| *unused_since);
+    for (path, _) in unused {
+        if resident <= budget {
+            break;
+        }
+        debug!("over the blueprint cache budget, unloading {:?}", path);
+        resident -= asset_refs.sizes.remove(&path).unwrap_or(0);
+        asset_refs.unused_since.remove(&path);
+        blueprints_cache.gltfs.remove(&path);
+    }
+}
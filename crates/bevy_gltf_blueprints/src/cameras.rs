@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+
+use crate::GameWorldReady;
+
+/// flag component for the camera (added to it in Blender) to make active once the game world (level) containing it is ready
+/// * cameras exported from Blender are spawned by bevy's gltf loader as `Camera3d`s, with their field of view & clip planes
+/// * the other active 3d cameras (with the same order) are deactivated, including the ones created in code
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct ActiveCamera;
+
+pub(crate) fn activate_level_cameras(
+    worlds: Query<Entity, Added<GameWorldReady>>,
+    all_children: Query<&Children>,
+    flagged: Query<(), With<ActiveCamera>>,
+    mut cameras: Query<(Entity, &mut Camera), With<Camera3d>>,
+) {
+    for world in worlds.iter() {
+        let mut flagged_cameras = all_children
+            .iter_descendants(world)
+            .filter(|entity| flagged.contains(*entity) && cameras.contains(*entity));
+        let Some(active) = flagged_cameras.next() else {
+            continue;
+        };
+        if flagged_cameras.next().is_some() {
+            warn!(
+                "several cameras flagged as ActiveCamera in game world {:?}, using {:?}",
+                world, active
+            );
+        }
+
+        let Ok((_, mut active_camera)) = cameras.get_mut(active) else {
+            continue;
+        };
+        active_camera.is_active = true;
+        let order = active_camera.order;
+        for (entity, mut camera) in cameras.iter_mut() {
+            if entity != active && camera.is_active && camera.order == order {
+                debug!("deactivating camera {:?} in favor of {:?}", entity, active);
+                camera.is_active = false;
+            }
+        }
+    }
+}
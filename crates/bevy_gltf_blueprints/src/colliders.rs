@@ -0,0 +1,382 @@
+use bevy::prelude::*;
+#[cfg(any(feature = "avian", feature = "rapier"))]
+use bevy::{ecs::system::SystemParam, utils::HashSet};
+
+#[cfg(any(feature = "avian", feature = "rapier"))]
+use crate::{BlueprintName, BlueprintSpawnState, CollisionMesh, RenderOnly};
+
+/// the shape of a collider described in Blender
+#[derive(Reflect, Default, Debug, Clone, PartialEq)]
+pub enum ColliderShape {
+    /// sphere with the given radius
+    Ball(f32),
+    /// box with the given (full) size
+    Cuboid(Vec3),
+    /// capsule along the Y axis, `height` being the height of its cylindrical part
+    Capsule { height: f32, radius: f32 },
+    /// cylinder along the Y axis
+    Cylinder { height: f32, radius: f32 },
+    /// triangle mesh collider generated from the mesh of the object
+    #[default]
+    TriMesh,
+    /// convex hull generated from the mesh of the object
+    ConvexHull,
+}
+
+#[cfg(any(feature = "avian", feature = "rapier"))]
+impl ColliderShape {
+    fn is_mesh_based(&self) -> bool {
+        matches!(self, ColliderShape::TriMesh | ColliderShape::ConvexHull)
+    }
+}
+
+/// a collider described in Blender, turned into the colliders of the physics engine once blueprint instances are post processed
+/// (with the `avian` or `rapier` feature enabled)
+#[derive(Component, Reflect, Default, Debug, Clone)]
+#[reflect(Component)]
+pub struct ColliderDescription {
+    pub shape: ColliderShape,
+    /// friction coefficient, uses the default of the physics engine if None
+    pub friction: Option<f32>,
+    /// restitution (bounciness) coefficient, uses the default of the physics engine if None
+    pub restitution: Option<f32>,
+    /// detect intersections without generating contacts
+    pub sensor: bool,
+}
+
+/// flag component for blueprint instances (ie vehicles made of sub blueprints) whose colliders should be gathered into a single compound collider
+/// on the instance, once it & all the blueprint instances nested inside it are ready (the colliders are removed from the other entities)
+/// * the parts keep their position, rotation & scale relative to the instance
+/// * the friction, restitution & sensor settings of the parts are not kept, set them on the instance instead
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct CompoundCollider;
+
+/// flag component for instances whose compound collider has been assembled
+#[cfg(any(feature = "avian", feature = "rapier"))]
+#[derive(Component, Debug)]
+pub(crate) struct CompoundColliderAssembled;
+
+/// what a collider gets built from
+#[cfg(any(feature = "avian", feature = "rapier"))]
+pub(crate) enum ColliderSource<'a> {
+    Shape(&'a ColliderShape),
+    Mesh { mesh: &'a Mesh, convex: bool },
+}
+
+/// a collider to add to an entity of a blueprint instance
+#[cfg(any(feature = "avian", feature = "rapier"))]
+pub(crate) struct ColliderToBuild<'a> {
+    pub(crate) entity: Entity,
+    pub(crate) source: ColliderSource<'a>,
+    pub(crate) description: Option<&'a ColliderDescription>,
+}
+
+/// gathers the colliders to build for blueprint instances, independently of the physics engine
+#[cfg(any(feature = "avian", feature = "rapier"))]
+#[derive(SystemParam)]
+pub(crate) struct BlueprintColliders<'w, 's> {
+    all_children: Query<'w, 's, &'static Children>,
+    nested_instances: Query<'w, 's, (), With<BlueprintName>>,
+    render_only: Query<'w, 's, (), With<RenderOnly>>,
+    descriptions: Query<'w, 's, &'static ColliderDescription>,
+    collision_meshes: Query<'w, 's, &'static CollisionMesh>,
+    mesh_handles: Query<'w, 's, &'static Handle<Mesh>>,
+    meshes: Res<'w, Assets<Mesh>>,
+}
+
+#[cfg(any(feature = "avian", feature = "rapier"))]
+impl BlueprintColliders<'_, '_> {
+    /// the mesh of an entity: the one of collision only objects, or the rendered one
+    fn mesh(&self, entity: Entity) -> Option<&Mesh> {
+        let handle = self
+            .collision_meshes
+            .get(entity)
+            .map(|collision_mesh| &collision_mesh.0)
+            .or_else(|_| self.mesh_handles.get(entity))
+            .ok()?;
+        let mesh = self.meshes.get(handle);
+        if mesh.is_none() {
+            warn!("mesh of {:?} not loaded, cannot build its collider", entity);
+        }
+        mesh
+    }
+
+    /// the colliders of a blueprint instance, without going into nested blueprint instances:
+    /// * objects with a [`ColliderDescription`] (mesh based shapes are built from the meshes of the object, one per primitive)
+    /// * collision only objects without description, which get triangle mesh colliders
+    pub(crate) fn to_build(&self, instance: Entity) -> Vec<ColliderToBuild<'_>> {
+        let mut found = vec![];
+        let mut handled: HashSet<Entity> = HashSet::new();
+        let mut stack = vec![instance];
+        while let Some(entity) = stack.pop() {
+            let children = self.all_children.get(entity).ok();
+            for child in children.into_iter().flatten() {
+                // nested blueprint instances build their own colliders
+                if !self.nested_instances.contains(*child) {
+                    stack.push(*child);
+                }
+            }
+            if self.render_only.contains(entity) {
+                continue;
+            }
+            if let Ok(description) = self.descriptions.get(entity) {
+                if !description.shape.is_mesh_based() {
+                    found.push(ColliderToBuild {
+                        entity,
+                        source: ColliderSource::Shape(&description.shape),
+                        description: Some(description),
+                    });
+                    continue;
+                }
+                // bevy spawns one entity per primitive of the mesh of the object
+                for mesh_entity in
+                    std::iter::once(entity).chain(children.into_iter().flatten().copied())
+                {
+                    handled.insert(mesh_entity);
+                    let Some(mesh) = self.mesh(mesh_entity) else {
+                        continue;
+                    };
+                    found.push(ColliderToBuild {
+                        entity: mesh_entity,
+                        source: ColliderSource::Mesh {
+                            mesh,
+                            convex: description.shape == ColliderShape::ConvexHull,
+                        },
+                        description: Some(description),
+                    });
+                }
+            } else if self.collision_meshes.contains(entity) && !handled.contains(&entity) {
+                let Some(mesh) = self.mesh(entity) else {
+                    continue;
+                };
+                found.push(ColliderToBuild {
+                    entity,
+                    source: ColliderSource::Mesh {
+                        mesh,
+                        convex: false,
+                    },
+                    description: None,
+                });
+            }
+        }
+        found
+    }
+}
+
+/// builds the Avian (xpbd) colliders of blueprint instances once they are post processed
+#[cfg(feature = "avian")]
+pub(crate) fn build_avian_colliders(
+    instances: Query<Entity, Added<crate::BlueprintReadyForFinalizing>>,
+    colliders: BlueprintColliders,
+    mut commands: Commands,
+) {
+    use bevy_xpbd_3d::prelude::{Collider, Friction, Restitution, Sensor};
+
+    for instance in instances.iter() {
+        for to_build in colliders.to_build(instance) {
+            let collider = match to_build.source {
+                ColliderSource::Shape(ColliderShape::Ball(radius)) => Some(Collider::ball(*radius)),
+                ColliderSource::Shape(ColliderShape::Cuboid(size)) => {
+                    Some(Collider::cuboid(size.x, size.y, size.z))
+                }
+                ColliderSource::Shape(ColliderShape::Capsule { height, radius }) => {
+                    Some(Collider::capsule(*height, *radius))
+                }
+                ColliderSource::Shape(ColliderShape::Cylinder { height, radius }) => {
+                    Some(Collider::cylinder(*height, *radius))
+                }
+                ColliderSource::Shape(ColliderShape::TriMesh | ColliderShape::ConvexHull) => None,
+                ColliderSource::Mesh {
+                    mesh,
+                    convex: false,
+                } => Collider::trimesh_from_mesh(mesh),
+                ColliderSource::Mesh { mesh, convex: true } => {
+                    Collider::convex_hull_from_mesh(mesh)
+                }
+            };
+            let Some(collider) = collider else {
+                warn!("could not build collider for {:?}", to_build.entity);
+                continue;
+            };
+            let mut entity_commands = commands.entity(to_build.entity);
+            entity_commands.insert(collider);
+            if let Some(description) = to_build.description {
+                if let Some(friction) = description.friction {
+                    entity_commands.insert(Friction::new(friction));
+                }
+                if let Some(restitution) = description.restitution {
+                    entity_commands.insert(Restitution::new(restitution));
+                }
+                if description.sensor {
+                    entity_commands.insert(Sensor);
+                }
+            }
+        }
+    }
+}
+
+/// builds the Rapier colliders of blueprint instances once they are post processed
+#[cfg(feature = "rapier")]
+pub(crate) fn build_rapier_colliders(
+    instances: Query<Entity, Added<crate::BlueprintReadyForFinalizing>>,
+    colliders: BlueprintColliders,
+    mut commands: Commands,
+) {
+    use bevy_rapier3d::prelude::{Collider, ComputedColliderShape, Friction, Restitution, Sensor};
+
+    for instance in instances.iter() {
+        for to_build in colliders.to_build(instance) {
+            // rapier uses half extents
+            let collider = match to_build.source {
+                ColliderSource::Shape(ColliderShape::Ball(radius)) => Some(Collider::ball(*radius)),
+                ColliderSource::Shape(ColliderShape::Cuboid(size)) => {
+                    Some(Collider::cuboid(size.x / 2.0, size.y / 2.0, size.z / 2.0))
+                }
+                ColliderSource::Shape(ColliderShape::Capsule { height, radius }) => {
+                    Some(Collider::capsule_y(height / 2.0, *radius))
+                }
+                ColliderSource::Shape(ColliderShape::Cylinder { height, radius }) => {
+                    Some(Collider::cylinder(height / 2.0, *radius))
+                }
+                ColliderSource::Shape(ColliderShape::TriMesh | ColliderShape::ConvexHull) => None,
+                ColliderSource::Mesh {
+                    mesh,
+                    convex: false,
+                } => Collider::from_bevy_mesh(mesh, &ComputedColliderShape::TriMesh),
+                ColliderSource::Mesh { mesh, convex: true } => {
+                    Collider::from_bevy_mesh(mesh, &ComputedColliderShape::ConvexHull)
+                }
+            };
+            let Some(collider) = collider else {
+                warn!("could not build collider for {:?}", to_build.entity);
+                continue;
+            };
+            let mut entity_commands = commands.entity(to_build.entity);
+            entity_commands.insert(collider);
+            if let Some(description) = to_build.description {
+                if let Some(friction) = description.friction {
+                    entity_commands.insert(Friction::coefficient(friction));
+                }
+                if let Some(restitution) = description.restitution {
+                    entity_commands.insert(Restitution::coefficient(restitution));
+                }
+                if description.sensor {
+                    entity_commands.insert(Sensor);
+                }
+            }
+        }
+    }
+}
+
+/// the colliders of the hierarchy of an instance with a [`CompoundCollider`], nested blueprint instances included, with their transforms
+/// relative to the instance: None while the instance or any of the blueprint instances nested inside it is still spawning
+#[cfg(any(feature = "avian", feature = "rapier"))]
+fn compound_parts<C: Component + Clone>(
+    instance: Entity,
+    all_children: &Query<&Children>,
+    spawn_states: &Query<&BlueprintSpawnState>,
+    transforms: &Query<&Transform>,
+    colliders: &Query<&C>,
+) -> Option<Vec<(Entity, Transform, C)>> {
+    let mut parts = vec![];
+    let mut stack = vec![(instance, Transform::IDENTITY)];
+    while let Some((entity, relative_transform)) = stack.pop() {
+        if let Ok(state) = spawn_states.get(entity) {
+            if !matches!(
+                state,
+                BlueprintSpawnState::Ready | BlueprintSpawnState::Failed
+            ) {
+                return None;
+            }
+        }
+        if let Ok(collider) = colliders.get(entity) {
+            parts.push((entity, relative_transform, collider.clone()));
+        }
+        for child in all_children.get(entity).into_iter().flatten() {
+            let child_transform = transforms.get(*child).copied().unwrap_or_default();
+            stack.push((*child, relative_transform * child_transform));
+        }
+    }
+    Some(parts)
+}
+
+/// assembles the Avian (xpbd) compound colliders of [`CompoundCollider`] instances
+#[cfg(feature = "avian")]
+pub(crate) fn assemble_avian_compound_colliders(
+    instances: Query<Entity, (With<CompoundCollider>, Without<CompoundColliderAssembled>)>,
+    all_children: Query<&Children>,
+    spawn_states: Query<&BlueprintSpawnState>,
+    transforms: Query<&Transform>,
+    colliders: Query<&bevy_xpbd_3d::prelude::Collider>,
+    mut commands: Commands,
+) {
+    use bevy_xpbd_3d::prelude::Collider;
+
+    for instance in instances.iter() {
+        let Some(parts) = compound_parts(
+            instance,
+            &all_children,
+            &spawn_states,
+            &transforms,
+            &colliders,
+        ) else {
+            continue;
+        };
+        commands.entity(instance).insert(CompoundColliderAssembled);
+        if parts.is_empty() {
+            warn!(
+                "no colliders found for the compound collider of {:?}",
+                instance
+            );
+            continue;
+        }
+        let mut shapes = vec![];
+        for (entity, transform, mut collider) in parts {
+            collider.set_scale(transform.scale, 10);
+            shapes.push((transform.translation, transform.rotation, collider));
+            commands.entity(entity).remove::<Collider>();
+        }
+        commands.entity(instance).insert(Collider::compound(shapes));
+    }
+}
+
+/// assembles the Rapier compound colliders of [`CompoundCollider`] instances
+#[cfg(feature = "rapier")]
+pub(crate) fn assemble_rapier_compound_colliders(
+    instances: Query<Entity, (With<CompoundCollider>, Without<CompoundColliderAssembled>)>,
+    all_children: Query<&Children>,
+    spawn_states: Query<&BlueprintSpawnState>,
+    transforms: Query<&Transform>,
+    colliders: Query<&bevy_rapier3d::prelude::Collider>,
+    mut commands: Commands,
+) {
+    use bevy_rapier3d::prelude::Collider;
+
+    for instance in instances.iter() {
+        let Some(parts) = compound_parts(
+            instance,
+            &all_children,
+            &spawn_states,
+            &transforms,
+            &colliders,
+        ) else {
+            continue;
+        };
+        commands.entity(instance).insert(CompoundColliderAssembled);
+        if parts.is_empty() {
+            warn!(
+                "no colliders found for the compound collider of {:?}",
+                instance
+            );
+            continue;
+        }
+        let mut shapes = vec![];
+        for (entity, transform, mut collider) in parts {
+            collider.set_scale(transform.scale, 10);
+            shapes.push((transform.translation, transform.rotation, collider));
+            commands.entity(entity).remove::<Collider>();
+        }
+        commands.entity(instance).insert(Collider::compound(shapes));
+    }
+}
@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+
+use crate::{BlueprintName, BlueprintReadyForFinalizing};
+
+/// flag component for objects that are only used for collisions (also applies to objects whose name ends with `-colonly` or `-nomesh`):
+/// their meshes are not rendered, and are left in a [`CollisionMesh`] component for physics integrations instead
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct CollisionOnly;
+
+/// flag component for objects that are only rendered, that physics integrations should not generate colliders for
+/// (also applies to objects whose name ends with `-rendonly`)
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct RenderOnly;
+
+/// the mesh of a collision only object, that is not rendered: for physics integrations to build colliders from
+#[derive(Component, Debug, Clone)]
+pub struct CollisionMesh(pub Handle<Mesh>);
+
+const COLLISION_ONLY_SUFFIXES: [&str; 2] = ["-colonly", "-nomesh"];
+const RENDER_ONLY_SUFFIX: &str = "-rendonly";
+
+/// handles the collision only & render only objects of blueprint instances, once they are post processed
+pub(crate) fn process_collision_only_objects(
+    instances: Query<Entity, Added<BlueprintReadyForFinalizing>>,
+    all_children: Query<&Children>,
+    nested_instances: Query<(), With<BlueprintName>>,
+    objects: Query<(Option<&Name>, Has<CollisionOnly>, Has<RenderOnly>)>,
+    meshes: Query<&Handle<Mesh>>,
+    mut commands: Commands,
+) {
+    for instance in instances.iter() {
+        let mut stack = vec![instance];
+        while let Some(entity) = stack.pop() {
+            let children = all_children.get(entity).ok();
+            for child in children.into_iter().flatten() {
+                // nested blueprint instances handle their own objects
+                if !nested_instances.contains(*child) {
+                    stack.push(*child);
+                }
+            }
+            let Ok((name, collision_only, render_only)) = objects.get(entity) else {
+                continue;
+            };
+            let name = name.map(Name::as_str).unwrap_or_default();
+            if !render_only && name.ends_with(RENDER_ONLY_SUFFIX) {
+                commands.entity(entity).insert(RenderOnly);
+            }
+            let collision_only = collision_only
+                || COLLISION_ONLY_SUFFIXES
+                    .iter()
+                    .any(|suffix| name.ends_with(suffix));
+            if !collision_only {
+                continue;
+            }
+            commands.entity(entity).insert(CollisionOnly);
+            // bevy spawns one entity per primitive of the mesh of the object
+            for mesh_entity in
+                std::iter::once(entity).chain(children.into_iter().flatten().copied())
+            {
+                let Ok(mesh) = meshes.get(mesh_entity) else {
+                    continue;
+                };
+                commands
+                    .entity(mesh_entity)
+                    .insert(CollisionMesh(mesh.clone()))
+                    .remove::<(Handle<Mesh>, Handle<StandardMaterial>)>();
+            }
+        }
+    }
+}
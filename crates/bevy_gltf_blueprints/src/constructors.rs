@@ -0,0 +1,56 @@
+use bevy::{ecs::system::SystemId, prelude::*, utils::HashMap};
+
+/// "construction scripts" registered with [`BlueprintConstructorAppExt::add_blueprint_constructor`], keyed by blueprint name
+#[derive(Resource, Debug, Default)]
+pub struct BlueprintConstructors(pub(crate) HashMap<String, Vec<SystemId<Entity>>>);
+
+impl BlueprintConstructors {
+    /// the constructors of the blueprint with the given name
+    pub fn get(&self, blueprint_name: &str) -> &[SystemId<Entity>] {
+        self.0
+            .get(blueprint_name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// extension trait for [`App`], to register constructors for blueprints
+pub trait BlueprintConstructorAppExt {
+    /// register a system that runs for each instance of the blueprint with the given name, once it has been post processed
+    /// (right before it is marked as ready), to do procedural setup in Rust: randomizing variants, wiring children etc
+    /// * the system gets the instance as input, and can query any of its components (ie its [`crate::BlueprintOverrides`]) as parameters
+    /// * several constructors can be registered for the same blueprint, they run in the order they were registered
+    ///
+    /// ```ignore
+    /// app.add_blueprint_constructor("Lamp", randomize_lamp);
+    ///
+    /// fn randomize_lamp(In(entity): In<Entity>, mut lamps: Query<&mut LampSettings>) {
+    ///     if let Ok(mut settings) = lamps.get_mut(entity) {
+    ///         settings.intensity *= rand::random::<f32>();
+    ///     }
+    /// }
+    /// ```
+    fn add_blueprint_constructor<M>(
+        &mut self,
+        blueprint_name: impl Into<String>,
+        system: impl IntoSystem<Entity, (), M> + 'static,
+    ) -> &mut Self;
+}
+
+impl BlueprintConstructorAppExt for App {
+    fn add_blueprint_constructor<M>(
+        &mut self,
+        blueprint_name: impl Into<String>,
+        system: impl IntoSystem<Entity, (), M> + 'static,
+    ) -> &mut Self {
+        let system_id = self.world.register_system(system);
+        self.init_resource::<BlueprintConstructors>();
+        self.world
+            .resource_mut::<BlueprintConstructors>()
+            .0
+            .entry(blueprint_name.into())
+            .or_default()
+            .push(system_id);
+        self
+    }
+}
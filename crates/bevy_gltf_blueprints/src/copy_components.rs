@@ -1,11 +1,111 @@
 use bevy::ecs::system::Command;
 use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
 use std::any::TypeId;
+use std::fmt;
+use std::sync::Arc;
+
+/// custom merge function: receives the component of the instance (to modify) and the one from the blueprint
+pub type ComponentMergeFn = Arc<dyn Fn(&mut dyn Reflect, &dyn Reflect) + Send + Sync>;
+
+/// what to do when a component of a blueprint is also present on the instance it is spawned on
+#[derive(Clone, Default)]
+pub enum ComponentMergeStrategy {
+    /// keep the component of the instance: ie instance components override blueprint components (default)
+    #[default]
+    KeepInstance,
+    /// overwrite the component of the instance with the one from the blueprint
+    KeepBlueprint,
+    /// merge both components using a custom function
+    Custom(ComponentMergeFn),
+}
+
+impl fmt::Debug for ComponentMergeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentMergeStrategy::KeepInstance => write!(f, "KeepInstance"),
+            ComponentMergeStrategy::KeepBlueprint => write!(f, "KeepBlueprint"),
+            ComponentMergeStrategy::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+/// per type rules for copying the components of blueprints to their instances:
+/// * components to exclude from the transfer (on top of `Parent` & `Children`)
+/// * merge strategies (see [`ComponentMergeStrategy`])
+#[derive(Resource, Debug, Clone, Default)]
+pub struct BlueprintComponentRules {
+    pub(crate) excluded: HashSet<TypeId>,
+    pub(crate) merge_strategies: HashMap<TypeId, ComponentMergeStrategy>,
+}
+
+impl BlueprintComponentRules {
+    /// never copy components of the given type from blueprints to their instances
+    pub fn exclude<T: Component>(&mut self) {
+        self.excluded.insert(TypeId::of::<T>());
+    }
+
+    pub fn is_excluded(&self, type_id: TypeId) -> bool {
+        self.excluded.contains(&type_id)
+    }
+
+    /// set the merge strategy for the given component type
+    pub fn set_merge_strategy<T: Component>(&mut self, strategy: ComponentMergeStrategy) {
+        self.merge_strategies.insert(TypeId::of::<T>(), strategy);
+    }
+
+    pub fn merge_strategy(&self, type_id: TypeId) -> ComponentMergeStrategy {
+        self.merge_strategies
+            .get(&type_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// extension trait for [`App`], to configure how blueprint components are copied to instances
+pub trait BlueprintComponentRulesAppExt {
+    /// set the merge strategy used when a component of type `T` is present both in a blueprint & on its instance
+    fn set_blueprint_merge_strategy<T: Component>(
+        &mut self,
+        strategy: ComponentMergeStrategy,
+    ) -> &mut Self;
+
+    /// never copy components of type `T` from blueprints to their instances
+    fn exclude_from_blueprint_transfer<T: Component>(&mut self) -> &mut Self;
+}
+
+impl BlueprintComponentRulesAppExt for App {
+    fn set_blueprint_merge_strategy<T: Component>(
+        &mut self,
+        strategy: ComponentMergeStrategy,
+    ) -> &mut Self {
+        self.init_resource::<BlueprintComponentRules>();
+        self.world
+            .resource_mut::<BlueprintComponentRules>()
+            .set_merge_strategy::<T>(strategy);
+        self
+    }
+
+    fn exclude_from_blueprint_transfer<T: Component>(&mut self) -> &mut Self {
+        self.init_resource::<BlueprintComponentRules>();
+        self.world
+            .resource_mut::<BlueprintComponentRules>()
+            .exclude::<T>();
+        self
+    }
+}
+
+/// the components that were last copied from a blueprint to its instance, with their values:
+/// when the instance is respawned (ie on hot reload), authored components that were not modified at runtime follow the new blueprint,
+/// while components modified or added at runtime are preserved
+#[derive(Component, Default)]
+pub(crate) struct AuthoredComponents(pub(crate) HashMap<TypeId, Box<dyn Reflect>>);
 
 // originally based  https://github.com/bevyengine/bevy/issues/1515,
 // more specifically https://gist.github.com/nwtnni/85d6b87ae75337a522166c500c9a8418
 // to work with Bevy 0.11
 // to copy components between entities but NOT overwriting any existing components
+// (unless configured otherwise per type, see BlueprintComponentRules)
 // plus some bells & whistles
 pub struct CopyComponents {
     pub source: Entity,
@@ -22,6 +122,11 @@ impl CopyComponents {
     // - the world does not have a type registry
     // - the source or destination entity do not exist
     fn transfer_components(self, world: &mut World) {
+        let rules = world
+            .get_resource::<BlueprintComponentRules>()
+            .cloned()
+            .unwrap_or_default();
+
         let components = {
             let registry = world
                 .get_resource::<AppTypeRegistry>()
@@ -40,7 +145,7 @@ impl CopyComponents {
                         .expect("component info should be available");
 
                     let type_id = component_info.type_id().unwrap();
-                    if self.exclude.contains(&type_id) {
+                    if self.exclude.contains(&type_id) || rules.is_excluded(type_id) {
                         debug!("excluding component: {:?}", component_info.name());
                         None
                     } else {
@@ -77,6 +182,12 @@ impl CopyComponents {
                 .collect::<Vec<_>>()
         };
 
+        let previous_authored = world
+            .get_entity_mut(self.destination)
+            .and_then(|mut destination| destination.take::<AuthoredComponents>())
+            .unwrap_or_default();
+        let mut authored = AuthoredComponents::default();
+
         for (component, type_id) in components {
             let type_registry: &AppTypeRegistry = world.resource();
             let type_registry = type_registry.clone();
@@ -85,6 +196,15 @@ impl CopyComponents {
                 .reflect(world.get_entity(self.source).unwrap())
                 .unwrap()
                 .clone_value();
+            // was the component authored in the previous version of the blueprint, and left untouched at runtime ?
+            let unchanged_at_runtime = previous_authored.0.get(&type_id).is_some_and(|previous| {
+                component
+                    .reflect(world.get_entity(self.destination).unwrap())
+                    .is_some_and(|current| {
+                        current.reflect_partial_eq(previous.as_ref()) == Some(true)
+                    })
+            });
+            authored.0.insert(type_id, source.clone_value());
 
             let mut destination = world
                 .get_entity_mut(self.destination)
@@ -92,10 +212,47 @@ impl CopyComponents {
 
             // println!("contains typeid {:?} {}", type_id, destination.contains_type_id(type_id));
             // we only want to copy components that are NOT already in the destination (ie no overwriting existing components)
-            if !destination.contains_type_id(type_id) {
+            // unless the merge strategy for that type says otherwise
+            if !destination.contains_type_id(type_id) || unchanged_at_runtime {
                 component.insert(&mut destination, &*source, &type_registry);
+                continue;
+            }
+            match rules.merge_strategy(type_id) {
+                ComponentMergeStrategy::KeepInstance => {}
+                ComponentMergeStrategy::KeepBlueprint => {
+                    component.insert(&mut destination, &*source, &type_registry);
+                }
+                ComponentMergeStrategy::Custom(merge) => {
+                    if let Some(mut existing) = component.reflect_mut(&mut destination) {
+                        merge(&mut *existing, &*source);
+                    }
+                }
             }
         }
+
+        // components that are not in the blueprint anymore are removed, unless they were modified at runtime
+        let type_registry: &AppTypeRegistry = world.resource();
+        let type_registry = type_registry.clone();
+        let type_registry = type_registry.read();
+        for (type_id, previous) in previous_authored.0.iter() {
+            if authored.0.contains_key(type_id) {
+                continue;
+            }
+            let Some(component) = type_registry
+                .get(*type_id)
+                .and_then(|registration| registration.data::<ReflectComponent>())
+            else {
+                continue;
+            };
+            let unchanged_at_runtime = component
+                .reflect(world.get_entity(self.destination).unwrap())
+                .is_some_and(|current| current.reflect_partial_eq(previous.as_ref()) == Some(true));
+            if unchanged_at_runtime {
+                component.remove(&mut world.get_entity_mut(self.destination).unwrap());
+            }
+        }
+
+        world.entity_mut(self.destination).insert(authored);
     }
 }
 
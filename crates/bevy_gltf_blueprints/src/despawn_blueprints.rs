@@ -0,0 +1,131 @@
+use bevy::{ecs::entity::Entities, gltf::Gltf, prelude::*};
+
+use crate::{
+    teardown_blueprint_instance, AssetsToLoad, AwaitingComponentInjection, BlueprintAssetsLoaded,
+    BlueprintAssetsNotLoaded, BlueprintEvent, BlueprintName, BlueprintReadyForFinalizing,
+    BlueprintSpawnState, ComponentTransferRetries, OriginalChildren, PendingPostProcessors,
+    SpawnHere,
+};
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+/// flag component to request the removal of a blueprint instance:
+/// the instance & all its children get despawned (which also frees up the handles to its assets),
+/// and a `BlueprintEvent::Despawned` event is sent
+pub struct DespawnBlueprint;
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+/// flag component to cancel the spawning of a blueprint instance that is not fully spawned yet:
+/// the loading trackers & spawned children are removed, the entity itself is kept,
+/// and a `BlueprintEvent::SpawnCancelled` event is sent
+/// (removing `SpawnHere` or despawning the entity while it is spawning does the same)
+pub struct CancelSpawn;
+
+pub(crate) fn despawn_blueprints(
+    to_despawn: Query<(Entity, &BlueprintName), With<DespawnBlueprint>>,
+    mut commands: Commands,
+    mut blueprint_events: EventWriter<BlueprintEvent>,
+) {
+    for (entity, blueprint_name) in to_despawn.iter() {
+        debug!("despawning blueprint instance {:?}", entity);
+        commands.entity(entity).despawn_recursive();
+        blueprint_events.send(BlueprintEvent::Despawned {
+            entity,
+            blueprint_name: blueprint_name.0.clone(),
+        });
+    }
+}
+
+fn is_spawning(spawn_state: Option<&BlueprintSpawnState>) -> bool {
+    matches!(
+        spawn_state,
+        Some(
+            BlueprintSpawnState::LoadingAssets
+                | BlueprintSpawnState::SpawningScene
+                | BlueprintSpawnState::PostProcessing
+        )
+    )
+}
+
+/// cleanly cancels the spawning of blueprint instances that were flagged with `CancelSpawn`,
+/// lost their `SpawnHere` component or were despawned while spawning
+pub(crate) fn cancel_blueprint_spawns(
+    to_cancel: Query<
+        (
+            Entity,
+            &BlueprintName,
+            Option<&BlueprintSpawnState>,
+            Option<&Children>,
+            Option<&OriginalChildren>,
+            Has<SpawnHere>,
+            Has<CancelSpawn>,
+        ),
+        Or<(With<CancelSpawn>, With<BlueprintSpawnState>)>,
+    >,
+    mut removed_spawn_here: RemovedComponents<SpawnHere>,
+    entities: &Entities,
+    mut commands: Commands,
+    mut blueprint_events: EventWriter<BlueprintEvent>,
+) {
+    let mut candidates: Vec<Entity> = to_cancel
+        .iter()
+        .filter(|(.., cancel_requested)| *cancel_requested)
+        .map(|(entity, ..)| entity)
+        .collect();
+    for entity in removed_spawn_here.read() {
+        if !entities.contains(entity) {
+            debug!(
+                "blueprint instance {:?} was despawned while spawning",
+                entity
+            );
+            blueprint_events.send(BlueprintEvent::SpawnCancelled {
+                entity,
+                blueprint_name: None,
+            });
+            continue;
+        }
+        candidates.push(entity);
+    }
+    candidates.sort();
+    candidates.dedup();
+
+    for entity in candidates {
+        let Ok((
+            entity,
+            blueprint_name,
+            spawn_state,
+            children,
+            original_children,
+            spawn_here,
+            cancel_requested,
+        )) = to_cancel.get(entity)
+        else {
+            continue;
+        };
+        if cancel_requested {
+            commands.entity(entity).remove::<CancelSpawn>();
+        }
+        // SpawnHere is also removed once the instance is ready or failed to spawn, that is not a cancellation
+        if !is_spawning(spawn_state) || (spawn_here && !cancel_requested) {
+            continue;
+        }
+        debug!("cancelling the spawning of blueprint instance {:?}", entity);
+        teardown_blueprint_instance(&mut commands, entity, children, original_children);
+        commands.entity(entity).remove::<(
+            SpawnHere,
+            AssetsToLoad<Gltf>,
+            BlueprintAssetsLoaded,
+            BlueprintAssetsNotLoaded,
+            BlueprintSpawnState,
+            AwaitingComponentInjection,
+            BlueprintReadyForFinalizing,
+            PendingPostProcessors,
+            ComponentTransferRetries,
+        )>();
+        blueprint_events.send(BlueprintEvent::SpawnCancelled {
+            entity,
+            blueprint_name: Some(blueprint_name.0.clone()),
+        });
+    }
+}
@@ -0,0 +1,57 @@
+use bevy::{ecs::system::Command, prelude::*};
+
+/// flag component to spawn a blueprint instance disabled (ie pre-spawned waves of enemies, pooled content):
+/// it is fully spawned, but kept hidden & flagged with [`BlueprintInstanceDisabled`] until `commands.enable_blueprint_instance(entity)` is called
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct SpawnDisabled;
+
+/// flag component for disabled blueprint instances (see [`SpawnDisabled`]), to filter them out in your systems
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct BlueprintInstanceDisabled;
+
+/// flags instances spawned with [`SpawnDisabled`] as disabled, and keeps disabled instances hidden, even when they finish spawning
+pub(crate) fn disable_blueprint_instances(
+    added: Query<Entity, Added<SpawnDisabled>>,
+    mut disabled: Query<&mut Visibility, With<BlueprintInstanceDisabled>>,
+    mut commands: Commands,
+) {
+    for entity in added.iter() {
+        commands
+            .entity(entity)
+            .remove::<SpawnDisabled>()
+            .insert((BlueprintInstanceDisabled, Visibility::Hidden));
+    }
+    for mut visibility in disabled.iter_mut() {
+        visibility.set_if_neq(Visibility::Hidden);
+    }
+}
+
+/// enables a disabled blueprint instance: shows it & removes its [`BlueprintInstanceDisabled`] flag
+pub struct EnableBlueprintInstance {
+    pub entity: Entity,
+}
+
+impl Command for EnableBlueprintInstance {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity) = world.get_entity_mut(self.entity) else {
+            warn!("cannot enable {:?}: it does not exist", self.entity);
+            return;
+        };
+        entity
+            .remove::<(SpawnDisabled, BlueprintInstanceDisabled)>()
+            .insert(Visibility::Inherited);
+    }
+}
+
+/// extension trait for [`Commands`], to enable blueprint instances spawned with [`SpawnDisabled`]
+pub trait DisabledInstanceCommandsExt {
+    fn enable_blueprint_instance(&mut self, entity: Entity);
+}
+
+impl DisabledInstanceCommandsExt for Commands<'_, '_> {
+    fn enable_blueprint_instance(&mut self, entity: Entity) {
+        self.add(EnableBlueprintInstance { entity });
+    }
+}
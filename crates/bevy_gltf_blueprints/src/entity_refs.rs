@@ -0,0 +1,328 @@
+use std::any::TypeId;
+
+use bevy::{
+    ecs::event::ManualEventReader,
+    prelude::*,
+    reflect::{ReflectMut, ReflectRef},
+    utils::HashSet,
+};
+
+use crate::{BlueprintEvent, GameWorldTag, SpawnHere};
+
+/// reference to another entity by name, usable as a field of components exported from Blender (where entities cannot be referenced directly):
+/// ie `NamedEntityRef("Door_01")`, or with a path of names to disambiguate: `NamedEntityRef("House_02/Door_01")`
+/// * references are resolved once the blueprint instance containing the component is ready,
+///   looking for the named entity in the same instance first, then in the same game world (ie other blueprints of the same level), then anywhere
+/// * references that still do not resolve once nothing is spawning anymore send a `BlueprintEvent::DanglingEntityRef` event
+#[derive(Reflect, Default, Debug, Clone, PartialEq)]
+pub struct NamedEntityRef(pub String, #[reflect(ignore)] pub Option<Entity>);
+
+impl NamedEntityRef {
+    pub fn new(name: impl Into<String>) -> Self {
+        NamedEntityRef(name.into(), None)
+    }
+
+    /// the referenced entity, None if it is not resolved (yet)
+    pub fn get(&self) -> Option<Entity> {
+        self.1
+    }
+}
+
+/// components containing references that could not be resolved yet, retried as other instances get spawned
+#[derive(Resource, Debug, Default)]
+pub(crate) struct PendingEntityRefs(pub(crate) HashSet<(Entity, TypeId, Entity)>);
+
+fn contains_entity_refs(value: &dyn Reflect) -> bool {
+    if value.is::<NamedEntityRef>() {
+        return true;
+    }
+    match value.reflect_ref() {
+        ReflectRef::Struct(value) => value.iter_fields().any(contains_entity_refs),
+        ReflectRef::TupleStruct(value) => value.iter_fields().any(contains_entity_refs),
+        ReflectRef::Tuple(value) => value.iter_fields().any(contains_entity_refs),
+        ReflectRef::List(value) => value.iter().any(contains_entity_refs),
+        ReflectRef::Array(value) => value.iter().any(contains_entity_refs),
+        ReflectRef::Map(value) => value.iter().any(|(_, value)| contains_entity_refs(value)),
+        ReflectRef::Enum(value) => value
+            .iter_fields()
+            .any(|field| contains_entity_refs(field.value())),
+        ReflectRef::Value(_) => false,
+    }
+}
+
+fn visit_entity_refs(value: &mut dyn Reflect, visitor: &mut impl FnMut(&mut NamedEntityRef)) {
+    if let Some(entity_ref) = value.downcast_mut::<NamedEntityRef>() {
+        visitor(entity_ref);
+        return;
+    }
+    match value.reflect_mut() {
+        ReflectMut::Struct(value) => {
+            for index in 0..value.field_len() {
+                if let Some(field) = value.field_at_mut(index) {
+                    visit_entity_refs(field, visitor);
+                }
+            }
+        }
+        ReflectMut::TupleStruct(value) => {
+            for index in 0..value.field_len() {
+                if let Some(field) = value.field_mut(index) {
+                    visit_entity_refs(field, visitor);
+                }
+            }
+        }
+        ReflectMut::Tuple(value) => {
+            for index in 0..value.field_len() {
+                if let Some(field) = value.field_mut(index) {
+                    visit_entity_refs(field, visitor);
+                }
+            }
+        }
+        ReflectMut::List(value) => {
+            for index in 0..value.len() {
+                if let Some(item) = value.get_mut(index) {
+                    visit_entity_refs(item, visitor);
+                }
+            }
+        }
+        ReflectMut::Array(value) => {
+            for index in 0..value.len() {
+                if let Some(item) = value.get_mut(index) {
+                    visit_entity_refs(item, visitor);
+                }
+            }
+        }
+        ReflectMut::Map(value) => {
+            for index in 0..value.len() {
+                if let Some((_, item)) = value.get_at_mut(index) {
+                    visit_entity_refs(item, visitor);
+                }
+            }
+        }
+        ReflectMut::Enum(value) => {
+            for index in 0..value.field_len() {
+                if let Some(field) = value.field_at_mut(index) {
+                    visit_entity_refs(field, visitor);
+                }
+            }
+        }
+        ReflectMut::Value(_) => {}
+    }
+}
+
+fn is_descendant_of(world: &World, entity: Entity, ancestor: Entity) -> bool {
+    let mut current = Some(entity);
+    while let Some(entity) = current {
+        if entity == ancestor {
+            return true;
+        }
+        current = world.get::<Parent>(entity).map(|parent| parent.get());
+    }
+    false
+}
+
+/// the entity matches the given path of names (ie `["House_02", "Door_01"]`): the last one is its own name,
+/// the others have to match its ancestors, in order (but not necessarily direct parents)
+fn matches_path(world: &World, entity: Entity, path: &[&str]) -> bool {
+    let mut current = world.get::<Parent>(entity).map(|parent| parent.get());
+    for segment in path.iter().rev().skip(1) {
+        loop {
+            let Some(ancestor) = current else {
+                return false;
+            };
+            current = world.get::<Parent>(ancestor).map(|parent| parent.get());
+            if world
+                .get::<Name>(ancestor)
+                .is_some_and(|name| name.as_str() == *segment)
+            {
+                break;
+            }
+        }
+    }
+    true
+}
+
+/// resolves a reference from the point of view of the given entity (see [`NamedEntityRef`] for the lookup order)
+fn resolve(
+    world: &World,
+    named_entities: &[(Entity, String)],
+    from: Entity,
+    instance: Entity,
+    name: &str,
+) -> Option<Entity> {
+    let path: Vec<&str> = name.split('/').filter(|s| !s.is_empty()).collect();
+    let last = *path.last()?;
+    let candidates: Vec<Entity> = named_entities
+        .iter()
+        .filter(|(_, candidate_name)| candidate_name == last)
+        .map(|(entity, _)| *entity)
+        .filter(|entity| matches_path(world, *entity, &path))
+        .collect();
+
+    let game_world = {
+        let mut current = Some(from);
+        let mut game_world = None;
+        while let Some(entity) = current {
+            if world.get::<GameWorldTag>(entity).is_some() {
+                game_world = Some(entity);
+                break;
+            }
+            current = world.get::<Parent>(entity).map(|parent| parent.get());
+        }
+        game_world
+    };
+
+    candidates
+        .iter()
+        .find(|candidate| is_descendant_of(world, **candidate, instance))
+        .or_else(|| {
+            game_world.and_then(|game_world| {
+                candidates
+                    .iter()
+                    .find(|candidate| is_descendant_of(world, **candidate, game_world))
+            })
+        })
+        .or(candidates.first())
+        .copied()
+}
+
+/// resolves the [`NamedEntityRef`]s of the components of blueprint instances once they are ready
+pub(crate) fn resolve_entity_refs(
+    world: &mut World,
+    mut blueprint_event_reader: Local<ManualEventReader<BlueprintEvent>>,
+) {
+    let ready_instances: Vec<Entity> = blueprint_event_reader
+        .read(world.resource::<Events<BlueprintEvent>>())
+        .filter_map(|event| match event {
+            BlueprintEvent::InstanceReady { entity, .. } => Some(*entity),
+            _ => None,
+        })
+        .collect();
+    let has_pending = !world.resource::<PendingEntityRefs>().0.is_empty();
+    if ready_instances.is_empty() && !has_pending {
+        return;
+    }
+
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = type_registry.read();
+
+    // all the components (of the new instances & the ones still pending) that contain references, with the instance they belong to
+    let mut to_resolve: Vec<(Entity, TypeId, Entity)> = vec![];
+    let pending = std::mem::take(&mut world.resource_mut::<PendingEntityRefs>().0);
+    for (entity, type_id, instance) in pending {
+        if world.get_entity(entity).is_some() {
+            to_resolve.push((entity, type_id, instance));
+        }
+    }
+    for instance in ready_instances {
+        let Some(instance_ref) = world.get_entity(instance) else {
+            continue;
+        };
+        let mut entities = vec![instance];
+        if instance_ref.contains::<Children>() {
+            let mut stack = vec![instance];
+            while let Some(entity) = stack.pop() {
+                if let Some(children) = world.get::<Children>(entity) {
+                    entities.extend(children.iter());
+                    stack.extend(children.iter());
+                }
+            }
+        }
+        for entity in entities {
+            let entity_ref = world.entity(entity);
+            for component_id in entity_ref.archetype().components() {
+                let Some(type_id) = world
+                    .components()
+                    .get_info(component_id)
+                    .and_then(|info| info.type_id())
+                else {
+                    continue;
+                };
+                let Some(reflect_component) = type_registry
+                    .get(type_id)
+                    .and_then(|registration| registration.data::<ReflectComponent>())
+                else {
+                    continue;
+                };
+                if reflect_component
+                    .reflect(entity_ref)
+                    .is_some_and(contains_entity_refs)
+                {
+                    to_resolve.push((entity, type_id, instance));
+                }
+            }
+        }
+    }
+    if to_resolve.is_empty() {
+        return;
+    }
+
+    let named_entities: Vec<(Entity, String)> = world
+        .query::<(Entity, &Name)>()
+        .iter(world)
+        .map(|(entity, name)| (entity, name.to_string()))
+        .collect();
+
+    let mut still_pending: HashSet<(Entity, TypeId, Entity)> = HashSet::new();
+    let mut dangling: Vec<(Entity, String, String)> = vec![];
+    for (entity, type_id, instance) in to_resolve {
+        let Some(reflect_component) = type_registry
+            .get(type_id)
+            .and_then(|registration| registration.data::<ReflectComponent>())
+        else {
+            continue;
+        };
+        // resolve first (read only), then write the results
+        let mut names: Vec<String> = vec![];
+        if let Some(component) = reflect_component.reflect(world.entity(entity)) {
+            let mut component = component.clone_value();
+            visit_entity_refs(component.as_mut(), &mut |entity_ref| {
+                names.push(entity_ref.0.clone());
+            });
+        }
+        let resolved: Vec<Option<Entity>> = names
+            .iter()
+            .map(|name| resolve(world, &named_entities, entity, instance, name))
+            .collect();
+
+        let component_path = type_registry
+            .get(type_id)
+            .map(|registration| registration.type_info().type_path().to_string())
+            .unwrap_or_default();
+        for (name, resolved) in names.iter().zip(resolved.iter()) {
+            if resolved.is_none() {
+                still_pending.insert((entity, type_id, instance));
+                dangling.push((entity, component_path.clone(), name.clone()));
+            }
+        }
+
+        let mut entity_mut = world.entity_mut(entity);
+        if let Some(mut component) = reflect_component.reflect_mut(&mut entity_mut) {
+            let mut resolved = resolved.into_iter();
+            visit_entity_refs(&mut *component, &mut |entity_ref| {
+                entity_ref.1 = resolved.next().flatten();
+            });
+        }
+    }
+
+    // references to entities of blueprints that are not spawned yet get resolved later: only report them once nothing is spawning anymore
+    let spawning = world
+        .query_filtered::<(), With<SpawnHere>>()
+        .iter(world)
+        .next()
+        .is_some();
+    if spawning {
+        world.resource_mut::<PendingEntityRefs>().0 = still_pending;
+        return;
+    }
+    for (entity, component, name) in dangling {
+        warn!(
+            "dangling entity reference {:?} in component {} of {:?}",
+            name, component, entity
+        );
+        world.send_event(BlueprintEvent::DanglingEntityRef {
+            entity,
+            component,
+            name,
+        });
+    }
+}
@@ -0,0 +1,160 @@
+use bevy::{
+    core_pipeline::Skybox,
+    pbr::{FogFalloff, FogSettings},
+    prelude::*,
+};
+
+use crate::{AssetRef, BlueprintName, GameWorldReady};
+
+/// ambient light of a level, added to the level scene in Blender: replaces the `AmbientLight` resource once the level is ready
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct LevelAmbientLight {
+    pub color: Color,
+    pub brightness: f32,
+}
+
+impl Default for LevelAmbientLight {
+    fn default() -> Self {
+        let AmbientLight { color, brightness } = AmbientLight::default();
+        LevelAmbientLight { color, brightness }
+    }
+}
+
+/// skybox & environment map of a level, added to the level scene in Blender: added to the 3d cameras once the level is ready
+/// * the images need to be cubemaps (ie ktx2 files), they are loaded with the other assets of the level
+/// * leave a path empty to skip it
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct LevelEnvironmentMap {
+    pub skybox: AssetRef<Image>,
+    pub skybox_brightness: f32,
+    pub diffuse_map: AssetRef<Image>,
+    pub specular_map: AssetRef<Image>,
+    pub intensity: f32,
+}
+
+impl Default for LevelEnvironmentMap {
+    fn default() -> Self {
+        LevelEnvironmentMap {
+            skybox: AssetRef::default(),
+            skybox_brightness: 1000.0,
+            diffuse_map: AssetRef::default(),
+            specular_map: AssetRef::default(),
+            intensity: 1000.0,
+        }
+    }
+}
+
+/// distance fog of a level, added to the level scene in Blender: added to the 3d cameras once the level is ready
+/// * linear between `start` & `end`, unless a `density` is given, in which case the fog is exponential
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct LevelFog {
+    pub color: Color,
+    pub start: f32,
+    pub end: f32,
+    pub density: Option<f32>,
+}
+
+impl Default for LevelFog {
+    fn default() -> Self {
+        LevelFog {
+            color: FogSettings::default().color,
+            start: 0.0,
+            end: 100.0,
+            density: None,
+        }
+    }
+}
+
+/// the first component of the given type in the game world, without going into the blueprint instances inside it
+fn find_in_world<'a, T: Component>(
+    world: Entity,
+    all_children: &Query<&Children>,
+    blueprint_instances: &Query<(), With<BlueprintName>>,
+    components: &'a Query<&T>,
+) -> Option<&'a T> {
+    let mut stack = vec![world];
+    while let Some(entity) = stack.pop() {
+        if let Ok(component) = components.get(entity) {
+            return Some(component);
+        }
+        for child in all_children.get(entity).into_iter().flatten() {
+            if !blueprint_instances.contains(*child) {
+                stack.push(*child);
+            }
+        }
+    }
+    None
+}
+
+/// applies the environment settings of game worlds (levels) once they are ready, and to the 3d cameras added afterwards
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn apply_level_environments(
+    ready_worlds: Query<Entity, With<GameWorldReady>>,
+    added_worlds: Query<(), Added<GameWorldReady>>,
+    added_cameras: Query<(), Added<Camera3d>>,
+    cameras: Query<Entity, With<Camera3d>>,
+    all_children: Query<&Children>,
+    blueprint_instances: Query<(), With<BlueprintName>>,
+    ambient_lights: Query<&LevelAmbientLight>,
+    environment_maps: Query<&LevelEnvironmentMap>,
+    fogs: Query<&LevelFog>,
+    mut commands: Commands,
+) {
+    if added_worlds.is_empty() && added_cameras.is_empty() {
+        return;
+    }
+    for world in ready_worlds.iter() {
+        if let Some(ambient_light) =
+            find_in_world(world, &all_children, &blueprint_instances, &ambient_lights)
+        {
+            commands.insert_resource(AmbientLight {
+                color: ambient_light.color,
+                brightness: ambient_light.brightness,
+            });
+        }
+
+        let environment_map = find_in_world(
+            world,
+            &all_children,
+            &blueprint_instances,
+            &environment_maps,
+        );
+        let fog = find_in_world(world, &all_children, &blueprint_instances, &fogs);
+        for camera in cameras.iter() {
+            let mut camera_commands = commands.entity(camera);
+            if let Some(environment_map) = environment_map {
+                if !environment_map.skybox.0.is_empty() {
+                    camera_commands.insert(Skybox {
+                        image: environment_map.skybox.get().clone(),
+                        brightness: environment_map.skybox_brightness,
+                    });
+                }
+                if !environment_map.diffuse_map.0.is_empty()
+                    && !environment_map.specular_map.0.is_empty()
+                {
+                    camera_commands.insert(EnvironmentMapLight {
+                        diffuse_map: environment_map.diffuse_map.get().clone(),
+                        specular_map: environment_map.specular_map.get().clone(),
+                        intensity: environment_map.intensity,
+                    });
+                }
+            }
+            if let Some(fog) = fog {
+                camera_commands.insert(FogSettings {
+                    color: fog.color,
+                    falloff: match fog.density {
+                        Some(density) => FogFalloff::Exponential { density },
+                        None => FogFalloff::Linear {
+                            start: fog.start,
+                            end: fog.end,
+                        },
+                    },
+                    ..Default::default()
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use core::fmt;
+use std::{path::PathBuf, time::Duration};
+
+use bevy::prelude::*;
+
+/// the errors that can happen while spawning a blueprint instance
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlueprintSpawnError {
+    /// the gltf file of the blueprint is not (or could not be) loaded
+    GltfNotLoaded { path: PathBuf },
+    /// one of the assets needed by the blueprint failed to load
+    AssetLoadFailed { path: PathBuf },
+    /// the assets needed by the blueprint were still not loaded after the configured timeout
+    AssetLoadTimeout { timeout: Duration },
+    /// the gltf file of the blueprint does not contain any named scene to spawn
+    NoNamedScene { path: PathBuf },
+    /// the scene requested with a `BlueprintScene` component does not exist in the gltf file of the blueprint
+    SceneNotFound { path: PathBuf, scene_name: String },
+    /// the instance should be added to the game world, but there is not exactly one entity tagged with `GameWorldTag`
+    /// (use `TargetGameWorld` to pick one if there are several)
+    GameWorldUnavailable,
+    /// the instance should be added to the game world with the given `GameWorldName`, but there is no such game world
+    GameWorldNotFound { name: String },
+    /// the entity requested as parent with `SpawnParent` does not exist
+    ParentNotFound { parent: Entity },
+    /// there is no entity with the name requested with `SpawnAtMarker`
+    MarkerNotFound { marker: String },
+    /// the spawned blueprint scene still had no root node after the given amount of retries
+    NoSceneRoot { retries: u32 },
+    /// the blueprint contains itself, directly or through a chain of nested blueprints (from outermost to innermost)
+    BlueprintCycle { chain: Vec<PathBuf> },
+    /// the blueprint is nested deeper than the configured `max_blueprint_nesting_depth`
+    NestingTooDeep { path: PathBuf, max_depth: usize },
+    /// a component of an object of the blueprint could not be parsed (only with `Strictness::FailInstance`)
+    InvalidComponent {
+        object_name: String,
+        component_type: Option<String>,
+        error: String,
+    },
+}
+
+impl fmt::Display for BlueprintSpawnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlueprintSpawnError::GltfNotLoaded { path } => {
+                write!(f, "gltf file {:?} is not loaded", path)
+            }
+            BlueprintSpawnError::AssetLoadFailed { path } => {
+                write!(f, "asset {:?} failed to load", path)
+            }
+            BlueprintSpawnError::AssetLoadTimeout { timeout } => {
+                write!(f, "assets still not loaded after {:?}", timeout)
+            }
+            BlueprintSpawnError::NoNamedScene { path } => {
+                write!(f, "gltf file {:?} does not contain any named scene", path)
+            }
+            BlueprintSpawnError::SceneNotFound { path, scene_name } => {
+                write!(
+                    f,
+                    "gltf file {:?} does not contain a scene named {}",
+                    path, scene_name
+                )
+            }
+            BlueprintSpawnError::GameWorldUnavailable => {
+                write!(
+                    f,
+                    "there should be exactly one entity tagged with GameWorldTag"
+                )
+            }
+            BlueprintSpawnError::GameWorldNotFound { name } => {
+                write!(f, "there is no game world named {}", name)
+            }
+            BlueprintSpawnError::ParentNotFound { parent } => {
+                write!(f, "parent entity {:?} does not exist", parent)
+            }
+            BlueprintSpawnError::MarkerNotFound { marker } => {
+                write!(f, "there is no marker entity named {}", marker)
+            }
+            BlueprintSpawnError::NoSceneRoot { retries } => {
+                write!(
+                    f,
+                    "no root node found in the spawned blueprint scene after {} retries",
+                    retries
+                )
+            }
+            BlueprintSpawnError::BlueprintCycle { chain } => {
+                let chain: Vec<String> = chain.iter().map(|path| format!("{:?}", path)).collect();
+                write!(f, "blueprint contains itself: {}", chain.join(" -> "))
+            }
+            BlueprintSpawnError::NestingTooDeep { path, max_depth } => {
+                write!(
+                    f,
+                    "blueprint {:?} is nested more than {} levels deep",
+                    path, max_depth
+                )
+            }
+            BlueprintSpawnError::InvalidComponent {
+                object_name,
+                component_type,
+                error,
+            } => {
+                write!(
+                    f,
+                    "invalid component {} on object {}: {}",
+                    component_type.as_deref().unwrap_or("gltf extras"),
+                    object_name,
+                    error
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BlueprintSpawnError {}
+
+/// events sent at the various stages of blueprint spawning
+#[derive(Event, Debug, Clone)]
+pub enum BlueprintEvent {
+    /// all the assets needed by the blueprint instance are loaded
+    /// (see also the [`crate::BlueprintInstanceAssets`] component of the instance)
+    AssetsLoaded {
+        entity: Entity,
+        blueprint_name: String,
+        assets: Vec<crate::BlueprintAssetInfo>,
+    },
+    /// one of the assets needed by the blueprint instance failed to load
+    AssetLoadFailed {
+        entity: Entity,
+        blueprint_name: String,
+        asset_path: String,
+    },
+    /// the assets needed by the blueprint instance are still not loaded after the configured timeout
+    /// (the spawn also fails if `fail_on_asset_load_timeout` is enabled)
+    LoadTimeout {
+        entity: Entity,
+        blueprint_name: String,
+        pending_assets: Vec<String>,
+    },
+    /// loading one of the assets needed by the blueprint instance failed, and is retried (see `asset_retry` in the plugin)
+    AssetLoadRetry {
+        entity: Entity,
+        blueprint_name: String,
+        asset_path: String,
+        attempt: u32,
+    },
+    /// a blueprint instance nested inside another blueprint instance (`parent`) is fully spawned & post processed
+    SubInstanceReady {
+        parent: Entity,
+        child: Entity,
+        blueprint_name: String,
+    },
+    /// the blueprint (& its dependencies) requested with [`crate::PreloadBlueprint`] is loaded
+    Preloaded { blueprint_name: String },
+    /// the blueprint instance is fully spawned & post processed
+    InstanceReady {
+        entity: Entity,
+        blueprint_name: String,
+    },
+    /// spawning the blueprint instance failed, the entity is tagged with [`BlueprintSpawnFailed`]
+    SpawnFailed {
+        entity: Entity,
+        error: BlueprintSpawnError,
+    },
+    /// the game world (entity with [`crate::GameWorldTag`]) and all the blueprint instances inside it are fully spawned
+    WorldReady { world: Entity },
+    /// the blueprint instance is about to be spawned again, after a [`crate::RespawnBlueprint`] request
+    Respawning {
+        entity: Entity,
+        blueprint_name: String,
+    },
+    /// the spawning of the blueprint instance was cancelled (see [`crate::CancelSpawn`]),
+    /// the blueprint name is not available if the entity was despawned
+    SpawnCancelled {
+        entity: Entity,
+        blueprint_name: Option<String>,
+    },
+    /// the blueprint instance was despawned using [`crate::DespawnBlueprint`]
+    Despawned {
+        entity: Entity,
+        blueprint_name: String,
+    },
+    /// all the instances of a [`crate::SpawnBlueprintBatch`] are done spawning (see their [`crate::BlueprintSpawnState`] for failures)
+    BatchReady {
+        batch: Entity,
+        blueprint_name: String,
+        instances: Vec<Entity>,
+    },
+    /// the blueprint instance was attached to the socket of its [`crate::AttachTo`] target
+    Attached {
+        entity: Entity,
+        target: Entity,
+        socket: String,
+    },
+    /// the blueprint instance could not be attached: the target of its [`crate::AttachTo`] or the socket do not exist
+    AttachmentFailed {
+        entity: Entity,
+        target: Entity,
+        socket: String,
+    },
+    /// spawning the blueprint instance failed, and the configured fallback blueprint is spawned instead
+    FallbackSpawned {
+        entity: Entity,
+        blueprint_name: String,
+        error: BlueprintSpawnError,
+    },
+    /// a [`crate::NamedEntityRef`] in a component of the entity still does not match any named entity once nothing is spawning anymore
+    DanglingEntityRef {
+        entity: Entity,
+        component: String,
+        name: String,
+    },
+}
+
+/// sent when hot reload kicks in, so that games & editors can show "reloading..." indicators, pause the simulation, etc
+#[derive(Event, Debug, Clone)]
+pub enum BlueprintHotReloadEvent {
+    /// assets (gltf files, textures) of blueprints changed on disk, the listed instances are being respawned
+    BlueprintChanged {
+        paths: Vec<String>,
+        entities: Vec<Entity>,
+    },
+    /// material libraries changed on disk, the materials of the listed entities are being updated
+    MaterialChanged {
+        paths: Vec<String>,
+        entities: Vec<Entity>,
+    },
+}
+
+/// component inserted into blueprint instances that could not be spawned, instead of crashing
+#[derive(Component, Debug, Clone)]
+pub struct BlueprintSpawnFailed(pub BlueprintSpawnError);
@@ -0,0 +1,46 @@
+use bevy::{prelude::*, scene::SceneInstance};
+
+use crate::{BlueprintEvent, GameWorldTag, SpawnHere};
+
+#[derive(Component, Debug)]
+/// flag component inserted into the game world (entity with [`GameWorldTag`]) once it and all the blueprint instances it contains are fully spawned
+pub struct GameWorldReady;
+
+/// sends a `BlueprintEvent::WorldReady` event once a game world and all the blueprint instances inside it are spawned
+pub(crate) fn check_for_world_ready(
+    worlds: Query<
+        (Entity, Option<&SceneInstance>),
+        (
+            With<GameWorldTag>,
+            Without<GameWorldReady>,
+            Without<SpawnHere>,
+        ),
+    >,
+    spawning: Query<(), With<SpawnHere>>,
+    all_children: Query<&Children>,
+    scene_spawner: Res<SceneSpawner>,
+
+    mut commands: Commands,
+    mut blueprint_events: EventWriter<BlueprintEvent>,
+) {
+    for (world, scene_instance) in worlds.iter() {
+        // the content of the world itself needs to be there first
+        if let Some(scene_instance) = scene_instance {
+            if !scene_spawner.instance_is_ready(**scene_instance) {
+                continue;
+            }
+        }
+        if !all_children.contains(world) {
+            continue;
+        }
+
+        let still_spawning = all_children
+            .iter_descendants(world)
+            .any(|descendant| spawning.contains(descendant));
+        if !still_spawning {
+            debug!("game world {:?} is ready", world);
+            commands.entity(world).insert(GameWorldReady);
+            blueprint_events.send(BlueprintEvent::WorldReady { world });
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use std::path::PathBuf;
+
+use bevy::{
+    asset::UntypedAssetId,
+    ecs::entity::Entities,
+    gltf::Gltf,
+    prelude::*,
+    utils::{HashMap, HashSet, Instant},
+};
+
+use crate::{
+    BluePrintsConfig, BlueprintEvent, BlueprintHotReloadEvent, BlueprintInstanceAssets,
+    BlueprintName, MaterialInfo, MaterialLibraryCache, RespawnBlueprint, SpawnHere,
+};
+
+/// added to blueprint instances whose nested blueprint instances are being hot reloaded:
+/// the instance is considered as spawning again (see [`crate::BlueprintSpawnProgress`]) until all of them are ready,
+/// at which point a new `BlueprintEvent::InstanceReady` event is sent for it
+#[derive(Component, Debug, Default)]
+pub(crate) struct AwaitingSubInstanceReload(pub(crate) HashSet<Entity>);
+
+/// an instance waiting to be reloaded
+#[derive(Debug)]
+pub(crate) struct PendingHotReload {
+    /// time of the last change to one of the assets of the instance
+    pub(crate) changed_at: Instant,
+    /// paths of the changed assets
+    pub(crate) paths: HashSet<String>,
+}
+
+/// instances waiting to be reloaded:
+/// an export from Blender often touches a gltf file & several textures, so changes are coalesced into a single reload
+#[derive(Resource, Debug, Default)]
+pub(crate) struct PendingHotReloads(pub(crate) HashMap<Entity, PendingHotReload>);
+
+/// respawns the blueprint instances whose assets (gltf files, textures) changed on disk (requires asset watching to be enabled):
+/// the runtime state of the instances is preserved, as only the authored components that changed in the blueprint are re-applied
+/// (see [`crate::CopyComponents`]), and their original children are kept
+/// * for nested blueprints, only the innermost instances using the changed files are respawned, not the ones containing them
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn react_to_asset_changes(
+    mut gltf_events: EventReader<AssetEvent<Gltf>>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    blueprint_instances: Query<(Entity, &BlueprintInstanceAssets), Without<SpawnHere>>,
+    mut awaiting_instances: Query<&mut AwaitingSubInstanceReload>,
+    all_children: Query<&Children>,
+    all_parents: Query<&Parent>,
+    blueprints_config: Res<BluePrintsConfig>,
+    mut pending_reloads: ResMut<PendingHotReloads>,
+    mut hot_reload_events: EventWriter<BlueprintHotReloadEvent>,
+    mut commands: Commands,
+) {
+    let mut modified: HashSet<UntypedAssetId> = HashSet::new();
+    for event in gltf_events.read() {
+        if let AssetEvent::Modified { id } = event {
+            modified.insert(id.untyped());
+        }
+    }
+    for event in image_events.read() {
+        if let AssetEvent::Modified { id } = event {
+            modified.insert(id.untyped());
+        }
+    }
+
+    let now = Instant::now();
+    if !modified.is_empty() {
+        for (entity, instance_assets) in blueprint_instances.iter() {
+            let changed_paths: Vec<String> = instance_assets
+                .0
+                .iter()
+                .filter(|asset| modified.contains(&asset.handle.id()))
+                .map(|asset| asset.path.clone())
+                .collect();
+            if changed_paths.is_empty() {
+                continue;
+            }
+            let pending = pending_reloads
+                .0
+                .entry(entity)
+                .or_insert_with(|| PendingHotReload {
+                    changed_at: now,
+                    paths: HashSet::new(),
+                });
+            pending.changed_at = now;
+            pending.paths.extend(changed_paths);
+        }
+    }
+
+    // only reload once no more changes came in during the debounce window
+    let debounce = blueprints_config.hot_reload_debounce;
+    let mut ready: HashMap<Entity, HashSet<String>> = HashMap::new();
+    pending_reloads.0.retain(|entity, pending| {
+        if now.duration_since(pending.changed_at) < debounce {
+            return true;
+        }
+        if blueprint_instances.contains(*entity) {
+            ready.insert(*entity, std::mem::take(&mut pending.paths));
+        }
+        false
+    });
+
+    // the assets of nested blueprints are also listed in the assets of the instances containing them:
+    // changed files used by a nested instance only require respawning that nested instance
+    let mut to_reload: Vec<Entity> = ready
+        .iter()
+        .filter(|(entity, paths)| {
+            paths.iter().any(|path| {
+                !all_children.iter_descendants(**entity).any(|descendant| {
+                    ready
+                        .get(&descendant)
+                        .is_some_and(|descendant_paths| descendant_paths.contains(path))
+                })
+            })
+        })
+        .map(|(entity, _)| *entity)
+        .collect();
+    // nested instances get respawned anyway when one of the instances containing them is respawned
+    let reloaded_set: HashSet<Entity> = to_reload.iter().copied().collect();
+    to_reload.retain(|entity| {
+        !all_parents
+            .iter_ancestors(*entity)
+            .any(|ancestor| reloaded_set.contains(&ancestor))
+    });
+
+    let mut reloaded_paths: HashSet<String> = HashSet::new();
+    let mut reloaded_entities: Vec<Entity> = vec![];
+    for entity in to_reload {
+        debug!(
+            "assets of blueprint instance {:?} changed, reloading",
+            entity
+        );
+        commands.entity(entity).insert(RespawnBlueprint);
+        if let Some(paths) = ready.remove(&entity) {
+            reloaded_paths.extend(paths);
+        }
+        reloaded_entities.push(entity);
+
+        // the instances containing it are not fully spawned anymore until it is ready again
+        for ancestor in all_parents.iter_ancestors(entity) {
+            if !blueprint_instances.contains(ancestor) {
+                continue;
+            }
+            if let Ok(mut awaiting) = awaiting_instances.get_mut(ancestor) {
+                awaiting.0.insert(entity);
+            } else {
+                commands
+                    .entity(ancestor)
+                    .insert(AwaitingSubInstanceReload(HashSet::from([entity])));
+            }
+        }
+    }
+
+    if !reloaded_entities.is_empty() {
+        let mut paths: Vec<String> = reloaded_paths.into_iter().collect();
+        paths.sort();
+        hot_reload_events.send(BlueprintHotReloadEvent::BlueprintChanged {
+            paths,
+            entities: reloaded_entities,
+        });
+    }
+}
+
+/// when a material library changes on disk, the updated materials are injected in place into the entities using them,
+/// instead of respawning every blueprint instance that references them
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn react_to_material_library_changes(
+    mut gltf_events: EventReader<AssetEvent<Gltf>>,
+    material_infos: Query<(Entity, &MaterialInfo, &Children)>,
+    with_materials_and_meshes: Query<
+        (),
+        (
+            With<Parent>,
+            With<Handle<StandardMaterial>>,
+            With<Handle<Mesh>>,
+        ),
+    >,
+    assets_gltf: Res<Assets<Gltf>>,
+    asset_server: Res<AssetServer>,
+    blueprints_config: Res<BluePrintsConfig>,
+    mut material_library_cache: ResMut<MaterialLibraryCache>,
+    mut hot_reload_events: EventWriter<BlueprintHotReloadEvent>,
+    mut commands: Commands,
+) {
+    let mut changed_libraries: HashMap<PathBuf, AssetId<Gltf>> = HashMap::new();
+    for event in gltf_events.read() {
+        if let AssetEvent::Modified { id } = event {
+            if let Some(path) = asset_server.get_path(*id) {
+                changed_libraries.insert(path.path().to_path_buf(), *id);
+            }
+        }
+    }
+    if changed_libraries.is_empty() {
+        return;
+    }
+
+    let mut reloaded_paths: HashSet<String> = HashSet::new();
+    let mut updated_entities: Vec<Entity> = vec![];
+    for (entity, material_info, children) in material_infos.iter() {
+        let materials_path = blueprints_config.material_library_path(&material_info.source);
+        let Some(library_id) = changed_libraries.get(&materials_path) else {
+            continue;
+        };
+        let Some(material) = assets_gltf
+            .get(*library_id)
+            .and_then(|library| library.named_materials.get(&material_info.name))
+        else {
+            warn!(
+                "material {} not found in reloaded material library {:?}",
+                material_info.name, materials_path
+            );
+            continue;
+        };
+
+        material_library_cache.materials.insert(
+            (materials_path.clone(), material_info.name.clone()),
+            material.clone(),
+        );
+
+        for child in children.iter() {
+            if with_materials_and_meshes.contains(*child) {
+                debug!(
+                    "re-injecting material {}, path: {:?}",
+                    material_info.name, materials_path
+                );
+                commands.entity(*child).insert(material.clone());
+            }
+        }
+        reloaded_paths.insert(materials_path.to_string_lossy().to_string());
+        updated_entities.push(entity);
+    }
+
+    if !updated_entities.is_empty() {
+        let mut paths: Vec<String> = reloaded_paths.into_iter().collect();
+        paths.sort();
+        hot_reload_events.send(BlueprintHotReloadEvent::MaterialChanged {
+            paths,
+            entities: updated_entities,
+        });
+    }
+}
+
+/// once all the hot reloaded blueprint instances nested inside an instance are ready (or failed to spawn), the instance is ready again
+pub(crate) fn finalize_sub_instance_reloads(
+    mut awaiting_instances: Query<(Entity, &BlueprintName, &mut AwaitingSubInstanceReload)>,
+    mut blueprint_events: ParamSet<(EventReader<BlueprintEvent>, EventWriter<BlueprintEvent>)>,
+    entities: &Entities,
+    mut commands: Commands,
+) {
+    if awaiting_instances.is_empty() {
+        return;
+    }
+    let mut done: HashSet<Entity> = HashSet::new();
+    for event in blueprint_events.p0().read() {
+        match event {
+            BlueprintEvent::InstanceReady { entity, .. }
+            | BlueprintEvent::SpawnFailed { entity, .. }
+            | BlueprintEvent::SpawnCancelled { entity, .. } => {
+                done.insert(*entity);
+            }
+            _ => {}
+        }
+    }
+
+    let mut ready_again: Vec<(Entity, String)> = vec![];
+    for (entity, blueprint_name, mut awaiting) in awaiting_instances.iter_mut() {
+        awaiting.0.retain(|sub_instance| {
+            !done.contains(sub_instance) && entities.contains(*sub_instance)
+        });
+        if awaiting.0.is_empty() {
+            commands
+                .entity(entity)
+                .remove::<AwaitingSubInstanceReload>();
+            ready_again.push((entity, blueprint_name.0.clone()));
+        }
+    }
+    for (entity, blueprint_name) in ready_again {
+        debug!(
+            "blueprint instance {:?} is ready again after hot reload",
+            entity
+        );
+        blueprint_events.p1().send(BlueprintEvent::InstanceReady {
+            entity,
+            blueprint_name,
+        });
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+};
+
+use bevy::{
+    prelude::*,
+    render::mesh::Indices,
+    utils::{HashMap, HashSet},
+};
+
+/// how many draw calls were saved by consolidating identical meshes & materials (see `consolidate_meshes` in the plugin)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrawCallReport {
+    /// amount of mesh entities
+    pub mesh_entities: usize,
+    /// distinct mesh/material pairs without consolidation
+    pub unique_pairs_before: usize,
+    /// distinct mesh/material pairs with consolidation: what actually gets batched/instanced by bevy
+    pub unique_pairs_after: usize,
+}
+
+impl DrawCallReport {
+    pub fn draw_calls_saved(&self) -> usize {
+        self.unique_pairs_before
+            .saturating_sub(self.unique_pairs_after)
+    }
+}
+
+/// meshes & materials with the same content (ie identical meshes in different blueprints, or the same patched materials
+/// on several instances), that get replaced by a single shared copy so bevy's automatic batching/instancing kicks in
+#[derive(Resource, Debug, Default)]
+pub struct MeshConsolidation {
+    pub(crate) meshes: HashMap<u64, Handle<Mesh>>,
+    pub(crate) mesh_hashes: HashMap<AssetId<Mesh>, u64>,
+    pub(crate) materials: HashMap<u64, Handle<StandardMaterial>>,
+    pub(crate) material_hashes: HashMap<AssetId<StandardMaterial>, u64>,
+    pub(crate) report: DrawCallReport,
+}
+
+impl MeshConsolidation {
+    /// the latest report of saved draw calls
+    pub fn report(&self) -> DrawCallReport {
+        self.report
+    }
+}
+
+/// the mesh & material a mesh entity used before being consolidated
+#[derive(Component, Debug, Clone, Copy)]
+pub(crate) struct ConsolidatedFrom {
+    pub(crate) mesh: AssetId<Mesh>,
+    pub(crate) material: AssetId<StandardMaterial>,
+}
+
+fn mesh_hash(mesh: &Mesh) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", mesh.primitive_topology()).hash(&mut hasher);
+    for (attribute, _) in mesh.attributes() {
+        attribute.hash(&mut hasher);
+    }
+    mesh.get_vertex_buffer_data().hash(&mut hasher);
+    match mesh.indices() {
+        Some(Indices::U16(indices)) => indices.hash(&mut hasher),
+        Some(Indices::U32(indices)) => indices.hash(&mut hasher),
+        None => {}
+    }
+    hasher.finish()
+}
+
+fn material_hash(material: &StandardMaterial) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", material).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// the shared copy of an asset with the given content hash, the asset itself if it is the first one
+pub(crate) fn consolidated<A: Asset>(
+    handle: &Handle<A>,
+    hash: impl FnOnce() -> Option<u64>,
+    hashes: &mut HashMap<AssetId<A>, u64>,
+    by_hash: &mut HashMap<u64, Handle<A>>,
+) -> Handle<A> {
+    let hash = match hashes.get(&handle.id()) {
+        Some(hash) => *hash,
+        None => {
+            let Some(hash) = hash() else {
+                // not loaded yet
+                return handle.clone();
+            };
+            hashes.insert(handle.id(), hash);
+            hash
+        }
+    };
+    by_hash
+        .entry(hash)
+        .or_insert_with(|| handle.clone())
+        .clone()
+}
+
+/// only the consolidation cache holds on to it
+pub(crate) fn unused<A: Asset>(handle: &Handle<A>) -> bool {
+    match handle {
+        Handle::Strong(strong) => Arc::strong_count(strong) == 1,
+        Handle::Weak(_) => true,
+    }
+}
+
+/// replaces the meshes & materials of mesh entities with a shared copy, when identical ones are already in use,
+/// and updates the [`DrawCallReport`]
+#[allow(clippy::type_complexity)]
+pub(crate) fn consolidate_meshes(
+    changed: Query<
+        (Entity, &Handle<Mesh>, &Handle<StandardMaterial>),
+        Or<(Changed<Handle<Mesh>>, Changed<Handle<StandardMaterial>>)>,
+    >,
+    all: Query<(
+        &Handle<Mesh>,
+        &Handle<StandardMaterial>,
+        Option<&ConsolidatedFrom>,
+    )>,
+    mut removed: RemovedComponents<Handle<Mesh>>,
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
+    mut material_events: EventReader<AssetEvent<StandardMaterial>>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut consolidation: ResMut<MeshConsolidation>,
+    mut commands: Commands,
+) {
+    let MeshConsolidation {
+        meshes: shared_meshes,
+        mesh_hashes,
+        materials: shared_materials,
+        material_hashes,
+        report,
+    } = &mut *consolidation;
+
+    // assets whose content changed need to be hashed again
+    for event in mesh_events.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = event {
+            mesh_hashes.remove(id);
+        }
+    }
+    for event in material_events.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = event {
+            material_hashes.remove(id);
+        }
+    }
+
+    let mut changes = removed.read().count() > 0;
+    for (entity, mesh, material) in changed.iter() {
+        changes = true;
+        let shared_mesh = consolidated(
+            mesh,
+            || meshes.get(mesh).map(mesh_hash),
+            mesh_hashes,
+            shared_meshes,
+        );
+        let shared_material = consolidated(
+            material,
+            || materials.get(material).map(material_hash),
+            material_hashes,
+            shared_materials,
+        );
+        if shared_mesh == *mesh && shared_material == *material {
+            continue;
+        }
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.insert(ConsolidatedFrom {
+            mesh: mesh.id(),
+            material: material.id(),
+        });
+        if shared_mesh != *mesh {
+            entity_commands.insert(shared_mesh);
+        }
+        if shared_material != *material {
+            entity_commands.insert(shared_material);
+        }
+    }
+    if !changes {
+        return;
+    }
+
+    shared_meshes.retain(|_, handle| !unused(handle));
+    shared_materials.retain(|_, handle| !unused(handle));
+
+    let mut before: HashSet<(AssetId<Mesh>, AssetId<StandardMaterial>)> = HashSet::new();
+    let mut after: HashSet<(AssetId<Mesh>, AssetId<StandardMaterial>)> = HashSet::new();
+    let mut mesh_entities = 0;
+    for (mesh, material, consolidated_from) in all.iter() {
+        mesh_entities += 1;
+        before.insert(
+            consolidated_from
+                .map(|from| (from.mesh, from.material))
+                .unwrap_or((mesh.id(), material.id())),
+        );
+        after.insert((mesh.id(), material.id()));
+    }
+    *report = DrawCallReport {
+        mesh_entities,
+        unique_pairs_before: before.len(),
+        unique_pairs_after: after.len(),
+    };
+}
@@ -0,0 +1,82 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{world_affine, GameWorldReady, GameWorldTag};
+
+/// flag component for gameplay markers (usually empties) placed in levels in Blender, with the type of marker (ie "SpawnPoint", "PatrolPoint"):
+/// markers are indexed in [`LevelMarkers`] by type & name once the level is ready
+#[derive(Component, Reflect, Default, Debug, Clone, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct LevelMarker(pub String);
+
+/// a marker of a level
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedMarker {
+    /// name of the marker entity (ie `PlayerStart_01`)
+    pub name: String,
+    pub entity: Entity,
+    /// global transform of the marker when the level got ready
+    pub transform: GlobalTransform,
+}
+
+/// index of the [`LevelMarker`]s of the ready game worlds (blueprint instances inside them included), by type of marker,
+/// so that game code can find markers without walking the hierarchy
+#[derive(Resource, Debug, Default)]
+pub struct LevelMarkers {
+    pub(crate) by_type: HashMap<String, Vec<IndexedMarker>>,
+}
+
+impl LevelMarkers {
+    /// all the markers of the given type
+    pub fn of_type(&self, marker_type: &str) -> &[IndexedMarker] {
+        self.by_type
+            .get(marker_type)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// the marker with the given type & name
+    pub fn get(&self, marker_type: &str, name: &str) -> Option<&IndexedMarker> {
+        self.of_type(marker_type)
+            .iter()
+            .find(|marker| marker.name == name)
+    }
+
+    /// all the marker types
+    pub fn types(&self) -> impl Iterator<Item = &str> {
+        self.by_type.keys().map(String::as_str)
+    }
+}
+
+/// rebuilds the [`LevelMarkers`] index whenever a game world gets ready or goes away
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn index_level_markers(
+    added_worlds: Query<(), Added<GameWorldReady>>,
+    mut removed_worlds: RemovedComponents<GameWorldTag>,
+    ready_worlds: Query<Entity, With<GameWorldReady>>,
+    all_children: Query<&Children>,
+    parents: Query<&Parent>,
+    transforms: Query<&Transform>,
+    markers: Query<(&LevelMarker, Option<&Name>)>,
+    mut level_markers: ResMut<LevelMarkers>,
+) {
+    if added_worlds.is_empty() && removed_worlds.read().count() == 0 {
+        return;
+    }
+    level_markers.by_type.clear();
+    for world in ready_worlds.iter() {
+        for entity in all_children.iter_descendants(world) {
+            let Ok((marker, name)) = markers.get(entity) else {
+                continue;
+            };
+            level_markers
+                .by_type
+                .entry(marker.0.clone())
+                .or_default()
+                .push(IndexedMarker {
+                    name: name.map(ToString::to_string).unwrap_or_default(),
+                    entity,
+                    transform: world_affine(entity, &parents, &transforms).into(),
+                });
+        }
+    }
+}
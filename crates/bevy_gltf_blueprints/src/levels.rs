@@ -0,0 +1,249 @@
+use std::path::Path;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    BluePrintsConfig, BlueprintEvent, BlueprintSpawnError, BlueprintSpawnProgress, BlueprintsList,
+    GameWorldReady, GameWorldTag, SpawnBlueprintCommandsExt,
+};
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+/// component requesting to replace the current level (entities with [`GameWorldTag`] & their children) by the level stored at the given path
+/// (relative to the assets folder): the entity holding it is despawned once the request is handled
+pub struct LoadLevel(pub String);
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+/// component requesting to despawn the current level (entities with [`GameWorldTag`] & their children)
+/// the entity holding it is despawned once the request is handled
+pub struct UnloadLevel;
+
+#[derive(Component, Debug)]
+/// path of the level loaded into the game world
+pub struct Level(pub String);
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+/// flag component for entities inside the game world that should survive level transitions (player, inventory, managers...):
+/// they are moved out of the game world before it is despawned, and added to the new game world once it is ready
+pub struct Persistent;
+
+/// persistent entities waiting for the next game world to be ready
+#[derive(Component, Debug)]
+pub(crate) struct AwaitingGameWorld;
+
+/// state of the current level, and of the transition to a new level, if any
+#[derive(Resource, Debug, Clone, Default)]
+pub struct LevelState {
+    /// path of the current (or loading) level
+    pub current: Option<String>,
+    /// the game world of the current level
+    pub world: Option<Entity>,
+    /// true while a level is loading
+    pub transitioning: bool,
+    /// 0..1 loading progress of the level (including all the blueprint instances inside it)
+    pub progress: f32,
+}
+
+/// sent when a level starts to be unloaded/loaded
+#[derive(Event, Debug, Clone)]
+pub struct LevelTransitionStarted {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// sent once the new level (and all the blueprint instances inside it) is fully spawned, once the level is unloaded,
+/// or once the new level failed to spawn (with the `error` that occured: persistent entities are then left outside of any game world)
+#[derive(Event, Debug, Clone)]
+pub struct LevelTransitionFinished {
+    pub level: Option<String>,
+    pub world: Option<Entity>,
+    pub error: Option<BlueprintSpawnError>,
+}
+
+/// extension trait for [`Commands`], to switch levels
+pub trait LevelCommandsExt {
+    /// replace the current level by the one stored at the given path (ie "levels/World.glb")
+    fn load_level(&mut self, path: impl Into<String>) -> Entity;
+    /// despawn the current level
+    fn unload_level(&mut self) -> Entity;
+}
+
+impl<'w, 's> LevelCommandsExt for Commands<'w, 's> {
+    fn load_level(&mut self, path: impl Into<String>) -> Entity {
+        self.spawn((Name::from("load_level"), LoadLevel(path.into())))
+            .id()
+    }
+
+    fn unload_level(&mut self) -> Entity {
+        self.spawn((Name::from("unload_level"), UnloadLevel)).id()
+    }
+}
+
+pub(crate) fn handle_level_requests(
+    load_requests: Query<(Entity, &LoadLevel)>,
+    unload_requests: Query<Entity, With<UnloadLevel>>,
+    worlds: Query<Entity, With<GameWorldTag>>,
+    persistent: Query<(), With<Persistent>>,
+    all_children: Query<&Children>,
+    all_parents: Query<&Parent>,
+    mut level_state: ResMut<LevelState>,
+    mut commands: Commands,
+    mut transition_started: EventWriter<LevelTransitionStarted>,
+    mut transition_finished: EventWriter<LevelTransitionFinished>,
+) {
+    if load_requests.is_empty() && unload_requests.is_empty() {
+        return;
+    }
+    // only the last load request matters, the rest would be despawned right away anyway
+    let requested_level = load_requests.iter().last().map(|(_, load)| load.0.clone());
+    for (request, _) in load_requests.iter() {
+        commands.entity(request).despawn_recursive();
+    }
+    for request in unload_requests.iter() {
+        commands.entity(request).despawn_recursive();
+    }
+
+    transition_started.send(LevelTransitionStarted {
+        from: level_state.current.clone(),
+        to: requested_level.clone(),
+    });
+    for world in worlds.iter() {
+        // move persistent entities out of the world first (nested persistent entities simply follow their persistent ancestor)
+        for descendant in all_children.iter_descendants(world) {
+            if !persistent.contains(descendant) {
+                continue;
+            }
+            let has_persistent_ancestor = all_parents
+                .iter_ancestors(descendant)
+                .take_while(|ancestor| *ancestor != world)
+                .any(|ancestor| persistent.contains(ancestor));
+            if !has_persistent_ancestor {
+                debug!(
+                    "keeping persistent entity {:?} out of the level transition",
+                    descendant
+                );
+                commands
+                    .entity(descendant)
+                    .remove_parent_in_place()
+                    .insert(AwaitingGameWorld);
+            }
+        }
+        debug!("despawning game world {:?}", world);
+        commands.entity(world).despawn_recursive();
+    }
+
+    let Some(level) = requested_level else {
+        *level_state = LevelState {
+            progress: 1.0,
+            ..Default::default()
+        };
+        transition_finished.send(LevelTransitionFinished {
+            level: None,
+            world: None,
+            error: None,
+        });
+        return;
+    };
+
+    debug!("loading level {}", level);
+    let level_name = Path::new(&level)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let world = commands.spawn_blueprint_from_path(&level).spawn();
+    commands.entity(world).insert((
+        Name::from("world"),
+        GameWorldTag,
+        Level(level.clone()),
+        // so that the level gets loaded before spawning, like any other blueprint
+        BlueprintsList(HashMap::from([(level_name, vec![])])),
+    ));
+    *level_state = LevelState {
+        current: Some(level),
+        world: Some(world),
+        transitioning: true,
+        progress: 0.0,
+    };
+}
+
+pub(crate) fn update_level_transition(
+    worlds: Query<(Option<&BlueprintSpawnProgress>, Has<GameWorldReady>), With<GameWorldTag>>,
+    awaiting_world: Query<Entity, With<AwaitingGameWorld>>,
+    blueprints_config: Res<BluePrintsConfig>,
+    mut level_state: ResMut<LevelState>,
+    mut commands: Commands,
+    mut blueprint_events: EventReader<BlueprintEvent>,
+    mut transition_finished: EventWriter<LevelTransitionFinished>,
+) {
+    let failures: Vec<(Entity, BlueprintSpawnError)> = blueprint_events
+        .read()
+        .filter_map(|event| match event {
+            BlueprintEvent::SpawnFailed { entity, error } => Some((*entity, error.clone())),
+            _ => None,
+        })
+        .collect();
+    if !level_state.transitioning {
+        return;
+    }
+    let Some(world) = level_state.world else {
+        return;
+    };
+    let Ok((spawn_progress, ready)) = worlds.get(world) else {
+        return;
+    };
+
+    if let Some((_, error)) = failures.into_iter().find(|(entity, _)| *entity == world) {
+        // the fallback blueprint (if any) gets spawned in place of the level, the transition goes on with it
+        let level_name = level_state
+            .current
+            .as_ref()
+            .and_then(|level| Path::new(level).file_stem())
+            .map(|stem| stem.to_string_lossy().to_string());
+        if blueprints_config
+            .fallback_blueprint
+            .as_ref()
+            .is_some_and(|fallback| Some(fallback) != level_name.as_ref())
+        {
+            warn!(
+                "level {:?} failed to spawn ({}), using the fallback blueprint instead",
+                level_state.current, error
+            );
+            return;
+        }
+        error!("level {:?} failed to spawn: {}", level_state.current, error);
+        for persistent in awaiting_world.iter() {
+            commands.entity(persistent).remove::<AwaitingGameWorld>();
+        }
+        commands.entity(world).despawn_recursive();
+        transition_finished.send(LevelTransitionFinished {
+            level: level_state.current.clone(),
+            world: None,
+            error: Some(error),
+        });
+        *level_state = LevelState {
+            progress: 1.0,
+            ..Default::default()
+        };
+        return;
+    }
+
+    if ready {
+        for persistent in awaiting_world.iter() {
+            commands
+                .entity(persistent)
+                .set_parent_in_place(world)
+                .remove::<AwaitingGameWorld>();
+        }
+        level_state.transitioning = false;
+        level_state.progress = 1.0;
+        transition_finished.send(LevelTransitionFinished {
+            level: level_state.current.clone(),
+            world: Some(world),
+            error: None,
+        });
+    } else if let Some(spawn_progress) = spawn_progress {
+        level_state.progress = spawn_progress.progress;
+    }
+}
@@ -7,6 +7,74 @@ pub(crate) use spawn_post_process::*;
 pub mod animation;
 pub use animation::*;
 
+pub mod animation_markers;
+pub use animation_markers::*;
+
+pub mod root_motion;
+pub use root_motion::*;
+
+pub mod scene_animations;
+pub use scene_animations::*;
+
+pub mod morph_targets;
+pub use morph_targets::*;
+
+pub mod cameras;
+pub use cameras::*;
+
+pub mod lights;
+pub use lights::*;
+
+pub mod environment;
+pub use environment::*;
+
+pub mod light_probes;
+pub use light_probes::*;
+
+pub mod lightmaps;
+pub use lightmaps::*;
+
+pub mod collision_only;
+pub use collision_only::*;
+
+pub mod colliders;
+pub use colliders::*;
+
+pub mod navmesh;
+pub use navmesh::*;
+
+pub mod splines;
+pub use splines::*;
+
+pub mod level_markers;
+pub use level_markers::*;
+
+pub mod render_flags;
+pub(crate) use render_flags::*;
+
+pub mod lods;
+pub use lods::*;
+
+pub mod spawn_proxies;
+pub use spawn_proxies::*;
+
+pub mod reveal;
+pub use reveal::*;
+
+pub mod pause_until_ready;
+pub use pause_until_ready::*;
+
+pub mod disabled_instances;
+pub use disabled_instances::*;
+
+pub mod blueprint_registry;
+pub use blueprint_registry::*;
+
+#[cfg(feature = "audio")]
+pub mod audio_emitters;
+#[cfg(feature = "audio")]
+pub use audio_emitters::*;
+
 pub mod aabb;
 pub use aabb::*;
 
@@ -16,21 +84,115 @@ pub use materials::*;
 pub mod copy_components;
 pub use copy_components::*;
 
-use core::fmt;
-use std::path::PathBuf;
+pub mod spawn_commands;
+pub use spawn_commands::*;
+
+pub mod events;
+pub use events::*;
+
+pub mod despawn_blueprints;
+pub use despawn_blueprints::*;
+
+pub mod respawn_blueprints;
+pub use respawn_blueprints::*;
 
-use bevy::{
-    prelude::*,
-    render::{primitives::Aabb, view::VisibilitySystems},
-    utils::HashMap,
+pub mod blueprints_cache;
+pub use blueprints_cache::*;
+
+pub mod preload_blueprints;
+pub use preload_blueprints::*;
+
+pub mod loading_progress;
+pub use loading_progress::*;
+
+pub mod game_world;
+pub use game_world::*;
+
+pub mod post_processors;
+pub use post_processors::*;
+
+pub mod batch_spawning;
+pub use batch_spawning::*;
+
+pub mod pooling;
+pub use pooling::*;
+
+pub mod streaming;
+pub use streaming::*;
+
+pub mod levels;
+pub use levels::*;
+
+pub mod attachments;
+pub use attachments::*;
+
+pub mod blueprint_assets;
+pub use blueprint_assets::*;
+
+pub mod hot_reload;
+pub(crate) use hot_reload::*;
+
+pub mod entity_refs;
+pub use entity_refs::*;
+
+pub mod asset_refs;
+pub use asset_refs::*;
+
+pub mod overrides;
+pub use overrides::*;
+
+pub mod constructors;
+pub use constructors::*;
+
+pub mod material_overrides;
+pub use material_overrides::*;
+
+pub mod material_variants;
+pub use material_variants::*;
+
+pub mod texture_dedup;
+pub use texture_dedup::*;
+
+pub mod instancing;
+pub use instancing::*;
+
+pub mod skins;
+pub use skins::*;
+
+use core::fmt;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
 };
+
+use bevy::{prelude::*, render::view::VisibilitySystems, utils::HashMap};
+pub use bevy_gltf_components::Strictness;
 use bevy_gltf_components::{ComponentsFromGltfPlugin, GltfComponentsSet};
 
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
 /// set for the two stages of blueprint based spawning :
+/// - `Spawn` (in `Update`) contains, in order: `PrepareSpawn` => `AssetsCheck` => `SceneSpawn`
+/// - `Runtime` (in `Update`, after `Spawn`) contains the per frame updates of the spawned instances (animations, proxies, root motion ...)
+/// - `AfterSpawn` (in `PostUpdate`) contains, in order: `ComponentTransfer` => `PostProcess` => `Finalize`
+///
+/// use the finer grained sets to insert your own systems between specific stages
 pub enum GltfBlueprintsSet {
     Spawn,
+    /// the spawned instances are kept up to date (animations, proxies, root motion ...)
+    Runtime,
     AfterSpawn,
+    /// blueprint instances are requested (spawn, respawn, despawn, preload) and their assets start loading
+    PrepareSpawn,
+    /// the loading state of the assets of each instance is checked
+    AssetsCheck,
+    /// the scenes of blueprint instances whose assets are loaded are spawned
+    SceneSpawn,
+    /// the components & children of the spawned blueprint scenes are transfered to the instances
+    ComponentTransfer,
+    /// empty by default: for your own post processing of instances, after their components have been transfered & injected
+    PostProcess,
+    /// the instances are cleaned up, revealed & marked as ready
+    Finalize,
 }
 
 #[derive(Bundle)]
@@ -52,11 +214,72 @@ pub struct BluePrintsConfig {
     pub(crate) format: GltfFormat,
     pub(crate) library_folder: PathBuf,
     pub(crate) aabbs: bool,
-    pub(crate) aabb_cache: HashMap<String, Aabb>, // cache for aabbs
+    pub(crate) aabb_settings: AabbSettings,
 
     pub(crate) material_library: bool,
     pub(crate) material_library_folder: PathBuf,
-    pub(crate) material_library_cache: HashMap<String, Handle<StandardMaterial>>,
+
+    pub(crate) fallback_blueprint: Option<String>,
+
+    pub(crate) max_blueprint_spawns_per_frame: Option<usize>,
+    pub(crate) max_blueprint_spawn_time_per_frame: Option<Duration>,
+
+    pub(crate) keep_blueprint_roots: bool,
+
+    pub(crate) max_component_transfer_retries: u32,
+
+    pub(crate) max_blueprint_nesting_depth: Option<usize>,
+
+    pub(crate) asset_load_timeout: Option<Duration>,
+    pub(crate) fail_on_asset_load_timeout: bool,
+
+    pub(crate) asset_retry: Option<AssetRetryPolicy>,
+
+    pub(crate) unload_unused_blueprints: Option<Duration>,
+    pub(crate) blueprint_cache_budget_mb: Option<usize>,
+
+    pub(crate) strictness: Strictness,
+
+    pub(crate) hot_reload: bool,
+    pub(crate) hot_reload_debounce: Duration,
+
+    pub(crate) scan_blueprint_folders: Vec<PathBuf>,
+
+    pub(crate) deduplicate_textures: bool,
+    pub(crate) consolidate_meshes: bool,
+    pub(crate) share_skins: bool,
+
+    pub(crate) root_motion_bone: Option<String>,
+
+    pub(crate) light_conversion: LightConversion,
+}
+
+/// how to retry loading assets that failed to load, before failing the spawn of the blueprint instances using them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AssetRetryPolicy {
+    /// maximum amount of retries
+    pub attempts: u32,
+    /// delay before the first retry, multiplied by the attempt number for the following ones
+    pub backoff: Duration,
+}
+
+impl BluePrintsConfig {
+    /// path of the gltf file of the given blueprint, either in the library folder or in the folder of the `Library` override
+    pub(crate) fn blueprint_path(
+        &self,
+        blueprint_name: &str,
+        library_override: Option<&Library>,
+    ) -> PathBuf {
+        let library_path = library_override.map_or_else(|| &self.library_folder, |l| &l.0);
+        let model_file_name = format!("{}.{}", blueprint_name, self.format);
+        Path::new(library_path).join(Path::new(model_file_name.as_str()))
+    }
+
+    /// path of the material library gltf file of the given source (see [`MaterialInfo`])
+    pub(crate) fn material_library_path(&self, source: &str) -> PathBuf {
+        let model_file_name = format!("{}_materials_library.{}", source, self.format);
+        Path::new(&self.material_library_folder).join(Path::new(model_file_name.as_str()))
+    }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
@@ -83,80 +306,358 @@ impl fmt::Display for GltfFormat {
 /// Plugin for gltf blueprints
 pub struct BlueprintsPlugin {
     pub legacy_mode: bool, // flag that gets passed on to bevy_gltf_components
+    /// Validate the components of blueprints against the type registry when they are loaded, and log the issues (passed on to `bevy_gltf_components`)
+    pub validate_blueprints: bool,
 
     pub format: GltfFormat,
     /// The base folder where library/blueprints assets are loaded from, relative to the executable.
     pub library_folder: PathBuf,
-    /// Automatically generate aabbs for the blueprints root objects
+    /// Automatically generate aabbs for blueprint instances, once they & their nested instances are ready (see also [`BlueprintAabbSettings`])
     pub aabbs: bool,
+    /// How the aabbs of blueprint instances are computed
+    pub aabb_settings: AabbSettings,
     ///
     pub material_library: bool,
     pub material_library_folder: PathBuf,
+    /// Name of a blueprint (in the library folder) to spawn instead of blueprints that fail to spawn (missing or broken gltf files, etc)
+    pub fallback_blueprint: Option<String>,
+    /// Maximum amount of blueprint instances spawned per frame, the rest is spawned over the next frames (no limit by default)
+    pub max_blueprint_spawns_per_frame: Option<usize>,
+    /// Maximum time spent spawning blueprint instances per frame, the rest is spawned over the next frames (no limit by default)
+    pub max_blueprint_spawn_time_per_frame: Option<Duration>,
+    /// Keep the root node of all blueprint scenes instead of flattening them (see [`KeepBlueprintRoot`] to do it per instance)
+    pub keep_blueprint_roots: bool,
+    /// How many frames to wait for the root node of a spawned blueprint scene to show up, before failing the spawn
+    pub max_component_transfer_retries: u32,
+    /// Maximum amount of blueprints nested inside each other, deeper instances fail to spawn (no limit if None)
+    pub max_blueprint_nesting_depth: Option<usize>,
+    /// Time after which a `BlueprintEvent::LoadTimeout` event is sent for instances whose assets are still not loaded (no timeout if None)
+    /// (use an [`AssetLoadTimeout`] component to override it per instance)
+    pub asset_load_timeout: Option<Duration>,
+    /// Fail the spawn of instances whose assets are not loaded after `asset_load_timeout`, instead of only sending an event
+    pub fail_on_asset_load_timeout: bool,
+    /// Retry loading assets that failed to load, before failing the spawn (no retries if None)
+    pub asset_retry: Option<AssetRetryPolicy>,
+    /// Drop the cached handles of blueprints once they have not been used by any instance for that long, to free up memory
+    /// (opt-in: cached blueprints are kept forever if None, the default)
+    pub unload_unused_blueprints: Option<Duration>,
+    /// Unload the least recently used blueprints that are not used by any instance, when the (estimated) memory used by cached blueprints goes over this budget
+    pub blueprint_cache_budget_mb: Option<usize>,
+    /// What to do with components & assets that cannot be loaded: panic, skip them & keep spawning (default), or mark the instance as failed
+    pub strictness: Strictness,
+    /// Reload blueprint instances whose assets changed on disk (requires asset watching to be enabled), on by default in debug builds only
+    pub hot_reload: bool,
+    /// How long to wait after the last change to the assets of a blueprint instance before reloading it,
+    /// so that an export touching several files results in a single reload
+    pub hot_reload_debounce: Duration,
+    /// Folders (relative to the assets folder) scanned for blueprints at startup, to populate the [`BlueprintRegistry`] (not supported on wasm)
+    pub scan_blueprint_folders: Vec<PathBuf>,
+    /// Only keep a single copy of textures with the same content used by different blueprints (ie the same texture embedded in several gltf files)
+    pub deduplicate_textures: bool,
+    /// Make mesh entities using identical meshes & materials (with different handles) share the same ones, so that bevy's automatic batching/instancing kicks in
+    pub consolidate_meshes: bool,
+    /// Share the inverse bind matrices & animation clips with the same content between blueprint instances (see [`SharedSkins`])
+    pub share_skins: bool,
+    /// Name of the root bone of animated blueprints (ie "Root"): when set, its horizontal motion is stripped from the animation clips,
+    /// and made available as a [`RootMotion`] component on the instances, for character controllers to use
+    pub root_motion_bone: Option<String>,
+    /// Conversion factors & shadow settings applied to the lights imported from Blender
+    pub light_conversion: LightConversion,
 }
 
 impl Default for BlueprintsPlugin {
     fn default() -> Self {
         Self {
             legacy_mode: true,
+            validate_blueprints: false,
             format: GltfFormat::GLB,
             library_folder: PathBuf::from("models/library"),
             aabbs: false,
+            aabb_settings: AabbSettings::default(),
             material_library: false,
             material_library_folder: PathBuf::from("materials"),
+            fallback_blueprint: None,
+            max_blueprint_spawns_per_frame: None,
+            max_blueprint_spawn_time_per_frame: None,
+            keep_blueprint_roots: false,
+            max_component_transfer_retries: 10,
+            max_blueprint_nesting_depth: Some(32),
+            asset_load_timeout: None,
+            fail_on_asset_load_timeout: false,
+            asset_retry: None,
+            unload_unused_blueprints: None,
+            blueprint_cache_budget_mb: None,
+            strictness: Strictness::SkipAndWarn,
+            hot_reload: cfg!(debug_assertions),
+            hot_reload_debounce: Duration::from_millis(500),
+            scan_blueprint_folders: vec![],
+            deduplicate_textures: false,
+            consolidate_meshes: false,
+            share_skins: false,
+            root_motion_bone: None,
+            light_conversion: LightConversion::default(),
         }
     }
 }
 
-fn aabbs_enabled(blueprints_config: Res<BluePrintsConfig>) -> bool {
-    blueprints_config.aabbs
-}
-
 fn materials_library_enabled(blueprints_config: Res<BluePrintsConfig>) -> bool {
     blueprints_config.material_library
 }
 
+fn texture_deduplication_enabled(blueprints_config: Res<BluePrintsConfig>) -> bool {
+    blueprints_config.deduplicate_textures
+}
+
+fn mesh_consolidation_enabled(blueprints_config: Res<BluePrintsConfig>) -> bool {
+    blueprints_config.consolidate_meshes
+}
+
+fn skin_sharing_enabled(blueprints_config: Res<BluePrintsConfig>) -> bool {
+    blueprints_config.share_skins
+}
+
+fn root_motion_enabled(blueprints_config: Res<BluePrintsConfig>) -> bool {
+    blueprints_config.root_motion_bone.is_some()
+}
+
+/// run condition: true if hot reloading of blueprints is enabled, to gate your own hot reload related systems
+pub fn hot_reload_enabled(blueprints_config: Res<BluePrintsConfig>) -> bool {
+    blueprints_config.hot_reload
+}
+
 impl Plugin for BlueprintsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(ComponentsFromGltfPlugin {
             legacy_mode: self.legacy_mode,
+            validate: self.validate_blueprints,
+            strictness: self.strictness,
         })
         .register_type::<BlueprintName>()
         .register_type::<MaterialInfo>()
         .register_type::<SpawnHere>()
+        .register_type::<HideUntilReady>()
+        .register_type::<GameWorldName>()
+        .register_type::<TargetGameWorld>()
+        .register_type::<SpawnAtMarker>()
+        .register_type::<AssetLoadTimeout>()
+        .register_type::<KeepBlueprintRoot>()
+        .register_type::<SpawnPriority>()
+        .register_type::<BlueprintScene>()
+        .register_type::<BlueprintVariant>()
+        .register_type::<BlueprintSpawnState>()
+        .register_type::<BlueprintSpawnProgress>()
+        .register_type::<DespawnBlueprint>()
+        .register_type::<CancelSpawn>()
+        .register_type::<RespawnBlueprint>()
+        .register_type::<PreloadBlueprint>()
+        .register_type::<SpawnBlueprintBatch>()
+        .register_type::<PooledInactive>()
+        .register_type::<BlueprintStreamer>()
+        .register_type::<BlueprintStreamingTarget>()
+        .register_type::<BlueprintStreamingGrid>()
+        .register_type::<LoadLevel>()
+        .register_type::<UnloadLevel>()
+        .register_type::<Persistent>()
         .register_type::<Animations>()
         .register_type::<BlueprintsList>()
+        .register_type::<NamedEntityRef>()
+        .register_type::<BlueprintOverrides>()
+        .register_type::<MaterialVariants>()
+        .register_type::<RootMotion>()
+        .register_type::<AutoplayAnimation>()
+        .register_type::<MorphTargetNames>()
+        .register_type::<ActiveCamera>()
+        .register_type::<LightSettings>()
+        .register_type::<LightsScale>()
+        .register_type::<LevelAmbientLight>()
+        .register_type::<LevelEnvironmentMap>()
+        .register_type::<LevelFog>()
+        .register_type::<ReflectionProbe>()
+        .register_type::<IrradianceVolumeProbe>()
+        .register_type::<BakedLightmap>()
+        .register_type::<CollisionOnly>()
+        .register_type::<RenderOnly>()
+        .register_type::<ColliderShape>()
+        .register_type::<ColliderDescription>()
+        .register_type::<CompoundCollider>()
+        .register_type::<NavMeshTag>()
+        .register_type::<Splines>()
+        .register_type::<Spline>()
+        .register_type::<SplinePoint>()
+        .register_type::<LevelMarker>()
+        .register_type::<BlueprintLods>()
+        .register_type::<SpawnProxy>()
+        .register_type::<RevealMode>()
+        .register_type::<PauseUntilReady>()
+        .register_type::<SpawnDisabled>()
+        .register_type::<BlueprintInstanceDisabled>()
+        .register_type::<BlueprintAabbSettings>()
+        .register_type::<BlueprintTags>()
+        .register_blueprint_asset_ref::<Image>()
+        .register_blueprint_asset_ref::<Mesh>()
+        .register_blueprint_asset_ref::<StandardMaterial>()
+        .register_blueprint_asset_ref::<Scene>()
+        .register_blueprint_asset_ref::<AnimationClip>()
+        .register_blueprint_asset_ref::<bevy::gltf::Gltf>()
         .register_type::<Vec<String>>()
         .register_type::<HashMap<String, Vec<String>>>()
+        .register_type::<HashMap<String, String>>()
+        .register_type::<Vec<Spline>>()
+        .register_type::<Vec<SplinePoint>>()
+        .add_event::<BlueprintEvent>()
+        .add_event::<BlueprintHotReloadEvent>()
+        .add_event::<GridCellEvent>()
+        .add_event::<LevelTransitionStarted>()
+        .add_event::<LevelTransitionFinished>()
+        .add_event::<BlueprintAnimationMarkerEvent>()
+        .add_event::<LevelNavMeshSources>()
+        .init_resource::<BlueprintAssetsCache>()
+        .init_resource::<BlueprintAabbCache>()
+        .init_resource::<BlueprintAssetRefs>()
+        .init_resource::<BlueprintsLoadingProgress>()
+        .init_resource::<BlueprintPostProcessors>()
+        .init_resource::<BlueprintConstructors>()
+        .init_resource::<BlueprintComponentRules>()
+        .init_resource::<BlueprintPool>()
+        .init_resource::<LevelState>()
+        .init_resource::<PendingHotReloads>()
+        .init_resource::<MaterialLibraryCache>()
+        .init_resource::<PendingEntityRefs>()
+        .init_resource::<TextureDeduplication>()
+        .init_resource::<BlueprintRegistry>()
+        .init_resource::<MeshConsolidation>()
+        .init_resource::<SharedSkins>()
+        .init_resource::<RootMotionCurves>()
+        .init_resource::<LevelMarkers>()
         .insert_resource(BluePrintsConfig {
             format: self.format,
             library_folder: self.library_folder.clone(),
 
             aabbs: self.aabbs,
-            aabb_cache: HashMap::new(),
+            aabb_settings: self.aabb_settings,
 
             material_library: self.material_library,
             material_library_folder: self.material_library_folder.clone(),
-            material_library_cache: HashMap::new(),
+
+            fallback_blueprint: self.fallback_blueprint.clone(),
+
+            max_blueprint_spawns_per_frame: self.max_blueprint_spawns_per_frame,
+            max_blueprint_spawn_time_per_frame: self.max_blueprint_spawn_time_per_frame,
+
+            keep_blueprint_roots: self.keep_blueprint_roots,
+
+            max_component_transfer_retries: self.max_component_transfer_retries,
+
+            max_blueprint_nesting_depth: self.max_blueprint_nesting_depth,
+
+            asset_load_timeout: self.asset_load_timeout,
+            fail_on_asset_load_timeout: self.fail_on_asset_load_timeout,
+
+            asset_retry: self.asset_retry,
+
+            unload_unused_blueprints: self.unload_unused_blueprints,
+            blueprint_cache_budget_mb: self.blueprint_cache_budget_mb,
+
+            strictness: self.strictness,
+
+            hot_reload: self.hot_reload,
+            hot_reload_debounce: self.hot_reload_debounce,
+
+            scan_blueprint_folders: self.scan_blueprint_folders.clone(),
+
+            deduplicate_textures: self.deduplicate_textures,
+            consolidate_meshes: self.consolidate_meshes,
+            share_skins: self.share_skins,
+
+            root_motion_bone: self.root_motion_bone.clone(),
+
+            light_conversion: self.light_conversion,
         })
         .configure_sets(
             Update,
-            (GltfBlueprintsSet::Spawn, GltfBlueprintsSet::AfterSpawn)
+            (
+                GltfBlueprintsSet::Spawn,
+                GltfBlueprintsSet::Runtime,
+                GltfBlueprintsSet::AfterSpawn,
+            )
                 .chain()
                 .after(GltfComponentsSet::Injection),
         )
+        .configure_sets(
+            Update,
+            (
+                GltfBlueprintsSet::PrepareSpawn,
+                GltfBlueprintsSet::AssetsCheck,
+                GltfBlueprintsSet::SceneSpawn,
+            )
+                .chain()
+                .in_set(GltfBlueprintsSet::Spawn),
+        )
+        .configure_sets(
+            PostUpdate,
+            (
+                GltfBlueprintsSet::ComponentTransfer,
+                GltfBlueprintsSet::PostProcess,
+                GltfBlueprintsSet::Finalize,
+            )
+                .chain()
+                .in_set(GltfBlueprintsSet::AfterSpawn),
+        )
+        .configure_sets(
+            PostUpdate,
+            GltfBlueprintsSet::AfterSpawn.before(VisibilitySystems::CheckVisibility),
+        )
+        .add_systems(
+            Update,
+            (
+                (
+                    despawn_blueprints,
+                    respawn_blueprints,
+                    preload_blueprints,
+                    switch_blueprint_variants,
+                    swap_blueprints,
+                    (init_blueprint_lods, update_blueprint_lods),
+                    spawn_blueprint_batches,
+                    cleanup_blueprint_pool,
+                    stream_blueprints,
+                    stream_grid_cells,
+                    handle_level_requests,
+                    cancel_blueprint_spawns,
+                    fail_instances_with_invalid_components,
+                    check_blueprint_references,
+                    react_to_asset_changes.run_if(hot_reload_enabled),
+                    invalidate_aabb_cache.run_if(hot_reload_enabled),
+                    react_to_material_library_changes
+                        .run_if(hot_reload_enabled)
+                        .run_if(materials_library_enabled),
+                ),
+                apply_deferred,
+                prepare_blueprints,
+                apply_deferred,
+            )
+                .chain()
+                .in_set(GltfBlueprintsSet::PrepareSpawn),
+        )
+        .add_systems(
+            Update,
+            (
+                check_for_loaded,
+                update_loading_progress,
+                update_spawn_progress,
+                apply_deferred,
+            )
+                .chain()
+                .in_set(GltfBlueprintsSet::AssetsCheck),
+        )
         .add_systems(
             Update,
             (
                 (
-                    prepare_blueprints,
-                    check_for_loaded,
                     spawn_from_blueprints,
                     apply_deferred,
+                    spawn_fallback_blueprints,
                 )
                     .chain(),
-                (compute_scene_aabbs, apply_deferred)
-                    .chain()
-                    .run_if(aabbs_enabled),
                 apply_deferred,
                 (
                     materials_inject,
@@ -165,16 +666,173 @@ impl Plugin for BlueprintsPlugin {
                 )
                     .chain()
                     .run_if(materials_library_enabled),
+                check_for_world_ready,
             )
                 .chain()
-                .in_set(GltfBlueprintsSet::Spawn),
+                .in_set(GltfBlueprintsSet::SceneSpawn),
+        )
+        .add_systems(
+            Update,
+            (
+                fire_animation_markers,
+                add_scene_animations,
+                spawn_light_probes,
+                apply_baked_lightmaps,
+                collect_navmesh_sources,
+                build_spline_curves,
+                index_level_markers,
+                (spawn_blueprint_proxies, update_blueprint_proxies),
+                update_fade_reveals,
+                compute_instance_aabbs,
+                recompute_instance_aabbs,
+                update_blueprint_registry,
+            )
+                .in_set(GltfBlueprintsSet::Runtime),
+        )
+        .add_systems(Startup, scan_blueprint_folders)
+        .add_systems(
+            Update,
+            (mark_ready_for_finalizing, apply_deferred)
+                .chain()
+                .after(GltfComponentsSet::Injection)
+                .before(GltfBlueprintsSet::Spawn),
+        )
+        .add_systems(
+            Update,
+            (extract_root_motions, update_root_motion)
+                .run_if(root_motion_enabled)
+                .in_set(GltfBlueprintsSet::Runtime),
+        )
+        .add_systems(
+            Update,
+            deduplicate_textures
+                .run_if(texture_deduplication_enabled)
+                .after(GltfBlueprintsSet::SceneSpawn),
         )
         .add_systems(
             PostUpdate,
-            (spawned_blueprint_post_process, apply_deferred)
+            (transfer_blueprint_components, apply_deferred)
                 .chain()
-                .in_set(GltfBlueprintsSet::AfterSpawn)
-                .before(VisibilitySystems::CheckVisibility),
+                .in_set(GltfBlueprintsSet::ComponentTransfer),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                apply_material_overrides,
+                track_source_gltfs,
+                list_material_variants,
+                list_morph_targets,
+                convert_blueprint_lights,
+                process_collision_only_objects,
+                apply_render_flags,
+            )
+                .in_set(GltfBlueprintsSet::PostProcess),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                consolidate_meshes
+                    .run_if(mesh_consolidation_enabled)
+                    .after(apply_material_overrides),
+                share_skins.run_if(skin_sharing_enabled),
+            )
+                .in_set(GltfBlueprintsSet::PostProcess),
+        )
+        .add_systems(
+            PostUpdate,
+            (pause_animations_until_ready, resume_animations_when_ready)
+                .after(GltfBlueprintsSet::ComponentTransfer)
+                .before(GltfBlueprintsSet::Finalize),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                apply_blueprint_overrides,
+                apply_deferred,
+                load_component_asset_refs,
+                check_component_assets_loaded,
+                apply_deferred,
+            )
+                .chain()
+                .after(GltfBlueprintsSet::ComponentTransfer)
+                .before(GltfBlueprintsSet::Finalize),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                finalize_blueprint_instances,
+                apply_deferred,
+                (
+                    check_blueprint_batches,
+                    hide_inactive_pooled_instances,
+                    update_level_transition,
+                    (activate_level_cameras, apply_level_environments).chain(),
+                    attach_to_sockets,
+                    start_fade_reveals,
+                    disable_blueprint_instances,
+                    finalize_sub_instance_reloads,
+                    (update_blueprint_asset_refs, enforce_blueprint_cache_budget).chain(),
+                ),
+                apply_deferred,
+                resolve_entity_refs,
+            )
+                .chain()
+                .in_set(GltfBlueprintsSet::Finalize),
+        );
+
+        #[cfg(feature = "audio")]
+        app.register_type::<AudioEmitter>()
+            .register_blueprint_asset_ref::<AudioSource>()
+            .add_systems(
+                Update,
+                spawn_audio_emitters.in_set(GltfBlueprintsSet::Runtime),
+            );
+
+        #[cfg(feature = "avian")]
+        app.add_systems(
+            PostUpdate,
+            (apply_deferred, build_avian_colliders)
+                .chain()
+                .after(GltfBlueprintsSet::PostProcess)
+                .before(GltfBlueprintsSet::Finalize),
+        )
+        .add_systems(
+            Update,
+            assemble_avian_compound_colliders.in_set(GltfBlueprintsSet::Runtime),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                pause_rigid_bodies_until_ready::<bevy_xpbd_3d::prelude::RigidBody>,
+                resume_rigid_bodies_when_ready::<bevy_xpbd_3d::prelude::RigidBody>,
+            )
+                .after(GltfBlueprintsSet::ComponentTransfer)
+                .before(GltfBlueprintsSet::Finalize),
+        );
+
+        #[cfg(feature = "rapier")]
+        app.add_systems(
+            PostUpdate,
+            (apply_deferred, build_rapier_colliders)
+                .chain()
+                .after(GltfBlueprintsSet::PostProcess)
+                .before(GltfBlueprintsSet::Finalize),
+        )
+        .add_systems(
+            Update,
+            assemble_rapier_compound_colliders.in_set(GltfBlueprintsSet::Runtime),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                pause_rigid_bodies_until_ready::<bevy_rapier3d::prelude::RigidBody>,
+                resume_rigid_bodies_when_ready::<bevy_rapier3d::prelude::RigidBody>,
+            )
+                .after(GltfBlueprintsSet::ComponentTransfer)
+                .before(GltfBlueprintsSet::Finalize),
         );
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,83 @@
+use bevy::{
+    pbr::{irradiance_volume::IrradianceVolume, LightProbe},
+    prelude::*,
+};
+
+use crate::AssetRef;
+
+/// a reflection probe placed in Blender (ie on an empty, scaled to the area it affects): its baked cubemaps are loaded with the other assets of the blueprint,
+/// and it becomes a bevy reflection probe (`LightProbe` & `EnvironmentMapLight`) once they are loaded
+/// * the area affected by the probe is a 1x1x1 cube, scaled/rotated/moved by the transform of the entity
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct ReflectionProbe {
+    pub diffuse_map: AssetRef<Image>,
+    pub specular_map: AssetRef<Image>,
+    pub intensity: f32,
+}
+
+impl Default for ReflectionProbe {
+    fn default() -> Self {
+        ReflectionProbe {
+            diffuse_map: AssetRef::default(),
+            specular_map: AssetRef::default(),
+            intensity: 1000.0,
+        }
+    }
+}
+
+/// an irradiance volume placed in Blender, see [`ReflectionProbe`]: becomes a bevy `LightProbe` with an `IrradianceVolume` once its voxels are loaded
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct IrradianceVolumeProbe {
+    /// the baked voxels (ie a ktx2 file)
+    pub voxels: AssetRef<Image>,
+    pub intensity: f32,
+}
+
+impl Default for IrradianceVolumeProbe {
+    fn default() -> Self {
+        IrradianceVolumeProbe {
+            voxels: AssetRef::default(),
+            intensity: 1000.0,
+        }
+    }
+}
+
+/// turns the probes placed in Blender into bevy light probes, once their baked assets are loaded
+pub(crate) fn spawn_light_probes(
+    reflection_probes: Query<(Entity, &ReflectionProbe), Without<LightProbe>>,
+    irradiance_volumes: Query<(Entity, &IrradianceVolumeProbe), Without<LightProbe>>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    for (entity, probe) in reflection_probes.iter() {
+        let (diffuse_map, specular_map) = (probe.diffuse_map.get(), probe.specular_map.get());
+        if !asset_server.is_loaded_with_dependencies(diffuse_map)
+            || !asset_server.is_loaded_with_dependencies(specular_map)
+        {
+            continue;
+        }
+        commands.entity(entity).insert((
+            LightProbe,
+            EnvironmentMapLight {
+                diffuse_map: diffuse_map.clone(),
+                specular_map: specular_map.clone(),
+                intensity: probe.intensity,
+            },
+        ));
+    }
+    for (entity, probe) in irradiance_volumes.iter() {
+        let voxels = probe.voxels.get();
+        if !asset_server.is_loaded_with_dependencies(voxels) {
+            continue;
+        }
+        commands.entity(entity).insert((
+            LightProbe,
+            IrradianceVolume {
+                voxels: voxels.clone(),
+                intensity: probe.intensity,
+            },
+        ));
+    }
+}
@@ -0,0 +1,65 @@
+use bevy::{pbr::Lightmap, prelude::*};
+
+use crate::AssetRef;
+
+/// a baked lightmap, added to objects in Blender (along with a second uv set for their meshes): the lightmap gets loaded with the other assets
+/// of the blueprint/level, and a bevy `Lightmap` is added to the meshes of the object once it is loaded
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct BakedLightmap {
+    /// path of the lightmap texture, relative to the assets folder
+    pub image: AssetRef<Image>,
+    /// the area of the lightmap texture used by the object (in the 0..1 range), for lightmaps shared by several objects
+    pub uv_rect: Rect,
+}
+
+impl Default for BakedLightmap {
+    fn default() -> Self {
+        BakedLightmap {
+            image: AssetRef::default(),
+            uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+        }
+    }
+}
+
+/// flag component for the objects whose [`BakedLightmap`] has been applied to their meshes
+#[derive(Component, Debug)]
+pub(crate) struct BakedLightmapApplied;
+
+/// adds the [`BakedLightmap`]s of objects to their meshes (the object itself & its direct children, as bevy spawns one entity per primitive),
+/// once the lightmaps are loaded
+pub(crate) fn apply_baked_lightmaps(
+    lightmapped: Query<(Entity, &BakedLightmap, Option<&Children>), Without<BakedLightmapApplied>>,
+    mesh_entities: Query<&Handle<Mesh>>,
+    meshes: Res<Assets<Mesh>>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    for (entity, lightmap, children) in lightmapped.iter() {
+        let image = lightmap.image.get();
+        if !asset_server.is_loaded_with_dependencies(image) {
+            continue;
+        }
+        for mesh_entity in std::iter::once(entity).chain(children.into_iter().flatten().copied()) {
+            let Ok(mesh) = mesh_entities.get(mesh_entity) else {
+                continue;
+            };
+            // lightmaps use the second uv set
+            if !meshes
+                .get(mesh)
+                .is_some_and(|mesh| mesh.contains_attribute(Mesh::ATTRIBUTE_UV_1))
+            {
+                warn!(
+                    "the mesh of {:?} has no second uv set, it cannot use the lightmap {}",
+                    mesh_entity, lightmap.image.0
+                );
+                continue;
+            }
+            commands.entity(mesh_entity).insert(Lightmap {
+                image: image.clone(),
+                uv_rect: lightmap.uv_rect,
+            });
+        }
+        commands.entity(entity).insert(BakedLightmapApplied);
+    }
+}
@@ -0,0 +1,139 @@
+use bevy::prelude::*;
+
+use crate::{BluePrintsConfig, BlueprintName, BlueprintReadyForFinalizing};
+
+/// conversion factors applied to the intensity of the lights imported from Blender (through gltf), since they rarely match bevy's out of the box
+/// (ie `point_intensity: 683.0 / (4.0 * PI)` to go from watts to lumens)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightConversion {
+    /// factor applied to the intensity (lumens) of point lights
+    pub point_intensity: f32,
+    /// factor applied to the intensity (lumens) of spot lights
+    pub spot_intensity: f32,
+    /// factor applied to the illuminance (lux) of directional lights
+    pub directional_illuminance: f32,
+    /// enable/disable shadows on all imported lights, unless overriden per light with [`LightSettings`] (kept as imported if None)
+    pub shadows_enabled: Option<bool>,
+}
+
+impl Default for LightConversion {
+    fn default() -> Self {
+        LightConversion {
+            point_intensity: 1.0,
+            spot_intensity: 1.0,
+            directional_illuminance: 1.0,
+            shadows_enabled: None,
+        }
+    }
+}
+
+/// per light settings, added to light objects in Blender (values left to None are kept as imported)
+#[derive(Component, Reflect, Default, Debug, Clone)]
+#[reflect(Component)]
+pub struct LightSettings {
+    /// factor applied to the intensity of the light, on top of the global [`LightConversion`]
+    pub intensity_scale: Option<f32>,
+    pub shadows_enabled: Option<bool>,
+    pub shadow_depth_bias: Option<f32>,
+    pub shadow_normal_bias: Option<f32>,
+}
+
+/// factor applied to the intensity of all the lights of a level (or blueprint), added to the level scene in Blender
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct LightsScale(pub f32);
+
+impl Default for LightsScale {
+    fn default() -> Self {
+        LightsScale(1.0)
+    }
+}
+
+/// the light settings of an entity: bevy's gltf loader spawns lights as children of the node (holding the components) of the Blender light
+fn light_settings<'a>(
+    entity: Entity,
+    parents: &Query<&Parent>,
+    settings: &'a Query<&LightSettings>,
+) -> Option<&'a LightSettings> {
+    settings.get(entity).ok().or_else(|| {
+        parents
+            .get(entity)
+            .ok()
+            .and_then(|parent| settings.get(parent.get()).ok())
+    })
+}
+
+/// converts the lights of blueprint instances, once they are post processed (see `light_conversion` in the plugin)
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(crate) fn convert_blueprint_lights(
+    instances: Query<Entity, Added<BlueprintReadyForFinalizing>>,
+    all_children: Query<&Children>,
+    parents: Query<&Parent>,
+    nested_instances: Query<(), With<BlueprintName>>,
+    scales: Query<&LightsScale>,
+    settings: Query<&LightSettings>,
+    mut lights: Query<
+        (
+            Option<&mut PointLight>,
+            Option<&mut SpotLight>,
+            Option<&mut DirectionalLight>,
+        ),
+        Or<(With<PointLight>, With<SpotLight>, With<DirectionalLight>)>,
+    >,
+    blueprints_config: Res<BluePrintsConfig>,
+) {
+    let conversion = blueprints_config.light_conversion;
+    for instance in instances.iter() {
+        let scale: f32 = std::iter::once(instance)
+            .chain(parents.iter_ancestors(instance))
+            .filter_map(|entity| scales.get(entity).ok())
+            .map(|scale| scale.0)
+            .product();
+
+        let mut stack = vec![instance];
+        while let Some(entity) = stack.pop() {
+            for child in all_children.get(entity).into_iter().flatten() {
+                // nested blueprint instances convert their own lights
+                if !nested_instances.contains(*child) {
+                    stack.push(*child);
+                }
+            }
+            let Ok((point, spot, directional)) = lights.get_mut(entity) else {
+                continue;
+            };
+            let light_settings = light_settings(entity, &parents, &settings);
+            let light_scale = scale
+                * light_settings
+                    .and_then(|light_settings| light_settings.intensity_scale)
+                    .unwrap_or(1.0);
+            let shadows_enabled = light_settings
+                .and_then(|light_settings| light_settings.shadows_enabled)
+                .or(conversion.shadows_enabled);
+            let depth_bias =
+                light_settings.and_then(|light_settings| light_settings.shadow_depth_bias);
+            let normal_bias =
+                light_settings.and_then(|light_settings| light_settings.shadow_normal_bias);
+
+            if let Some(mut point) = point {
+                point.intensity *= conversion.point_intensity * light_scale;
+                point.shadows_enabled = shadows_enabled.unwrap_or(point.shadows_enabled);
+                point.shadow_depth_bias = depth_bias.unwrap_or(point.shadow_depth_bias);
+                point.shadow_normal_bias = normal_bias.unwrap_or(point.shadow_normal_bias);
+            }
+            if let Some(mut spot) = spot {
+                spot.intensity *= conversion.spot_intensity * light_scale;
+                spot.shadows_enabled = shadows_enabled.unwrap_or(spot.shadows_enabled);
+                spot.shadow_depth_bias = depth_bias.unwrap_or(spot.shadow_depth_bias);
+                spot.shadow_normal_bias = normal_bias.unwrap_or(spot.shadow_normal_bias);
+            }
+            if let Some(mut directional) = directional {
+                directional.illuminance *= conversion.directional_illuminance * light_scale;
+                directional.shadows_enabled =
+                    shadows_enabled.unwrap_or(directional.shadows_enabled);
+                directional.shadow_depth_bias = depth_bias.unwrap_or(directional.shadow_depth_bias);
+                directional.shadow_normal_bias =
+                    normal_bias.unwrap_or(directional.shadow_normal_bias);
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use bevy::{gltf::Gltf, prelude::*};
+
+use crate::{AssetsToLoad, AwaitingSubInstanceReload, BlueprintName, SpawnHere};
+
+/// loading progress of all the blueprint instances currently being spawned, aggregated into a single resource
+/// (ie to drive a loading bar)
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct BlueprintsLoadingProgress {
+    /// amount of blueprint instances that are currently being spawned
+    pub spawning_instances: usize,
+    /// total amount of assets required by the instances being spawned
+    pub total_assets: usize,
+    /// amount of assets (out of `total_assets`) that are loaded
+    pub loaded_assets: usize,
+    /// 0..1 fraction of loaded assets, 1.0 when nothing is loading
+    pub progress: f32,
+}
+
+/// spawn progress of a single blueprint instance, including all the blueprint instances nested inside it
+/// (ie to show "castle 73% loaded" for deeply nested content)
+/// it keeps being updated after the instance itself is ready, until all its nested instances are ready too
+#[derive(Component, Reflect, Debug, Clone, Default, PartialEq)]
+#[reflect(Component)]
+pub struct BlueprintSpawnProgress {
+    /// total amount of assets required by the instance & the nested instances currently being spawned
+    pub total_assets: usize,
+    /// amount of assets (out of `total_assets`) that are loaded
+    pub loaded_assets: usize,
+    /// amount of nested blueprint instances that are still being spawned
+    pub pending_sub_instances: usize,
+    /// 0..1 fraction of loaded assets, 1.0 when nothing is loading
+    pub progress: f32,
+    /// true once the instance & all its nested instances are fully spawned
+    pub complete: bool,
+}
+
+fn asset_counts(assets_to_load: Option<&AssetsToLoad<Gltf>>) -> (usize, usize) {
+    assets_to_load.map_or((0, 0), |assets_to_load| {
+        (
+            assets_to_load.asset_infos.len(),
+            assets_to_load
+                .asset_infos
+                .iter()
+                .filter(|tracker| tracker.loaded)
+                .count(),
+        )
+    })
+}
+
+pub(crate) fn update_spawn_progress(
+    instances: Query<
+        (
+            Entity,
+            Option<&AssetsToLoad<Gltf>>,
+            Has<SpawnHere>,
+            Has<AwaitingSubInstanceReload>,
+        ),
+        With<BlueprintName>,
+    >,
+    mut spawn_progresses: Query<&mut BlueprintSpawnProgress>,
+    all_children: Query<&Children>,
+    mut commands: Commands,
+) {
+    for (entity, assets_to_load, spawning, awaiting_sub_instances) in instances.iter() {
+        // instances that are done (with all their nested instances) do not need updating anymore
+        let done = match spawn_progresses.get(entity) {
+            Ok(current) => current.complete,
+            Err(_) => true,
+        };
+        if !spawning && !awaiting_sub_instances && done {
+            continue;
+        }
+
+        let (mut total_assets, mut loaded_assets) = if spawning {
+            asset_counts(assets_to_load)
+        } else {
+            (0, 0)
+        };
+        let mut pending_sub_instances = 0;
+        for descendant in all_children.iter_descendants(entity) {
+            if let Ok((_, sub_assets_to_load, true, _)) = instances.get(descendant) {
+                let (sub_total, sub_loaded) = asset_counts(sub_assets_to_load);
+                total_assets += sub_total;
+                loaded_assets += sub_loaded;
+                pending_sub_instances += 1;
+            }
+        }
+
+        let spawn_progress = BlueprintSpawnProgress {
+            total_assets,
+            loaded_assets,
+            pending_sub_instances,
+            progress: if total_assets == 0 {
+                1.0
+            } else {
+                loaded_assets as f32 / total_assets as f32
+            },
+            complete: !spawning && !awaiting_sub_instances && pending_sub_instances == 0,
+        };
+        match spawn_progresses.get_mut(entity) {
+            Ok(mut current) => {
+                current.set_if_neq(spawn_progress);
+            }
+            Err(_) => {
+                commands.entity(entity).insert(spawn_progress);
+            }
+        }
+    }
+}
+
+pub(crate) fn update_loading_progress(
+    spawning: Query<Option<&AssetsToLoad<Gltf>>, With<SpawnHere>>,
+    mut loading_progress: ResMut<BlueprintsLoadingProgress>,
+) {
+    let mut spawning_instances = 0;
+    let mut total_assets = 0;
+    let mut loaded_assets = 0;
+    for assets_to_load in spawning.iter() {
+        spawning_instances += 1;
+        if let Some(assets_to_load) = assets_to_load {
+            total_assets += assets_to_load.asset_infos.len();
+            loaded_assets += assets_to_load
+                .asset_infos
+                .iter()
+                .filter(|tracker| tracker.loaded)
+                .count();
+        }
+    }
+    let progress = if total_assets == 0 {
+        1.0
+    } else {
+        loaded_assets as f32 / total_assets as f32
+    };
+
+    // only trigger change detection when something actually changed
+    loading_progress.set_if_neq(BlueprintsLoadingProgress {
+        spawning_instances,
+        total_assets,
+        loaded_assets,
+        progress,
+    });
+}
@@ -0,0 +1,118 @@
+use bevy::{gltf::Gltf, prelude::*};
+
+use crate::{BlueprintSpawnState, BlueprintVariant, SourceGltf};
+
+/// levels of detail of a blueprint instance, for blueprints exporting one scene per level of detail ("LOD0", "LOD1" ...):
+/// the scene that is spawned is swapped (like with a [`BlueprintVariant`]) depending on the distance to the closest active 3d camera
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct BlueprintLods {
+    /// camera distances at which to switch to the next level of detail: `[20.0, 50.0]` uses "LOD0" closer than 20, "LOD1" between 20 & 50,
+    /// and "LOD2" beyond (limited to the LOD scenes available in the blueprint)
+    pub distances: Vec<f32>,
+    /// how far past a distance the camera needs to go before switching, to avoid switching back & forth around it
+    pub hysteresis: f32,
+}
+
+impl Default for BlueprintLods {
+    fn default() -> Self {
+        BlueprintLods {
+            distances: vec![20.0, 50.0],
+            hysteresis: 2.0,
+        }
+    }
+}
+
+fn lod_scene(level: usize) -> String {
+    format!("LOD{level}")
+}
+
+/// the level of detail for the given camera distance, starting from the current one
+fn lod_level(lods: &BlueprintLods, current: usize, distance: f32, max_level: usize) -> usize {
+    let mut level = current.min(max_level);
+    while level < max_level
+        && lods
+            .distances
+            .get(level)
+            .is_some_and(|threshold| distance > threshold + lods.hysteresis)
+    {
+        level += 1;
+    }
+    while level > 0
+        && !lods
+            .distances
+            .get(level - 1)
+            .is_some_and(|threshold| distance >= threshold - lods.hysteresis)
+    {
+        level -= 1;
+    }
+    level
+}
+
+/// starts blueprint instances with levels of detail at "LOD0"
+pub(crate) fn init_blueprint_lods(
+    instances: Query<Entity, (Added<BlueprintLods>, Without<BlueprintVariant>)>,
+    mut commands: Commands,
+) {
+    for entity in instances.iter() {
+        commands
+            .entity(entity)
+            .insert(BlueprintVariant(lod_scene(0)));
+    }
+}
+
+/// switches the level of detail of ready blueprint instances depending on their distance to the cameras
+pub(crate) fn update_blueprint_lods(
+    mut instances: Query<(
+        &BlueprintLods,
+        &mut BlueprintVariant,
+        &BlueprintSpawnState,
+        &SourceGltf,
+        &GlobalTransform,
+    )>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    gltfs: Res<Assets<Gltf>>,
+) {
+    let camera_positions: Vec<Vec3> = cameras
+        .iter()
+        .filter(|(camera, _)| camera.is_active)
+        .map(|(_, transform)| transform.translation())
+        .collect();
+    if camera_positions.is_empty() {
+        return;
+    }
+    for (lods, mut variant, spawn_state, source_gltf, transform) in instances.iter_mut() {
+        // do not swap scenes in the middle of a (re)spawn
+        if *spawn_state != BlueprintSpawnState::Ready {
+            continue;
+        }
+        let Some(gltf) = gltfs.get(&source_gltf.0) else {
+            continue;
+        };
+        let available = (0..)
+            .take_while(|level| gltf.named_scenes.contains_key(&lod_scene(*level)))
+            .count();
+        let Some(current) = variant
+            .0
+            .strip_prefix("LOD")
+            .and_then(|level| level.parse::<usize>().ok())
+        else {
+            continue;
+        };
+        if available == 0 {
+            continue;
+        }
+        let distance = camera_positions
+            .iter()
+            .map(|position| position.distance(transform.translation()))
+            .fold(f32::INFINITY, f32::min);
+        let level = lod_level(lods, current, distance, available - 1);
+        if level != current {
+            debug!(
+                "switching to level of detail {} ({} from camera)",
+                level, distance
+            );
+            variant.0 = lod_scene(level);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::{fmt, sync::Arc};
+
+use bevy::{gltf::Gltf, prelude::*, utils::HashMap};
+
+use crate::{BlueprintReadyForFinalizing, MaterialLibraryCache, SpawnHere};
+
+/// function modifying a copy of a material, see [`MaterialOverride::Patch`]
+pub type MaterialPatchFn = Arc<dyn Fn(&mut StandardMaterial) + Send + Sync>;
+
+/// how to override a material of a blueprint instance
+#[derive(Clone)]
+pub enum MaterialOverride {
+    /// use the given material instead
+    Replace(Handle<StandardMaterial>),
+    /// use a copy of the material, modified by the given function (ie tinting)
+    Patch(MaterialPatchFn),
+}
+
+impl fmt::Debug for MaterialOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaterialOverride::Replace(handle) => write!(f, "Replace({:?})", handle),
+            MaterialOverride::Patch(_) => write!(f, "Patch"),
+        }
+    }
+}
+
+/// per instance overrides of the materials of a blueprint, keyed by material name (as in Blender):
+/// to tint variants, swap skins, apply damage materials ... without authoring separate blueprints
+/// * applied during post processing, and again whenever the component changes
+/// * removing an override restores the original material
+#[derive(Component, Debug, Clone, Default)]
+pub struct MaterialOverrides(pub HashMap<String, MaterialOverride>);
+
+impl MaterialOverrides {
+    /// use the given material instead of the one with the given name
+    pub fn replace(
+        mut self,
+        material_name: impl Into<String>,
+        material: Handle<StandardMaterial>,
+    ) -> Self {
+        self.0
+            .insert(material_name.into(), MaterialOverride::Replace(material));
+        self
+    }
+
+    /// use a copy of the material with the given name, modified by the given function
+    pub fn patch(
+        mut self,
+        material_name: impl Into<String>,
+        patch: impl Fn(&mut StandardMaterial) + Send + Sync + 'static,
+    ) -> Self {
+        self.0.insert(
+            material_name.into(),
+            MaterialOverride::Patch(Arc::new(patch)),
+        );
+        self
+    }
+}
+
+/// the material a mesh entity of a blueprint instance had before any override was applied
+#[derive(Component, Debug, Clone)]
+pub(crate) struct OriginalMaterial {
+    pub(crate) name: Option<String>,
+    pub(crate) handle: Handle<StandardMaterial>,
+}
+
+/// names of all the known materials (from the loaded gltf files & the material libraries)
+fn material_names(
+    gltfs: &Assets<Gltf>,
+    material_library_cache: &MaterialLibraryCache,
+) -> HashMap<AssetId<StandardMaterial>, String> {
+    let mut names: HashMap<AssetId<StandardMaterial>, String> = HashMap::new();
+    for (_, gltf) in gltfs.iter() {
+        for (name, handle) in gltf.named_materials.iter() {
+            names.insert(handle.id(), name.to_string());
+        }
+    }
+    for ((_, name), handle) in material_library_cache.materials.iter() {
+        names.insert(handle.id(), name.clone());
+    }
+    names
+}
+
+/// applies the [`MaterialOverrides`] of blueprint instances to their meshes,
+/// once they are post processed, and whenever the overrides change on instances that are ready
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn apply_material_overrides(
+    instances: Query<
+        (Entity, &MaterialOverrides, Has<BlueprintReadyForFinalizing>),
+        Or<(
+            Added<BlueprintReadyForFinalizing>,
+            Changed<MaterialOverrides>,
+        )>,
+    >,
+    spawning: Query<(), With<SpawnHere>>,
+    all_children: Query<&Children>,
+    meshes: Query<(&Handle<StandardMaterial>, Option<&OriginalMaterial>), With<Handle<Mesh>>>,
+    gltfs: Res<Assets<Gltf>>,
+    material_library_cache: Res<MaterialLibraryCache>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    let mut names: Option<HashMap<AssetId<StandardMaterial>, String>> = None;
+    for (instance, overrides, ready_for_finalizing) in instances.iter() {
+        // the meshes are not there yet, the overrides get applied once the instance is post processed
+        if spawning.contains(instance) && !ready_for_finalizing {
+            continue;
+        }
+        let names = names.get_or_insert_with(|| material_names(&gltfs, &material_library_cache));
+
+        // patched materials are shared by all the meshes of the instance using the same material
+        let mut patched: HashMap<String, Handle<StandardMaterial>> = HashMap::new();
+        for child in all_children.iter_descendants(instance) {
+            let Ok((current, original)) = meshes.get(child) else {
+                continue;
+            };
+            let original = match original {
+                Some(original) => original.clone(),
+                None => {
+                    let original = OriginalMaterial {
+                        name: names.get(&current.id()).cloned(),
+                        handle: current.clone(),
+                    };
+                    commands.entity(child).insert(original.clone());
+                    original
+                }
+            };
+
+            let material = match original.name.as_ref().and_then(|name| {
+                overrides
+                    .0
+                    .get(name)
+                    .map(|material_override| (name, material_override))
+            }) {
+                Some((_, MaterialOverride::Replace(handle))) => handle.clone(),
+                Some((name, MaterialOverride::Patch(patch))) => patched
+                    .entry(name.clone())
+                    .or_insert_with(|| {
+                        let mut material =
+                            materials.get(&original.handle).cloned().unwrap_or_default();
+                        patch(&mut material);
+                        materials.add(material)
+                    })
+                    .clone(),
+                None => original.handle.clone(),
+            };
+            if material != *current {
+                commands.entity(child).insert(material);
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use bevy::{
+    ecs::system::Command,
+    gltf::{Gltf, GltfMesh},
+    prelude::*,
+    utils::HashMap,
+};
+use bevy_gltf_components::GltfMeshMaterialExtras;
+
+use crate::BlueprintReadyForFinalizing;
+
+/// the material variants (`KHR_materials_variants`) of the gltf file of a blueprint instance, and the active one
+/// (None for the default materials), added to instances once their gltf file has been inspected:
+/// use `commands.set_material_variant(entity, "Winter")` to switch between them
+#[derive(Component, Reflect, Default, Debug, Clone, PartialEq)]
+#[reflect(Component)]
+pub struct MaterialVariants {
+    pub available: Vec<String>,
+    pub current: Option<String>,
+}
+
+/// the gltf file a blueprint instance was spawned from
+#[derive(Component, Debug)]
+pub(crate) struct SourceGltf(pub(crate) Handle<Gltf>);
+
+/// keeps track of the gltf file of blueprint instances, while their scene handle is still available
+pub(crate) fn track_source_gltfs(
+    instances: Query<(Entity, &Handle<Scene>), Added<BlueprintReadyForFinalizing>>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    for (entity, scene) in instances.iter() {
+        let Some(gltf) = asset_server
+            .get_path(scene.id())
+            .and_then(|path| asset_server.get_handle::<Gltf>(path.without_label()))
+        else {
+            continue;
+        };
+        commands.entity(entity).insert(SourceGltf(gltf));
+    }
+}
+
+/// adds the [`MaterialVariants`] of blueprint instances, as soon as the material variants of their gltf file are known
+pub(crate) fn list_material_variants(
+    instances: Query<(Entity, &SourceGltf), Without<MaterialVariants>>,
+    gltf_extras: Res<GltfMeshMaterialExtras>,
+    mut commands: Commands,
+) {
+    for (entity, source_gltf) in instances.iter() {
+        let Some(extras) = gltf_extras.get(source_gltf.0.id()) else {
+            continue;
+        };
+        commands.entity(entity).insert(MaterialVariants {
+            available: extras.material_variants.clone(),
+            current: None,
+        });
+    }
+}
+
+/// switches the materials of the meshes of a blueprint instance to the given variant (None for the default materials)
+pub struct SetMaterialVariant {
+    pub entity: Entity,
+    pub variant: Option<String>,
+}
+
+impl Command for SetMaterialVariant {
+    fn apply(self, world: &mut World) {
+        let Some(source_gltf) = world
+            .get::<SourceGltf>(self.entity)
+            .map(|source| source.0.id())
+        else {
+            warn!(
+                "cannot set material variant of {:?}: not a (spawned) blueprint instance",
+                self.entity
+            );
+            return;
+        };
+
+        // material of each mesh of the blueprint for the requested variant
+        let mut materials: HashMap<AssetId<Mesh>, Handle<StandardMaterial>> = HashMap::new();
+        {
+            let gltfs = world.resource::<Assets<Gltf>>();
+            let gltf_meshes = world.resource::<Assets<GltfMesh>>();
+            let gltf_extras = world.resource::<GltfMeshMaterialExtras>();
+            let (Some(gltf), Some(extras)) = (gltfs.get(source_gltf), gltf_extras.get(source_gltf))
+            else {
+                warn!(
+                    "cannot set material variant of {:?}: its gltf file is not loaded",
+                    self.entity
+                );
+                return;
+            };
+            let variant_index = match &self.variant {
+                Some(variant) => {
+                    let Some(index) = extras
+                        .material_variants
+                        .iter()
+                        .position(|name| name == variant)
+                    else {
+                        warn!(
+                            "unknown material variant {} for {:?}, available variants: {:?}",
+                            variant, self.entity, extras.material_variants
+                        );
+                        return;
+                    };
+                    Some(index)
+                }
+                None => None,
+            };
+
+            for (mesh_index, gltf_mesh) in gltf.meshes.iter().enumerate() {
+                let Some(gltf_mesh) = gltf_meshes.get(gltf_mesh) else {
+                    continue;
+                };
+                for (primitive_index, primitive) in gltf_mesh.primitives.iter().enumerate() {
+                    let material = variant_index
+                        .and_then(|variant_index| {
+                            extras
+                                .primitive_variants
+                                .get(&(mesh_index, primitive_index))?
+                                .get(&variant_index)
+                        })
+                        .and_then(|material_index| gltf.materials.get(*material_index))
+                        .or(primitive.material.as_ref());
+                    if let Some(material) = material {
+                        materials.insert(primitive.mesh.id(), material.clone());
+                    }
+                }
+            }
+        }
+
+        let mut updates: Vec<(Entity, Handle<StandardMaterial>)> = vec![];
+        let mut stack = vec![self.entity];
+        while let Some(entity) = stack.pop() {
+            if let Some(children) = world.get::<Children>(entity) {
+                stack.extend(children.iter());
+            }
+            if let Some(material) = world
+                .get::<Handle<Mesh>>(entity)
+                .and_then(|mesh| materials.get(&mesh.id()))
+            {
+                updates.push((entity, material.clone()));
+            }
+        }
+        for (entity, material) in updates {
+            world.entity_mut(entity).insert(material);
+        }
+        if let Some(mut material_variants) = world.get_mut::<MaterialVariants>(self.entity) {
+            material_variants.current = self.variant;
+        }
+    }
+}
+
+/// extension trait for [`Commands`], to switch the material variants of blueprint instances
+pub trait MaterialVariantCommandsExt {
+    /// swaps the materials of all the meshes of the instance affected by the given variant (see [`MaterialVariants`])
+    fn set_material_variant(&mut self, entity: Entity, variant: impl Into<String>);
+
+    /// goes back to the default materials of the instance
+    fn reset_material_variant(&mut self, entity: Entity);
+}
+
+impl MaterialVariantCommandsExt for Commands<'_, '_> {
+    fn set_material_variant(&mut self, entity: Entity, variant: impl Into<String>) {
+        self.add(SetMaterialVariant {
+            entity,
+            variant: Some(variant.into()),
+        });
+    }
+
+    fn reset_material_variant(&mut self, entity: Entity) {
+        self.add(SetMaterialVariant {
+            entity,
+            variant: None,
+        });
+    }
+}
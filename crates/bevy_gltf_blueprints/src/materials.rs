@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use bevy::{
     asset::{AssetServer, Assets, Handle},
@@ -7,7 +7,7 @@ use bevy::{
         entity::Entity,
         query::{Added, With},
         reflect::ReflectComponent,
-        system::{Commands, Query, Res, ResMut},
+        system::{Commands, Query, Res, ResMut, Resource},
     },
     gltf::Gltf,
     hierarchy::{Children, Parent},
@@ -15,9 +15,10 @@ use bevy::{
     pbr::StandardMaterial,
     reflect::Reflect,
     render::mesh::Mesh,
+    utils::HashMap,
 };
 
-use crate::{AssetLoadTracker, AssetsToLoad, BluePrintsConfig};
+use crate::{AssetLoadTracker, AssetsToLoad, BluePrintsConfig, BlueprintAssetKind};
 
 #[derive(Component, Reflect, Default, Debug)]
 #[reflect(Component)]
@@ -27,6 +28,36 @@ pub struct MaterialInfo {
     pub source: String,
 }
 
+/// materials of the material libraries, shared by all the blueprint instances using them:
+/// each library file is loaded once, and each material is keyed by (library path, material name),
+/// so that instances of different blueprints using the same library material end up with the same handle (less memory, better batching)
+#[derive(Resource, Debug, Default)]
+pub struct MaterialLibraryCache {
+    pub(crate) libraries: HashMap<PathBuf, Handle<Gltf>>,
+    pub(crate) materials: HashMap<(PathBuf, String), Handle<StandardMaterial>>,
+}
+
+impl MaterialLibraryCache {
+    /// the shared handle of a material from a library, if it has been used already
+    pub fn get(
+        &self,
+        library_path: &Path,
+        material_name: &str,
+    ) -> Option<&Handle<StandardMaterial>> {
+        self.materials
+            .get(&(library_path.to_path_buf(), material_name.to_string()))
+    }
+
+    /// amount of distinct library materials in use
+    pub fn len(&self) -> usize {
+        self.materials.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.materials.is_empty()
+    }
+}
+
 /// flag component
 #[derive(Component)]
 pub(crate) struct BlueprintMaterialAssetsLoaded;
@@ -36,40 +67,40 @@ pub(crate) struct BlueprintMaterialAssetsNotLoaded;
 
 /// system that injects / replaces materials from material library
 pub(crate) fn materials_inject(
-    blueprints_config: ResMut<BluePrintsConfig>,
+    blueprints_config: Res<BluePrintsConfig>,
+    mut material_library_cache: ResMut<MaterialLibraryCache>,
     material_infos: Query<(Entity, &MaterialInfo), Added<MaterialInfo>>,
     asset_server: Res<AssetServer>,
     mut commands: Commands,
 ) {
     for (entity, material_info) in material_infos.iter() {
-        let model_file_name = format!(
-            "{}_materials_library.{}",
-            &material_info.source, &blueprints_config.format
-        );
-        let materials_path = Path::new(&blueprints_config.material_library_folder)
-            .join(Path::new(model_file_name.as_str()));
+        let materials_path = blueprints_config.material_library_path(&material_info.source);
         let material_name = &material_info.name;
-        let material_full_path = materials_path.to_str().unwrap().to_string() + "#" + material_name; // TODO: yikes, cleanup
 
-        if blueprints_config
-            .material_library_cache
-            .contains_key(&material_full_path)
+        if material_library_cache
+            .get(&materials_path, material_name)
+            .is_some()
         {
             debug!("material is cached, retrieving");
-            blueprints_config
-                .material_library_cache
-                .get(&material_full_path)
-                .expect("we should have the material available");
             commands
                 .entity(entity)
                 .insert(BlueprintMaterialAssetsLoaded);
         } else {
-            let material_file_handle: Handle<Gltf> = asset_server.load(materials_path.clone());
+            // each library file is only loaded once, no matter how many instances use its materials
+            let material_file_handle: Handle<Gltf> = material_library_cache
+                .libraries
+                .entry(materials_path.clone())
+                .or_insert_with(|| asset_server.load(materials_path.clone()))
+                .clone();
             let material_file_id = material_file_handle.id();
             let asset_infos: Vec<AssetLoadTracker<Gltf>> = vec![AssetLoadTracker {
-                name: material_full_path,
+                kind: BlueprintAssetKind::Gltf,
+                name: format!("{}#{}", materials_path.to_string_lossy(), material_name),
                 id: material_file_id,
                 loaded: false,
+                failed: false,
+                retries: 0,
+                retry_at: None,
                 handle: material_file_handle.clone(),
             }];
 
@@ -124,7 +155,8 @@ pub(crate) fn check_for_material_loaded(
 
 /// system that injects / replaces materials from material library
 pub(crate) fn materials_inject2(
-    mut blueprints_config: ResMut<BluePrintsConfig>,
+    blueprints_config: Res<BluePrintsConfig>,
+    mut material_library_cache: ResMut<MaterialLibraryCache>,
     material_infos: Query<
         (&MaterialInfo, &Children),
         (
@@ -141,45 +173,28 @@ pub(crate) fn materials_inject2(
         ),
     >,
     assets_gltf: Res<Assets<Gltf>>,
-    asset_server: Res<AssetServer>,
 
     mut commands: Commands,
 ) {
     for (material_info, children) in material_infos.iter() {
-        let model_file_name = format!(
-            "{}_materials_library.{}",
-            &material_info.source, &blueprints_config.format
-        );
-        let materials_path = Path::new(&blueprints_config.material_library_folder)
-            .join(Path::new(model_file_name.as_str()));
+        let materials_path = blueprints_config.material_library_path(&material_info.source);
         let material_name = &material_info.name;
 
-        let material_full_path = materials_path.to_str().unwrap().to_string() + "#" + material_name; // TODO: yikes, cleanup
-        let mut material_found: Option<&Handle<StandardMaterial>> = None;
-
-        if blueprints_config
-            .material_library_cache
-            .contains_key(&material_full_path)
-        {
-            debug!("material is cached, retrieving");
-            let material = blueprints_config
-                .material_library_cache
-                .get(&material_full_path)
-                .expect("we should have the material available");
-            material_found = Some(material);
-        } else {
-            let model_handle: Handle<Gltf> = asset_server.load(materials_path.clone()); // FIXME: kinda weird now
-            let mat_gltf = assets_gltf
-                .get(model_handle.id())
-                .expect("material should have been preloaded");
-            if mat_gltf.named_materials.contains_key(material_name) {
-                let material = mat_gltf
-                    .named_materials
-                    .get(material_name)
-                    .expect("this material should have been loaded");
-                blueprints_config
-                    .material_library_cache
-                    .insert(material_full_path, material.clone());
+        let mut material_found = material_library_cache
+            .get(&materials_path, material_name)
+            .cloned();
+        if material_found.is_none() {
+            let material = material_library_cache
+                .libraries
+                .get(&materials_path)
+                .and_then(|library| assets_gltf.get(library))
+                .and_then(|library| library.named_materials.get(material_name))
+                .cloned();
+            if let Some(material) = material {
+                material_library_cache.materials.insert(
+                    (materials_path.clone(), material_name.clone()),
+                    material.clone(),
+                );
                 material_found = Some(material);
             }
         }
@@ -0,0 +1,113 @@
+use bevy::{ecs::system::Command, prelude::*, render::mesh::morph::MorphWeights};
+
+use crate::{BlueprintName, BlueprintReadyForFinalizing};
+
+/// the names of the morph targets (shape keys in Blender) of the meshes of a blueprint instance,
+/// added to instances once they are post processed: use `commands.set_morph_weight(entity, "Smile", 0.8)` to change their weights
+#[derive(Component, Reflect, Default, Debug, Clone, PartialEq)]
+#[reflect(Component)]
+pub struct MorphTargetNames(pub Vec<String>);
+
+/// the entities with morph weights in the hierarchy of a blueprint instance, without going into nested blueprint instances,
+/// with the morph target names of their meshes
+fn morph_entities(world: &World, instance: Entity) -> Vec<(Entity, Vec<String>)> {
+    let meshes = world.resource::<Assets<Mesh>>();
+    let mut found = vec![];
+    let mut stack = vec![instance];
+    while let Some(entity) = stack.pop() {
+        let children: Vec<Entity> = world
+            .get::<Children>(entity)
+            .map(|children| {
+                children
+                    .iter()
+                    .filter(|child| world.get::<BlueprintName>(**child).is_none())
+                    .copied()
+                    .collect()
+            })
+            .unwrap_or_default();
+        if world.get::<MorphWeights>(entity).is_some() {
+            // the weights are set on the parent of the mesh (primitive) entities
+            let names = children
+                .iter()
+                .chain(std::iter::once(&entity))
+                .filter_map(|mesh_entity| world.get::<Handle<Mesh>>(*mesh_entity))
+                .filter_map(|mesh| meshes.get(mesh)?.morph_target_names())
+                .find(|names| !names.is_empty())
+                .map(<[String]>::to_vec)
+                .unwrap_or_default();
+            found.push((entity, names));
+        }
+        stack.extend(children);
+    }
+    found
+}
+
+/// lists the [`MorphTargetNames`] of blueprint instances once they are post processed
+pub(crate) fn list_morph_targets(world: &mut World) {
+    let instances: Vec<Entity> = world
+        .query_filtered::<Entity, Added<BlueprintReadyForFinalizing>>()
+        .iter(world)
+        .collect();
+    for instance in instances {
+        let mut names: Vec<String> = vec![];
+        for (_, entity_names) in morph_entities(world, instance) {
+            for name in entity_names {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        if !names.is_empty() {
+            world.entity_mut(instance).insert(MorphTargetNames(names));
+        }
+    }
+}
+
+/// sets the weight of the morph target with the given name on all the meshes of a blueprint instance that have it
+pub struct SetMorphWeight {
+    pub entity: Entity,
+    pub morph_target: String,
+    pub weight: f32,
+}
+
+impl Command for SetMorphWeight {
+    fn apply(self, world: &mut World) {
+        let mut found = false;
+        for (entity, names) in morph_entities(world, self.entity) {
+            let Some(index) = names.iter().position(|name| *name == self.morph_target) else {
+                continue;
+            };
+            let Some(mut weights) = world.get_mut::<MorphWeights>(entity) else {
+                continue;
+            };
+            if let Some(weight) = weights.weights_mut().get_mut(index) {
+                *weight = self.weight;
+                found = true;
+            }
+        }
+        if !found {
+            warn!(
+                "no morph target named {} for {:?}, available morph targets: {:?}",
+                self.morph_target,
+                self.entity,
+                world.get::<MorphTargetNames>(self.entity)
+            );
+        }
+    }
+}
+
+/// extension trait for [`Commands`], to change the morph target (shape key) weights of blueprint instances
+pub trait MorphTargetCommandsExt {
+    /// sets the weight (usually 0..1) of the morph target with the given name on all the meshes of the instance using it (see [`MorphTargetNames`])
+    fn set_morph_weight(&mut self, entity: Entity, morph_target: impl Into<String>, weight: f32);
+}
+
+impl MorphTargetCommandsExt for Commands<'_, '_> {
+    fn set_morph_weight(&mut self, entity: Entity, morph_target: impl Into<String>, weight: f32) {
+        self.add(SetMorphWeight {
+            entity,
+            morph_target: morph_target.into(),
+            weight,
+        });
+    }
+}
@@ -0,0 +1,134 @@
+use bevy::{
+    math::Affine3A,
+    prelude::*,
+    render::{mesh::VertexAttributeValues, render_resource::PrimitiveTopology},
+};
+
+use crate::{CollisionMesh, GameWorldReady};
+
+/// how an object of a level is used for navigation meshes, added to objects in Blender
+#[derive(Component, Reflect, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[reflect(Component)]
+pub enum NavMeshTag {
+    /// the object can be walked on
+    #[default]
+    Walkable,
+    /// the object blocks the way
+    Obstacle,
+}
+
+/// the triangles of a tagged object, in world space
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavMeshGeometry {
+    /// the tagged object
+    pub entity: Entity,
+    pub vertices: Vec<Vec3>,
+    /// three indices (into `vertices`) per triangle
+    pub indices: Vec<u32>,
+}
+
+/// sent once a game world (level) is ready, with the geometry of its objects tagged with [`NavMeshTag`]
+/// (blueprint instances inside it included), to hand to a navigation mesh generator (ie `oxidized_navigation`, recast)
+#[derive(Event, Debug, Clone)]
+pub struct LevelNavMeshSources {
+    pub world: Entity,
+    pub walkable: Vec<NavMeshGeometry>,
+    pub obstacles: Vec<NavMeshGeometry>,
+}
+
+/// the world space transform of an entity, from the (local) transforms of its ancestors, since global transforms
+/// are not propagated yet for freshly spawned levels
+pub(crate) fn world_affine(
+    entity: Entity,
+    parents: &Query<&Parent>,
+    transforms: &Query<&Transform>,
+) -> Affine3A {
+    std::iter::once(entity)
+        .chain(parents.iter_ancestors(entity))
+        .filter_map(|entity| transforms.get(entity).ok())
+        .fold(Affine3A::IDENTITY, |affine, transform| {
+            transform.compute_affine() * affine
+        })
+}
+
+/// the triangles of a mesh, transformed into world space
+fn mesh_triangles(entity: Entity, mesh: &Mesh, affine: Affine3A) -> Option<NavMeshGeometry> {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return None;
+    }
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return None;
+    };
+    let vertices = positions
+        .iter()
+        .map(|position| affine.transform_point3(Vec3::from(*position)))
+        .collect::<Vec<_>>();
+    let indices = match mesh.indices() {
+        Some(indices) => indices.iter().map(|index| index as u32).collect(),
+        None => (0..vertices.len() as u32).collect(),
+    };
+    Some(NavMeshGeometry {
+        entity,
+        vertices,
+        indices,
+    })
+}
+
+/// gathers the geometry of the objects tagged with [`NavMeshTag`] once game worlds are ready, and sends it as a [`LevelNavMeshSources`] event
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn collect_navmesh_sources(
+    worlds: Query<Entity, Added<GameWorldReady>>,
+    all_children: Query<&Children>,
+    parents: Query<&Parent>,
+    transforms: Query<&Transform>,
+    tags: Query<&NavMeshTag>,
+    mesh_handles: Query<AnyOf<(&Handle<Mesh>, &CollisionMesh)>>,
+    meshes: Res<Assets<Mesh>>,
+    mut navmesh_sources: EventWriter<LevelNavMeshSources>,
+) {
+    for world in worlds.iter() {
+        let mut walkable = vec![];
+        let mut obstacles = vec![];
+        for entity in all_children.iter_descendants(world) {
+            let Ok(tag) = tags.get(entity) else {
+                continue;
+            };
+            // bevy spawns one entity per primitive of the mesh of the object
+            let mesh_entities = std::iter::once(entity)
+                .chain(all_children.get(entity).into_iter().flatten().copied());
+            for mesh_entity in mesh_entities {
+                let Ok((handle, collision_mesh)) = mesh_handles.get(mesh_entity) else {
+                    continue;
+                };
+                let Some(mesh) = collision_mesh
+                    .map(|collision_mesh| &collision_mesh.0)
+                    .or(handle)
+                    .and_then(|handle| meshes.get(handle))
+                else {
+                    continue;
+                };
+                let affine = world_affine(mesh_entity, &parents, &transforms);
+                let Some(geometry) = mesh_triangles(entity, mesh, affine) else {
+                    continue;
+                };
+                match tag {
+                    NavMeshTag::Walkable => walkable.push(geometry),
+                    NavMeshTag::Obstacle => obstacles.push(geometry),
+                }
+            }
+        }
+        debug!(
+            "navmesh sources of {:?}: {} walkable, {} obstacles",
+            world,
+            walkable.len(),
+            obstacles.len()
+        );
+        navmesh_sources.send(LevelNavMeshSources {
+            world,
+            walkable,
+            obstacles,
+        });
+    }
+}
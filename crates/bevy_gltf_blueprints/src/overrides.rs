@@ -0,0 +1,87 @@
+use bevy::ecs::system::Command;
+use bevy::prelude::*;
+use bevy::reflect::serde::TypedReflectDeserializer;
+use bevy::reflect::{GetPath, TypeRegistry};
+use bevy::utils::HashMap;
+use serde::de::DeserializeSeed;
+
+use crate::BlueprintReadyForFinalizing;
+
+/// per instance overrides of the components of its blueprint, authored on the collection instance in Blender
+/// so that the same blueprint can be parameterized per placement:
+/// keyed by `Component.field.path` (or just `Component` to override the whole component), with ron values
+/// ie `BlueprintOverrides({"LampSettings.color": "Rgba(red:1.0,green:0.0,blue:0.0,alpha:1.0)"})`
+/// * overrides are applied once the components of the blueprint have been copied to the instance & injected from their gltf extras,
+///   taking precedence over the blueprint's values
+#[derive(Component, Reflect, Default, Debug, Clone)]
+#[reflect(Component)]
+pub struct BlueprintOverrides(pub HashMap<String, String>);
+
+/// applies the [`BlueprintOverrides`] of an instance to its components
+pub(crate) struct ApplyBlueprintOverrides {
+    pub entity: Entity,
+}
+
+impl Command for ApplyBlueprintOverrides {
+    fn apply(self, world: &mut World) {
+        let Some(overrides) = world.get::<BlueprintOverrides>(self.entity).cloned() else {
+            return;
+        };
+        let type_registry = world.resource::<AppTypeRegistry>().clone();
+        let type_registry = type_registry.read();
+        for (key, value) in overrides.0.iter() {
+            if let Err(error) = apply_override(world, self.entity, &type_registry, key, value) {
+                warn!(
+                    "could not apply override {} = {} to blueprint instance {:?}: {}",
+                    key, value, self.entity, error
+                );
+            }
+        }
+    }
+}
+
+fn apply_override(
+    world: &mut World,
+    entity: Entity,
+    type_registry: &TypeRegistry,
+    key: &str,
+    value: &str,
+) -> Result<(), String> {
+    let (component_name, field_path) = key.split_once('.').unwrap_or((key, ""));
+    let reflect_component = type_registry
+        .get_with_short_type_path(component_name)
+        .and_then(|registration| registration.data::<ReflectComponent>())
+        .ok_or_else(|| format!("unknown component type {}", component_name))?;
+    let mut entity_mut = world.entity_mut(entity);
+    let mut component = reflect_component
+        .reflect_mut(&mut entity_mut)
+        .ok_or_else(|| format!("the instance has no {} component", component_name))?;
+    let field: &mut dyn Reflect = if field_path.is_empty() {
+        &mut *component
+    } else {
+        component
+            .reflect_path_mut(field_path)
+            .map_err(|error| error.to_string())?
+    };
+
+    let field_registration = field
+        .get_represented_type_info()
+        .and_then(|type_info| type_registry.get(type_info.type_id()))
+        .ok_or_else(|| format!("the type of {} is not registered", key))?;
+    let mut deserializer = ron::Deserializer::from_str(value).map_err(|error| error.to_string())?;
+    let new_value = TypedReflectDeserializer::new(field_registration, type_registry)
+        .deserialize(&mut deserializer)
+        .map_err(|error| error.to_string())?;
+    field.apply(new_value.as_ref());
+    Ok(())
+}
+
+/// applies the [`BlueprintOverrides`] of blueprint instances, right after their components have been transfered & injected
+pub(crate) fn apply_blueprint_overrides(
+    instances: Query<Entity, (With<BlueprintOverrides>, Added<BlueprintReadyForFinalizing>)>,
+    mut commands: Commands,
+) {
+    for entity in instances.iter() {
+        commands.add(ApplyBlueprintOverrides { entity });
+    }
+}
@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+
+use crate::BlueprintSpawnState;
+
+/// flag component to freeze a blueprint instance until it is fully spawned & post processed, companion to `HideUntilReady`:
+/// its animation players stay paused, and (with the `avian` or `rapier` feature) its dynamic rigid bodies are kept kinematic,
+/// so that half spawned objects do not fall through a level that is not loaded yet
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct PauseUntilReady;
+
+/// flag component for the animation players paused by [`PauseUntilReady`]
+#[derive(Component, Debug)]
+pub(crate) struct PausedAnimation;
+
+/// flag component for the rigid bodies paused by [`PauseUntilReady`]
+#[cfg(any(feature = "avian", feature = "rapier"))]
+#[derive(Component, Debug)]
+pub(crate) struct PausedRigidBody;
+
+fn is_spawning(spawn_state: &BlueprintSpawnState) -> bool {
+    !matches!(
+        spawn_state,
+        BlueprintSpawnState::Ready | BlueprintSpawnState::Failed
+    )
+}
+
+/// the instance & all its descendants
+fn hierarchy(instance: Entity, all_children: &Query<&Children>) -> Vec<Entity> {
+    std::iter::once(instance)
+        .chain(all_children.iter_descendants(instance))
+        .collect()
+}
+
+/// pauses the animation players of [`PauseUntilReady`] instances that are still spawning
+pub(crate) fn pause_animations_until_ready(
+    instances: Query<(Entity, &BlueprintSpawnState), With<PauseUntilReady>>,
+    all_children: Query<&Children>,
+    mut players: Query<&mut AnimationPlayer, Without<PausedAnimation>>,
+    mut commands: Commands,
+) {
+    for (instance, spawn_state) in instances.iter() {
+        if !is_spawning(spawn_state) {
+            continue;
+        }
+        for entity in hierarchy(instance, &all_children) {
+            let Ok(mut player) = players.get_mut(entity) else {
+                continue;
+            };
+            // players paused by the user stay paused
+            if !player.is_paused() {
+                player.pause();
+                commands.entity(entity).insert(PausedAnimation);
+            }
+        }
+    }
+}
+
+/// resumes the animation players paused by [`PauseUntilReady`] once the instances are ready
+pub(crate) fn resume_animations_when_ready(
+    instances: Query<
+        (Entity, &BlueprintSpawnState),
+        (With<PauseUntilReady>, Changed<BlueprintSpawnState>),
+    >,
+    all_children: Query<&Children>,
+    mut players: Query<&mut AnimationPlayer, With<PausedAnimation>>,
+    mut commands: Commands,
+) {
+    for (instance, spawn_state) in instances.iter() {
+        if is_spawning(spawn_state) {
+            continue;
+        }
+        for entity in hierarchy(instance, &all_children) {
+            if let Ok(mut player) = players.get_mut(entity) {
+                player.resume();
+                commands.entity(entity).remove::<PausedAnimation>();
+            }
+        }
+    }
+}
+
+/// the rigid body component of a physics engine
+#[cfg(any(feature = "avian", feature = "rapier"))]
+pub(crate) trait PausableRigidBody: Component + Copy + PartialEq {
+    const DYNAMIC: Self;
+    const KINEMATIC: Self;
+}
+
+#[cfg(feature = "avian")]
+impl PausableRigidBody for bevy_xpbd_3d::prelude::RigidBody {
+    const DYNAMIC: Self = Self::Dynamic;
+    const KINEMATIC: Self = Self::Kinematic;
+}
+
+#[cfg(feature = "rapier")]
+impl PausableRigidBody for bevy_rapier3d::prelude::RigidBody {
+    const DYNAMIC: Self = Self::Dynamic;
+    const KINEMATIC: Self = Self::KinematicPositionBased;
+}
+
+/// keeps the dynamic rigid bodies of [`PauseUntilReady`] instances that are still spawning kinematic
+#[cfg(any(feature = "avian", feature = "rapier"))]
+pub(crate) fn pause_rigid_bodies_until_ready<B: PausableRigidBody>(
+    instances: Query<(Entity, &BlueprintSpawnState), With<PauseUntilReady>>,
+    all_children: Query<&Children>,
+    mut bodies: Query<&mut B, Without<PausedRigidBody>>,
+    mut commands: Commands,
+) {
+    for (instance, spawn_state) in instances.iter() {
+        if !is_spawning(spawn_state) {
+            continue;
+        }
+        for entity in hierarchy(instance, &all_children) {
+            let Ok(mut body) = bodies.get_mut(entity) else {
+                continue;
+            };
+            if *body == B::DYNAMIC {
+                *body = B::KINEMATIC;
+                commands.entity(entity).insert(PausedRigidBody);
+            }
+        }
+    }
+}
+
+/// makes the rigid bodies paused by [`PauseUntilReady`] dynamic again once the instances are ready
+#[cfg(any(feature = "avian", feature = "rapier"))]
+pub(crate) fn resume_rigid_bodies_when_ready<B: PausableRigidBody>(
+    instances: Query<
+        (Entity, &BlueprintSpawnState),
+        (With<PauseUntilReady>, Changed<BlueprintSpawnState>),
+    >,
+    all_children: Query<&Children>,
+    mut bodies: Query<&mut B, With<PausedRigidBody>>,
+    mut commands: Commands,
+) {
+    for (instance, spawn_state) in instances.iter() {
+        if is_spawning(spawn_state) {
+            continue;
+        }
+        for entity in hierarchy(instance, &all_children) {
+            if let Ok(mut body) = bodies.get_mut(entity) {
+                *body = B::DYNAMIC;
+                commands.entity(entity).remove::<PausedRigidBody>();
+            }
+        }
+    }
+}
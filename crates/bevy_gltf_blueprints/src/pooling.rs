@@ -0,0 +1,121 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{BlueprintName, SpawnBlueprintCommandsExt};
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+/// flag component for pooled blueprint instances that are currently not in use: they are kept hidden
+/// (use it to filter them out of your gameplay queries)
+pub struct PooledInactive;
+
+/// pool of blueprint instances, for projectiles, frequently respawned enemies etc:
+/// released instances are deactivated & reused instead of despawned, so they do not go through
+/// the whole asset loading & spawning pipeline again
+///
+/// ```ignore
+/// fn setup(mut commands: Commands, mut pool: ResMut<BlueprintPool>) {
+///     pool.prewarm(&mut commands, "Bullet", 50);
+/// }
+///
+/// fn fire(mut commands: Commands, mut pool: ResMut<BlueprintPool>) {
+///     let bullet = pool.acquire(&mut commands, "Bullet", Transform::from_xyz(0.0, 1.0, 0.0));
+///     // ... and later
+///     pool.release(&mut commands, bullet);
+/// }
+/// ```
+#[derive(Resource, Debug, Default)]
+pub struct BlueprintPool {
+    /// inactive instances, per blueprint name
+    pub(crate) available: HashMap<String, Vec<Entity>>,
+    /// blueprint name of every instance managed by the pool
+    pub(crate) members: HashMap<Entity, String>,
+}
+
+impl BlueprintPool {
+    /// spawn `count` inactive instances of the given blueprint, ready to be acquired
+    pub fn prewarm(&mut self, commands: &mut Commands, blueprint: impl Into<String>, count: usize) {
+        let blueprint = blueprint.into();
+        for _ in 0..count {
+            let entity = commands.spawn_blueprint(blueprint.clone()).spawn();
+            commands.entity(entity).insert(PooledInactive);
+            self.members.insert(entity, blueprint.clone());
+            self.available
+                .entry(blueprint.clone())
+                .or_default()
+                .push(entity);
+        }
+    }
+
+    /// get an instance of the given blueprint at the given transform: an inactive one if available, otherwise a new one
+    pub fn acquire(
+        &mut self,
+        commands: &mut Commands,
+        blueprint: &str,
+        transform: Transform,
+    ) -> Entity {
+        if let Some(entity) = self
+            .available
+            .get_mut(blueprint)
+            .and_then(|available| available.pop())
+        {
+            commands
+                .entity(entity)
+                .remove::<PooledInactive>()
+                .insert((transform, Visibility::Inherited));
+            return entity;
+        }
+        let entity = commands.spawn_blueprint(blueprint).at(transform).spawn();
+        self.members.insert(entity, blueprint.to_string());
+        entity
+    }
+
+    /// deactivate an instance acquired from the pool, so that it can be reused
+    /// (instances that were not acquired from the pool are left untouched)
+    pub fn release(&mut self, commands: &mut Commands, entity: Entity) {
+        let Some(blueprint) = self.members.get(&entity) else {
+            warn!("entity {:?} does not belong to the blueprint pool", entity);
+            return;
+        };
+        let available = self.available.entry(blueprint.clone()).or_default();
+        if available.contains(&entity) {
+            return;
+        }
+        available.push(entity);
+        commands
+            .entity(entity)
+            .insert((PooledInactive, Visibility::Hidden));
+    }
+
+    /// amount of inactive instances of the given blueprint
+    pub fn available(&self, blueprint: &str) -> usize {
+        self.available.get(blueprint).map_or(0, Vec::len)
+    }
+}
+
+/// keeps inactive pooled instances hidden, even when they finish spawning
+pub(crate) fn hide_inactive_pooled_instances(
+    mut inactive: Query<&mut Visibility, With<PooledInactive>>,
+) {
+    for mut visibility in inactive.iter_mut() {
+        visibility.set_if_neq(Visibility::Hidden);
+    }
+}
+
+/// forget about pooled instances that were despawned
+pub(crate) fn cleanup_blueprint_pool(
+    mut pool: ResMut<BlueprintPool>,
+    mut removed: RemovedComponents<BlueprintName>,
+    blueprint_instances: Query<(), With<BlueprintName>>,
+) {
+    for entity in removed.read() {
+        // respawned instances get their BlueprintName re-inserted: they are still part of the pool
+        if blueprint_instances.contains(entity) {
+            continue;
+        }
+        if let Some(blueprint) = pool.members.remove(&entity) {
+            if let Some(available) = pool.available.get_mut(&blueprint) {
+                available.retain(|e| *e != entity);
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::GltfBlueprintsSet;
+
+/// names of the post processors registered with [`BlueprintPostProcessorAppExt::add_blueprint_post_processor`]
+#[derive(Resource, Debug, Default)]
+pub struct BlueprintPostProcessors(pub(crate) Vec<&'static str>);
+
+/// component present on blueprint instances during post processing, listing the registered post processors that have not completed yet:
+/// the instance only gets finalized (and marked as ready) once all of them are completed
+#[derive(Component, Debug, Default)]
+pub struct PendingPostProcessors(pub(crate) HashSet<&'static str>);
+
+impl PendingPostProcessors {
+    /// is the post processor with the given name still pending for this instance
+    pub fn is_pending(&self, name: &str) -> bool {
+        self.0.contains(name)
+    }
+
+    /// mark the post processor with the given name as completed for this instance
+    pub fn complete(&mut self, name: &str) {
+        self.0.remove(name);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// extension trait for [`App`], to register post processors for blueprint instances
+pub trait BlueprintPostProcessorAppExt {
+    /// register a system that post processes blueprint instances (adding physics, AI, audio etc) before they are marked as ready
+    /// * the system runs in [`GltfBlueprintsSet::PostProcess`]
+    /// * it should query for [`PendingPostProcessors`] and call `complete(name)` once it is done with an instance (this can take several frames)
+    ///
+    /// ```ignore
+    /// app.add_blueprint_post_processor("physics", add_physics);
+    ///
+    /// fn add_physics(mut instances: Query<(Entity, &mut PendingPostProcessors)>, mut commands: Commands) {
+    ///     for (entity, mut pending) in instances.iter_mut() {
+    ///         if pending.is_pending("physics") {
+    ///             commands.entity(entity).insert(RigidBody::Dynamic);
+    ///             pending.complete("physics");
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn add_blueprint_post_processor<M>(
+        &mut self,
+        name: &'static str,
+        system: impl IntoSystemConfigs<M>,
+    ) -> &mut Self;
+}
+
+impl BlueprintPostProcessorAppExt for App {
+    fn add_blueprint_post_processor<M>(
+        &mut self,
+        name: &'static str,
+        system: impl IntoSystemConfigs<M>,
+    ) -> &mut Self {
+        self.init_resource::<BlueprintPostProcessors>();
+        self.world
+            .resource_mut::<BlueprintPostProcessors>()
+            .0
+            .push(name);
+        self.add_systems(PostUpdate, system.in_set(GltfBlueprintsSet::PostProcess))
+    }
+}
+
+/// component holding user defined conditions ("navmesh baked", "physics sleeping" ...) that must all be cleared
+/// before the blueprint instance is marked as ready (and revealed if it has a `HideUntilReady` component)
+#[derive(Component, Debug, Default, Clone)]
+pub struct ReadyGate(pub(crate) HashSet<String>);
+
+impl ReadyGate {
+    pub fn new<S: Into<String>>(conditions: impl IntoIterator<Item = S>) -> Self {
+        Self(conditions.into_iter().map(Into::into).collect())
+    }
+
+    /// is the given condition still blocking the instance from being ready
+    pub fn is_pending(&self, condition: &str) -> bool {
+        self.0.contains(condition)
+    }
+
+    /// clear the given condition
+    pub fn clear(&mut self, condition: &str) {
+        self.0.remove(condition);
+    }
+
+    /// are all the conditions cleared
+    pub fn is_open(&self) -> bool {
+        self.0.is_empty()
+    }
+}
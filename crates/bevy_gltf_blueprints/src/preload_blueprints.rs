@@ -0,0 +1,70 @@
+use bevy::{asset::RecursiveDependencyLoadState, gltf::Gltf, prelude::*};
+
+use crate::{BluePrintsConfig, BlueprintAssetsCache, BlueprintEvent, Library};
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+/// component requesting to load a blueprint (gltf file & its dependencies) into the [`BlueprintAssetsCache`] without spawning it:
+/// the entity holding it is despawned once loading is done, and a `BlueprintEvent::Preloaded` event is sent
+/// (use a [`Library`] component to preload from another folder than the default library folder)
+pub struct PreloadBlueprint(pub String);
+
+#[derive(Component, Debug)]
+pub(crate) struct PreloadingBlueprint(pub Handle<Gltf>);
+
+/// extension trait for [`Commands`], to preload blueprints before they are needed
+pub trait PreloadBlueprintCommandsExt {
+    /// load the blueprint with the given name (and its dependencies), without spawning it
+    fn preload_blueprint(&mut self, name: impl Into<String>) -> Entity;
+}
+
+impl<'w, 's> PreloadBlueprintCommandsExt for Commands<'w, 's> {
+    fn preload_blueprint(&mut self, name: impl Into<String>) -> Entity {
+        self.spawn((
+            Name::from("preload_blueprint"),
+            PreloadBlueprint(name.into()),
+        ))
+        .id()
+    }
+}
+
+pub(crate) fn preload_blueprints(
+    added_preloads: Query<(Entity, &PreloadBlueprint, Option<&Library>), Added<PreloadBlueprint>>,
+    preloading: Query<(Entity, &PreloadBlueprint, &PreloadingBlueprint)>,
+
+    asset_server: Res<AssetServer>,
+    blueprints_config: Res<BluePrintsConfig>,
+    mut blueprints_cache: ResMut<BlueprintAssetsCache>,
+    mut commands: Commands,
+    mut blueprint_events: EventWriter<BlueprintEvent>,
+) {
+    for (entity, preload, library_override) in added_preloads.iter() {
+        let model_path = blueprints_config.blueprint_path(&preload.0, library_override);
+        debug!("preloading blueprint {:?}", model_path);
+        let handle = blueprints_cache.gltf(&model_path, &asset_server);
+        commands.entity(entity).insert(PreloadingBlueprint(handle));
+    }
+
+    for (entity, preload, preloading) in preloading.iter() {
+        let asset_id = preloading.0.id();
+        if asset_server.is_loaded_with_dependencies(asset_id) {
+            blueprint_events.send(BlueprintEvent::Preloaded {
+                blueprint_name: preload.0.clone(),
+            });
+            commands.entity(entity).despawn_recursive();
+        } else if asset_server.get_recursive_dependency_load_state(asset_id)
+            == Some(RecursiveDependencyLoadState::Failed)
+        {
+            blueprint_events.send(BlueprintEvent::AssetLoadFailed {
+                entity,
+                blueprint_name: preload.0.clone(),
+                asset_path: preloading
+                    .0
+                    .path()
+                    .map(|path| path.to_string())
+                    .unwrap_or_default(),
+            });
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
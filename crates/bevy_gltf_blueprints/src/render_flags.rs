@@ -0,0 +1,103 @@
+use bevy::{
+    gltf::GltfExtras,
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    render::view::RenderLayers,
+    utils::HashMap,
+};
+use ron::Value;
+
+use crate::{BlueprintName, BlueprintReadyForFinalizing};
+
+/// the raw custom properties of an object, that do not map to components
+fn custom_properties(extras: &GltfExtras) -> HashMap<String, Value> {
+    ron::from_str(&extras.value).unwrap_or_default()
+}
+
+fn is_set(value: &Value) -> bool {
+    match value {
+        Value::Bool(value) => *value,
+        Value::Number(number) => number.into_f64() != 0.0,
+        Value::String(value) => value == "true" || value == "1",
+        _ => false,
+    }
+}
+
+/// the layers of a `render_layers` custom property, ie `[1, 2]` or "1,2"
+fn layers(value: &Value) -> Vec<u8> {
+    match value {
+        Value::Seq(layers) => layers
+            .iter()
+            .filter_map(|layer| match layer {
+                Value::Number(number) => Some(number.into_f64() as u8),
+                _ => None,
+            })
+            .collect(),
+        Value::Number(number) => vec![number.into_f64() as u8],
+        Value::String(layers) => layers
+            .split(',')
+            .filter_map(|layer| layer.trim().parse().ok())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// applies the render related custom properties of the objects of blueprint instances, once they are post processed:
+/// * `render_layers` (ie `[1, 2]`): `RenderLayers` of the meshes of the object
+/// * `hide_render`: hides the object (& its children)
+/// * `not_shadow_caster` / `not_shadow_receiver`: the meshes of the object do not cast/receive shadows
+pub(crate) fn apply_render_flags(
+    instances: Query<Entity, Added<BlueprintReadyForFinalizing>>,
+    all_children: Query<&Children>,
+    nested_instances: Query<(), With<BlueprintName>>,
+    extras: Query<&GltfExtras>,
+    meshes: Query<(), With<Handle<Mesh>>>,
+    mut commands: Commands,
+) {
+    for instance in instances.iter() {
+        let mut stack = vec![instance];
+        while let Some(entity) = stack.pop() {
+            let children = all_children.get(entity).ok();
+            for child in children.into_iter().flatten() {
+                // nested blueprint instances apply their own flags
+                if !nested_instances.contains(*child) {
+                    stack.push(*child);
+                }
+            }
+            let Ok(extras) = extras.get(entity) else {
+                continue;
+            };
+            let properties = custom_properties(extras);
+            if properties.get("hide_render").is_some_and(is_set) {
+                commands.entity(entity).insert(Visibility::Hidden);
+            }
+
+            let render_layers = properties
+                .get("render_layers")
+                .map(|value| RenderLayers::from_layers(&layers(value)));
+            let not_shadow_caster = properties.get("not_shadow_caster").is_some_and(is_set);
+            let not_shadow_receiver = properties.get("not_shadow_receiver").is_some_and(is_set);
+            if render_layers.is_none() && !not_shadow_caster && !not_shadow_receiver {
+                continue;
+            }
+            // these are not inherited: bevy spawns one entity per primitive of the mesh of the object
+            for mesh_entity in
+                std::iter::once(entity).chain(children.into_iter().flatten().copied())
+            {
+                if !meshes.contains(mesh_entity) {
+                    continue;
+                }
+                let mut mesh_commands = commands.entity(mesh_entity);
+                if let Some(render_layers) = render_layers {
+                    mesh_commands.insert(render_layers);
+                }
+                if not_shadow_caster {
+                    mesh_commands.insert(NotShadowCaster);
+                }
+                if not_shadow_receiver {
+                    mesh_commands.insert(NotShadowReceiver);
+                }
+            }
+        }
+    }
+}
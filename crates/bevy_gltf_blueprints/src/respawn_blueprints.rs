@@ -0,0 +1,110 @@
+use bevy::{prelude::*, scene::SceneInstance, utils::HashMap};
+
+use crate::{
+    AnimationPlayerLink, Animations, BlueprintEvent, BlueprintName, BlueprintSpawnFailed,
+    BlueprintVariant, BlueprintsList, OriginalChildren, RequestedBlueprint, SpawnHere, Spawned,
+};
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+/// flag component to request re-spawning an (already spawned) blueprint instance:
+/// - the children that came from the blueprint are despawned, the original children are kept
+/// - the components of the instance itself are kept
+/// - the blueprint is then spawned again, sending the usual events, prefixed by a `BlueprintEvent::Respawning` event
+pub struct RespawnBlueprint;
+
+pub(crate) fn respawn_blueprints(
+    to_respawn: Query<
+        (
+            Entity,
+            &BlueprintName,
+            Option<&Children>,
+            Option<&OriginalChildren>,
+        ),
+        (With<RespawnBlueprint>, Without<SpawnHere>),
+    >,
+    mut commands: Commands,
+    mut blueprint_events: EventWriter<BlueprintEvent>,
+) {
+    for (entity, blueprint_name, children, original_children) in to_respawn.iter() {
+        debug!("respawning blueprint instance {:?}", entity);
+        teardown_blueprint_instance(&mut commands, entity, children, original_children);
+
+        // re-inserting these triggers the whole spawning pipeline again
+        commands
+            .entity(entity)
+            .remove::<(RespawnBlueprint, BlueprintName)>()
+            .insert((BlueprintName(blueprint_name.0.clone()), SpawnHere));
+
+        blueprint_events.send(BlueprintEvent::Respawning {
+            entity,
+            blueprint_name: blueprint_name.0.clone(),
+        });
+    }
+}
+
+/// changing the `BlueprintVariant` of an already spawned instance respawns it with the new variant
+pub(crate) fn switch_blueprint_variants(
+    changed_variants: Query<Entity, (Changed<BlueprintVariant>, Without<SpawnHere>)>,
+    mut commands: Commands,
+) {
+    for entity in changed_variants.iter() {
+        debug!("switching variant of blueprint instance {:?}", entity);
+        commands.entity(entity).insert(RespawnBlueprint);
+    }
+}
+
+/// changing the `BlueprintName` of an already spawned (or failed) instance swaps it for the new blueprint (skins, modular upgrades...):
+/// the instance is respawned like with [`RespawnBlueprint`], keeping its original children & components
+pub(crate) fn swap_blueprints(
+    changed_blueprints: Query<
+        (Entity, &BlueprintName, &RequestedBlueprint),
+        (Changed<BlueprintName>, Without<SpawnHere>),
+    >,
+    mut commands: Commands,
+) {
+    for (entity, blueprint_name, requested_blueprint) in changed_blueprints.iter() {
+        // respawns & fallbacks re-insert the BlueprintName too, those are not swaps
+        if blueprint_name.0 == requested_blueprint.0 {
+            continue;
+        }
+        debug!(
+            "swapping blueprint of instance {:?} to {}",
+            entity, blueprint_name.0
+        );
+        commands
+            .entity(entity)
+            .remove::<BlueprintSpawnFailed>()
+            .insert((
+                // so that the new blueprint gets loaded before spawning, like any other blueprint
+                BlueprintsList(HashMap::from([(blueprint_name.0.clone(), vec![])])),
+                RespawnBlueprint,
+            ));
+    }
+}
+
+/// despawns the children that were spawned from the blueprint & removes the spawning related components,
+/// leaving the instance as it was before spawning (original children & user components are kept)
+pub(crate) fn teardown_blueprint_instance(
+    commands: &mut Commands,
+    entity: Entity,
+    children: Option<&Children>,
+    original_children: Option<&OriginalChildren>,
+) {
+    if let Some(children) = children {
+        for child in children.iter() {
+            let is_original = original_children.is_some_and(|o| o.0.contains(child));
+            if !is_original {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+    }
+    commands.entity(entity).remove::<(
+        SceneInstance,
+        Handle<Scene>,
+        Animations,
+        AnimationPlayerLink,
+        Spawned,
+        OriginalChildren,
+    )>();
+}
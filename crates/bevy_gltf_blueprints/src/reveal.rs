@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+
+use crate::{BlueprintSpawnState, HideUntilReady};
+
+/// how instances with [`HideUntilReady`] are revealed once they are ready
+#[derive(Component, Reflect, Default, Debug, Clone, Copy, PartialEq)]
+#[reflect(Component)]
+pub enum RevealMode {
+    /// shown all at once
+    #[default]
+    Instant,
+    /// faded in over the given duration (in seconds), by animating the alpha of its materials
+    Fade { duration: f32 },
+}
+
+/// a fade in in progress, with the original & temporary (faded) material of each mesh entity of the instance
+#[derive(Component, Debug)]
+pub(crate) struct FadeReveal {
+    elapsed: f32,
+    duration: f32,
+    materials: Vec<(
+        Entity,
+        Handle<StandardMaterial>,
+        Handle<StandardMaterial>,
+        f32,
+    )>,
+}
+
+/// swaps the materials of the instances revealed with a [`RevealMode::Fade`] for transparent copies, as they get ready
+#[allow(clippy::type_complexity)]
+pub(crate) fn start_fade_reveals(
+    instances: Query<
+        (Entity, &RevealMode, &BlueprintSpawnState),
+        (With<HideUntilReady>, Changed<BlueprintSpawnState>),
+    >,
+    all_children: Query<&Children>,
+    mesh_materials: Query<&Handle<StandardMaterial>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    for (instance, reveal_mode, spawn_state) in instances.iter() {
+        let RevealMode::Fade { duration } = *reveal_mode else {
+            continue;
+        };
+        if *spawn_state != BlueprintSpawnState::Ready {
+            continue;
+        }
+        let mut faded_materials = vec![];
+        for entity in std::iter::once(instance).chain(all_children.iter_descendants(instance)) {
+            let Ok(original) = mesh_materials.get(entity) else {
+                continue;
+            };
+            let Some(mut faded) = materials.get(original).cloned() else {
+                continue;
+            };
+            let alpha = faded.base_color.a();
+            faded.base_color.set_a(0.0);
+            faded.alpha_mode = AlphaMode::Blend;
+            let faded = materials.add(faded);
+            commands.entity(entity).insert(faded.clone());
+            faded_materials.push((entity, original.clone(), faded, alpha));
+        }
+        commands.entity(instance).insert(FadeReveal {
+            elapsed: 0.0,
+            duration,
+            materials: faded_materials,
+        });
+    }
+}
+
+/// animates the alpha of the faded materials, and restores the original materials once done
+pub(crate) fn update_fade_reveals(
+    mut instances: Query<(Entity, &mut FadeReveal)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (instance, mut fade) in instances.iter_mut() {
+        fade.elapsed += time.delta_seconds();
+        let progress = (fade.elapsed / fade.duration.max(f32::EPSILON)).min(1.0);
+        for (entity, original, faded, alpha) in fade.materials.iter() {
+            if progress >= 1.0 {
+                if let Some(mut entity_commands) = commands.get_entity(*entity) {
+                    entity_commands.insert(original.clone());
+                }
+            } else if let Some(material) = materials.get_mut(faded) {
+                material.base_color.set_a(alpha * progress);
+            }
+        }
+        if progress >= 1.0 {
+            commands.entity(instance).remove::<FadeReveal>();
+        }
+    }
+}
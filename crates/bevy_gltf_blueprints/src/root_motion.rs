@@ -0,0 +1,200 @@
+use bevy::{
+    animation::{EntityPath, Interpolation, Keyframes, VariableCurve},
+    prelude::*,
+    reflect::GetPath,
+    utils::HashMap,
+};
+
+use crate::{AnimationPlayerLink, Animations, BluePrintsConfig};
+
+/// the horizontal motion of the root bone of an animation, extracted from its clip
+#[derive(Debug, Clone)]
+pub(crate) struct RootMotionCurve {
+    timestamps: Vec<f32>,
+    translations: Vec<Vec3>,
+}
+
+impl RootMotionCurve {
+    fn sample(&self, time: f32) -> Vec3 {
+        let (Some(first), Some(last)) = (self.timestamps.first(), self.timestamps.last()) else {
+            return Vec3::ZERO;
+        };
+        if time <= *first {
+            return self.translations[0];
+        }
+        if time >= *last {
+            return self.translations[self.translations.len() - 1];
+        }
+        let next = self
+            .timestamps
+            .partition_point(|timestamp| *timestamp <= time);
+        let (start, end) = (self.timestamps[next - 1], self.timestamps[next]);
+        let factor = (time - start) / (end - start);
+        self.translations[next - 1].lerp(self.translations[next], factor)
+    }
+
+    fn start(&self) -> f32 {
+        self.timestamps.first().copied().unwrap_or_default()
+    }
+
+    fn end(&self) -> f32 {
+        self.timestamps.last().copied().unwrap_or_default()
+    }
+}
+
+/// the root motion extracted from the animation clips (see `root_motion_bone` in the plugin)
+#[derive(Resource, Debug, Default)]
+pub struct RootMotionCurves(pub(crate) HashMap<AssetId<AnimationClip>, RootMotionCurve>);
+
+impl RootMotionCurves {
+    /// true if root motion was extracted from the given clip
+    pub fn contains(&self, clip: &Handle<AnimationClip>) -> bool {
+        self.0.contains_key(&clip.id())
+    }
+}
+
+/// the root motion of the current animation of a blueprint instance, for character controllers to move the instance with
+/// (instead of the animation sliding the mesh away from it): only updated if `root_motion_bone` is set in the plugin
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct RootMotion {
+    /// horizontal movement of the root bone since the last frame, in the space of the parent of the root bone
+    pub delta: Vec3,
+    #[reflect(ignore)]
+    pub(crate) last: Option<(AssetId<AnimationClip>, f32)>,
+}
+
+/// removes the horizontal translation of the root bone from a clip, returns it
+fn extract_root_motion(clip: &mut AnimationClip, root_bone: &str) -> Option<RootMotionCurve> {
+    let curve_index = clip
+        .path::<HashMap<EntityPath, usize>>("paths")
+        .ok()?
+        .iter()
+        .find(|(path, _)| {
+            path.parts
+                .last()
+                .is_some_and(|name| name.as_str() == root_bone)
+        })
+        .map(|(_, index)| *index)?;
+    let curves = clip
+        .path_mut::<Vec<Vec<VariableCurve>>>("curves")
+        .ok()?
+        .get_mut(curve_index)?;
+
+    for curve in curves.iter_mut() {
+        let Keyframes::Translation(translations) = &mut curve.keyframes else {
+            continue;
+        };
+        let cubic = matches!(curve.interpolation, Interpolation::CubicSpline);
+        // cubic splines store an in tangent, the value & an out tangent per keyframe
+        let values: Vec<Vec3> = if cubic {
+            translations.iter().skip(1).step_by(3).copied().collect()
+        } else {
+            translations.clone()
+        };
+        let origin = *values.first()?;
+        for (index, translation) in translations.iter_mut().enumerate() {
+            if cubic && index % 3 != 1 {
+                translation.x = 0.0;
+                translation.z = 0.0;
+            } else {
+                translation.x = origin.x;
+                translation.z = origin.z;
+            }
+        }
+        return Some(RootMotionCurve {
+            timestamps: curve.keyframe_timestamps.clone(),
+            translations: values
+                .iter()
+                .map(|value| Vec3::new(value.x - origin.x, 0.0, value.z - origin.z))
+                .collect(),
+        });
+    }
+    None
+}
+
+/// strips the root motion from animation clips as they get loaded
+pub(crate) fn extract_root_motions(
+    mut clip_events: EventReader<AssetEvent<AnimationClip>>,
+    mut clips: ResMut<Assets<AnimationClip>>,
+    mut root_motion_curves: ResMut<RootMotionCurves>,
+    blueprints_config: Res<BluePrintsConfig>,
+) {
+    let Some(root_bone) = blueprints_config.root_motion_bone.as_ref() else {
+        return;
+    };
+    for event in clip_events.read() {
+        match event {
+            AssetEvent::Added { id } | AssetEvent::LoadedWithDependencies { id } => {
+                if root_motion_curves.0.contains_key(id) {
+                    continue;
+                }
+                // check without flagging the clip as modified first
+                if !clips.get(*id).is_some_and(|clip| {
+                    clip.path::<HashMap<EntityPath, usize>>("paths")
+                        .is_ok_and(|paths| {
+                            paths.keys().any(|path| {
+                                path.parts
+                                    .last()
+                                    .is_some_and(|name| name.as_str() == root_bone)
+                            })
+                        })
+                }) {
+                    continue;
+                }
+                let Some(clip) = clips.get_mut(*id) else {
+                    continue;
+                };
+                if let Some(curve) = extract_root_motion(clip, root_bone) {
+                    debug!("extracted root motion of animation clip {:?}", id);
+                    root_motion_curves.0.insert(*id, curve);
+                }
+            }
+            AssetEvent::Removed { id } => {
+                root_motion_curves.0.remove(id);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// updates the [`RootMotion`] of animated blueprint instances, based on the playback of their current animation
+pub(crate) fn update_root_motion(
+    mut instances: Query<(Entity, &AnimationPlayerLink, Option<&mut RootMotion>), With<Animations>>,
+    players: Query<&AnimationPlayer>,
+    root_motion_curves: Res<RootMotionCurves>,
+    mut commands: Commands,
+) {
+    for (entity, link, root_motion) in instances.iter_mut() {
+        let Some(mut root_motion) = root_motion else {
+            commands.entity(entity).insert(RootMotion::default());
+            continue;
+        };
+        let Ok(player) = players.get(link.0) else {
+            continue;
+        };
+        let clip = player.animation_clip().id();
+        let now = player.seek_time();
+        let last = root_motion.last.replace((clip, now));
+
+        let delta = match (root_motion_curves.0.get(&clip), last) {
+            (Some(curve), Some((last_clip, last_time)))
+                if last_clip == clip && !player.is_paused() =>
+            {
+                let forward = player.speed() >= 0.0;
+                if (forward && now >= last_time) || (!forward && now <= last_time) {
+                    curve.sample(now) - curve.sample(last_time)
+                } else if forward {
+                    // looped back to the start
+                    (curve.sample(curve.end()) - curve.sample(last_time))
+                        + (curve.sample(now) - curve.sample(curve.start()))
+                } else {
+                    (curve.sample(curve.start()) - curve.sample(last_time))
+                        + (curve.sample(now) - curve.sample(curve.end()))
+                }
+            }
+            _ => Vec3::ZERO,
+        };
+        root_motion.delta = delta;
+    }
+}
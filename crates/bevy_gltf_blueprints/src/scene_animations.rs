@@ -0,0 +1,256 @@
+use std::time::Duration;
+
+use bevy::{
+    animation::EntityPath,
+    ecs::system::SystemParam,
+    gltf::Gltf,
+    prelude::*,
+    reflect::GetPath,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{Animations, BlueprintName, GameWorldReady, GameWorldTag};
+
+/// the animations of a level (or any game world) itself, ie moving platforms & doors animated in Blender,
+/// as opposed to the [`Animations`] of the blueprint instances inside it: added to the game world once it is ready
+#[derive(Component, Debug, Default)]
+pub struct SceneAnimations {
+    /// animation name => clip & entity with the `AnimationPlayer` playing it
+    pub animations: HashMap<String, (Handle<AnimationClip>, Entity)>,
+}
+
+/// plays an animation of the level as soon as it is loaded, when added to an animated object in Blender
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct AutoplayAnimation {
+    /// name of the animation to play, empty to play the (first) one animating the object
+    pub animation: String,
+    /// play the animation only once, instead of looping it
+    pub once: bool,
+}
+
+/// name of the entity a clip is meant to be played from
+fn clip_root(clip: &AnimationClip) -> Option<Name> {
+    clip.path::<HashMap<EntityPath, usize>>("paths")
+        .ok()?
+        .keys()
+        .find_map(|path| path.parts.first().cloned())
+}
+
+/// the descendants of the entity (itself included), without going into nested blueprint instances
+fn scene_entities(
+    root: Entity,
+    all_children: &Query<&Children>,
+    blueprint_instances: &Query<(), With<BlueprintName>>,
+) -> Vec<Entity> {
+    let mut entities = vec![];
+    let mut stack = vec![root];
+    while let Some(entity) = stack.pop() {
+        entities.push(entity);
+        for child in all_children.get(entity).into_iter().flatten() {
+            if !blueprint_instances.contains(*child) {
+                stack.push(*child);
+            }
+        }
+    }
+    entities
+}
+
+/// adds the [`SceneAnimations`] of game worlds once they are ready, and starts the [`AutoplayAnimation`]s
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn add_scene_animations(
+    worlds: Query<
+        (Entity, Option<&Animations>, Option<&Handle<Scene>>),
+        (
+            With<GameWorldTag>,
+            With<GameWorldReady>,
+            Without<SceneAnimations>,
+        ),
+    >,
+    all_children: Query<&Children>,
+    blueprint_instances: Query<(), With<BlueprintName>>,
+    mut players: Query<(&Name, &mut AnimationPlayer)>,
+    autoplays: Query<(Entity, &AutoplayAnimation)>,
+    asset_server: Res<AssetServer>,
+    gltfs: Res<Assets<Gltf>>,
+    clips: Res<Assets<AnimationClip>>,
+    mut commands: Commands,
+) {
+    for (world, animations, scene) in worlds.iter() {
+        // levels spawned as blueprints have their animations already, the others get them from their gltf file
+        let named_animations = match animations {
+            Some(animations) => animations.named_animations.clone(),
+            None => scene
+                .and_then(|scene| asset_server.get_path(scene.id()))
+                .and_then(|path| asset_server.get_handle::<Gltf>(path.without_label()))
+                .and_then(|gltf| gltfs.get(gltf))
+                .map(|gltf| gltf.named_animations.clone())
+                .unwrap_or_default(),
+        };
+
+        let entities = scene_entities(world, &all_children, &blueprint_instances);
+        let level_players: HashMap<Name, Entity> = entities
+            .iter()
+            .filter_map(|entity| {
+                players
+                    .get(*entity)
+                    .ok()
+                    .map(|(name, _)| (name.clone(), *entity))
+            })
+            .collect();
+
+        let mut scene_animations = SceneAnimations::default();
+        for (name, clip) in named_animations {
+            let Some(player) = clips
+                .get(&clip)
+                .and_then(clip_root)
+                .and_then(|root| level_players.get(&root))
+            else {
+                continue;
+            };
+            scene_animations.animations.insert(name, (clip, *player));
+        }
+
+        for (entity, autoplay) in autoplays.iter_many(&entities) {
+            let animated: HashSet<Entity> =
+                scene_entities(entity, &all_children, &blueprint_instances)
+                    .into_iter()
+                    .collect();
+            let mut to_play: Vec<(&String, &(Handle<AnimationClip>, Entity))> = scene_animations
+                .animations
+                .iter()
+                .filter(|(name, (_, player))| {
+                    animated.contains(player)
+                        && (autoplay.animation.is_empty() || **name == autoplay.animation)
+                })
+                .collect();
+            to_play.sort_by_key(|(name, _)| *name);
+            // a player can only play one animation at a time
+            let mut started: HashSet<Entity> = HashSet::new();
+            for (name, (clip, player)) in to_play {
+                if !started.insert(*player) {
+                    continue;
+                }
+                if let Ok((_, mut animation_player)) = players.get_mut(*player) {
+                    debug!("autoplaying scene animation {}", name);
+                    animation_player.play(clip.clone());
+                    if !autoplay.once {
+                        animation_player.repeat();
+                    }
+                }
+            }
+            if started.is_empty() {
+                warn!(
+                    "no animation {} to autoplay for {:?}",
+                    autoplay.animation, entity
+                );
+            }
+        }
+
+        commands.entity(world).insert(scene_animations);
+    }
+}
+
+/// controls the [`SceneAnimations`] of game worlds (levels): ie `scene_animations.get_mut(world)?.play("DoorOpen")`
+#[derive(SystemParam)]
+pub struct SceneAnimationsControl<'w, 's> {
+    worlds: Query<'w, 's, &'static SceneAnimations>,
+    players: Query<'w, 's, &'static mut AnimationPlayer>,
+}
+
+impl<'w, 's> SceneAnimationsControl<'w, 's> {
+    /// the animations of the given game world, None if it is not ready (yet)
+    pub fn get_mut(&mut self, world: Entity) -> Option<SceneAnimationsMut<'_, 'w, 's>> {
+        let animations = self.worlds.get(world).ok()?;
+        Some(SceneAnimationsMut {
+            world,
+            animations,
+            players: &mut self.players,
+        })
+    }
+}
+
+/// see [`SceneAnimationsControl`]
+pub struct SceneAnimationsMut<'a, 'w, 's> {
+    world: Entity,
+    animations: &'a SceneAnimations,
+    players: &'a mut Query<'w, 's, &'static mut AnimationPlayer>,
+}
+
+impl SceneAnimationsMut<'_, '_, '_> {
+    /// names of the animations of the level
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.animations.animations.keys().map(String::as_str)
+    }
+
+    /// the player of an animation of the level
+    pub fn player(&self, name: &str) -> Option<&AnimationPlayer> {
+        let (_, player) = self.animations.animations.get(name)?;
+        self.players.get(*player).ok()
+    }
+
+    /// true if the animation with the given name is the one playing on its object
+    pub fn is_playing(&self, name: &str) -> bool {
+        let Some((clip, player)) = self.animations.animations.get(name) else {
+            return false;
+        };
+        self.players.get(*player).is_ok_and(|player| {
+            player.animation_clip() == clip && !player.is_paused() && !player.is_finished()
+        })
+    }
+
+    fn player_mut(&mut self, name: &str) -> Option<(Handle<AnimationClip>, Mut<AnimationPlayer>)> {
+        let Some((clip, player)) = self.animations.animations.get(name) else {
+            warn!(
+                "no scene animation named {} in {:?}, available animations: {:?}",
+                name,
+                self.world,
+                self.animations.animations.keys()
+            );
+            return None;
+        };
+        let player = self.players.get_mut(*player).ok()?;
+        Some((clip.clone(), player))
+    }
+
+    /// plays the animation with the given name, looping it if `repeat` is true
+    pub fn play(&mut self, name: &str, repeat: bool) -> &mut Self {
+        if let Some((clip, mut player)) = self.player_mut(name) {
+            player.play(clip);
+            if repeat {
+                player.repeat();
+            }
+        }
+        self
+    }
+
+    /// blends into the animation with the given name over `duration` seconds
+    pub fn crossfade(&mut self, name: &str, duration: f32) -> &mut Self {
+        if let Some((clip, mut player)) = self.player_mut(name) {
+            player.play_with_transition(clip, Duration::from_secs_f32(duration));
+        }
+        self
+    }
+
+    /// stops the animation with the given name: pauses it & goes back to its start
+    pub fn stop(&mut self, name: &str) -> &mut Self {
+        if let Some((clip, mut player)) = self.player_mut(name) {
+            if *player.animation_clip() == clip {
+                player.pause();
+                player.seek_to(0.0);
+            }
+        }
+        self
+    }
+
+    /// stops all the animations of the level
+    pub fn stop_all(&mut self) -> &mut Self {
+        for (_, player) in self.animations.animations.values() {
+            if let Ok(mut player) = self.players.get_mut(*player) {
+                player.pause();
+                player.seek_to(0.0);
+            }
+        }
+        self
+    }
+}
@@ -0,0 +1,104 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use bevy::{
+    prelude::*,
+    render::mesh::skinning::{SkinnedMesh, SkinnedMeshInverseBindposes},
+    utils::HashMap,
+};
+
+use crate::{consolidated, unused, Animations};
+
+/// immutable skinning data with the same content, shared between blueprint instances when `share_skins` is enabled in the plugin:
+/// * inverse bind matrices of skinned meshes (ie several character blueprints exported with the same armature)
+/// * animation clips of the [`Animations`] of instances
+///
+/// the joints themselves are not shared, as each instance is animated independently
+#[derive(Resource, Debug, Default)]
+pub struct SharedSkins {
+    pub(crate) inverse_bindposes: HashMap<u64, Handle<SkinnedMeshInverseBindposes>>,
+    pub(crate) inverse_bindposes_hashes: HashMap<AssetId<SkinnedMeshInverseBindposes>, u64>,
+    pub(crate) clips: HashMap<u64, Handle<AnimationClip>>,
+    pub(crate) clip_hashes: HashMap<AssetId<AnimationClip>, u64>,
+}
+
+impl SharedSkins {
+    /// amount of distinct inverse bind matrices & animation clips currently shared
+    pub fn len(&self) -> usize {
+        self.inverse_bindposes.len() + self.clips.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn inverse_bindposes_hash(inverse_bindposes: &SkinnedMeshInverseBindposes) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for matrix in inverse_bindposes.iter() {
+        for value in matrix.to_cols_array() {
+            value.to_bits().hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+fn clip_hash(clip: &AnimationClip) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", clip).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// swaps the skinning data of newly spawned skinned meshes & animated instances with the shared copies
+pub(crate) fn share_skins(
+    mut skinned_meshes: Query<&mut SkinnedMesh, Added<SkinnedMesh>>,
+    mut animated: Query<&mut Animations, Added<Animations>>,
+    inverse_bindposes: Res<Assets<SkinnedMeshInverseBindposes>>,
+    clips: Res<Assets<AnimationClip>>,
+    mut shared_skins: ResMut<SharedSkins>,
+) {
+    if skinned_meshes.is_empty() && animated.is_empty() {
+        return;
+    }
+    let SharedSkins {
+        inverse_bindposes: shared_inverse_bindposes,
+        inverse_bindposes_hashes,
+        clips: shared_clips,
+        clip_hashes,
+    } = &mut *shared_skins;
+    shared_inverse_bindposes.retain(|_, handle| !unused(handle));
+    shared_clips.retain(|_, handle| !unused(handle));
+
+    for mut skinned_mesh in skinned_meshes.iter_mut() {
+        let shared = consolidated(
+            &skinned_mesh.inverse_bindposes,
+            || {
+                inverse_bindposes
+                    .get(&skinned_mesh.inverse_bindposes)
+                    .map(inverse_bindposes_hash)
+            },
+            inverse_bindposes_hashes,
+            shared_inverse_bindposes,
+        );
+        if shared != skinned_mesh.inverse_bindposes {
+            skinned_mesh.inverse_bindposes = shared;
+        }
+    }
+
+    for mut animations in animated.iter_mut() {
+        for clip in animations
+            .bypass_change_detection()
+            .named_animations
+            .values_mut()
+        {
+            let shared = consolidated(
+                clip,
+                || clips.get(&*clip).map(clip_hash),
+                clip_hashes,
+                shared_clips,
+            );
+            if shared != *clip {
+                *clip = shared;
+            }
+        }
+    }
+}
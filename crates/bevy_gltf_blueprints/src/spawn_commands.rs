@@ -0,0 +1,204 @@
+use std::path::{Path, PathBuf};
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    AddToGameWorld, AttachTo, BlueprintName, BlueprintsList, HideUntilReady, Library,
+    PauseUntilReady, SpawnDisabled, SpawnHere, SpawnProxy, TargetGameWorld,
+};
+
+/// builder returned by [`SpawnBlueprintCommandsExt::spawn_blueprint`]
+/// it inserts the correct set of components for a blueprint instance, so you do not have to remember them all
+#[must_use = "the blueprint instance is only spawned once `spawn` is called"]
+pub struct BlueprintSpawnBuilder<'w, 's, 'a> {
+    commands: &'a mut Commands<'w, 's>,
+    name: String,
+    library: Option<PathBuf>,
+    transform: Transform,
+    add_to_world: bool,
+    target_world: Option<String>,
+    parent: Option<Entity>,
+    hide_until_ready: bool,
+    pause_until_ready: bool,
+    disabled: bool,
+    proxy: Option<SpawnProxy>,
+    attach_to: Option<AttachTo>,
+}
+
+impl<'w, 's, 'a> BlueprintSpawnBuilder<'w, 's, 'a> {
+    /// place the instance at the given transform (relative to its parent, if any)
+    pub fn at(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// load the blueprint from the given folder instead of the default library folder (see [`Library`])
+    pub fn library(mut self, library_folder: impl Into<PathBuf>) -> Self {
+        self.library = Some(library_folder.into());
+        self
+    }
+
+    /// add the instance as a child of the entity tagged with [`crate::GameWorldTag`] (see [`AddToGameWorld`])
+    pub fn add_to_world(mut self) -> Self {
+        self.add_to_world = true;
+        self
+    }
+
+    /// add the instance as a child of the game world with the given name (see [`crate::GameWorldName`])
+    pub fn add_to_named_world(mut self, world_name: impl Into<String>) -> Self {
+        self.add_to_world = true;
+        self.target_world = Some(world_name.into());
+        self
+    }
+
+    /// add the instance as a child of the given entity
+    pub fn child_of(mut self, parent: Entity) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// attach the instance to the named child node or bone of the target, once both are spawned (see [`AttachTo`])
+    pub fn attach_to(mut self, target: Entity, socket: impl Into<String>) -> Self {
+        self.attach_to = Some(AttachTo {
+            target,
+            socket: socket.into(),
+        });
+        self
+    }
+
+    /// keep the instance hidden until it is fully spawned (see [`HideUntilReady`])
+    pub fn hide_until_ready(mut self) -> Self {
+        self.hide_until_ready = true;
+        self
+    }
+
+    /// keep the animations & rigid bodies of the instance frozen until it is fully spawned (see [`PauseUntilReady`])
+    pub fn pause_until_ready(mut self) -> Self {
+        self.pause_until_ready = true;
+        self
+    }
+
+    /// spawn the instance disabled: hidden until enabled with `commands.enable_blueprint_instance(entity)` (see [`SpawnDisabled`])
+    pub fn disabled(mut self) -> Self {
+        self.disabled = true;
+        self
+    }
+
+    /// show a cheap stand-in while the instance is loading (see [`SpawnProxy`])
+    pub fn with_proxy(mut self, proxy: SpawnProxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// spawns the instance and returns its entity
+    /// * if both `add_to_world` and `child_of` were requested, the explicit parent wins
+    pub fn spawn(self) -> Entity {
+        let mut entity_commands = self.commands.spawn((
+            // so that the gltf file of the blueprint gets loaded before spawning
+            BlueprintsList(HashMap::from([(self.name.clone(), vec![])])),
+            BlueprintName(self.name),
+            SpawnHere,
+            TransformBundle::from_transform(self.transform),
+        ));
+        if let Some(library) = self.library {
+            entity_commands.insert(Library(library));
+        }
+        if self.hide_until_ready {
+            entity_commands.insert(HideUntilReady);
+        }
+        if self.pause_until_ready {
+            entity_commands.insert(PauseUntilReady);
+        }
+        if self.disabled {
+            entity_commands.insert(SpawnDisabled);
+        }
+        if let Some(proxy) = self.proxy {
+            entity_commands.insert(proxy);
+        }
+        if let Some(attach_to) = self.attach_to {
+            entity_commands.insert(attach_to);
+        }
+
+        match self.parent {
+            Some(parent) => {
+                if self.add_to_world {
+                    warn!("blueprint instance {:?} was requested to be added to the game world AND to parent {:?}, using the explicit parent", entity_commands.id(), parent);
+                }
+                entity_commands.set_parent(parent);
+            }
+            None => {
+                if self.add_to_world {
+                    entity_commands.insert(AddToGameWorld);
+                }
+                if let Some(target_world) = self.target_world {
+                    entity_commands.insert(TargetGameWorld(target_world));
+                }
+            }
+        }
+        entity_commands.id()
+    }
+}
+
+/// extension trait for [`Commands`], to spawn blueprint instances without having to assemble their components by hand
+pub trait SpawnBlueprintCommandsExt<'w, 's> {
+    /// start building a new instance of the blueprint with the given name
+    ///
+    /// ```ignore
+    /// commands
+    ///     .spawn_blueprint("Health_Pickup")
+    ///     .at(Transform::from_xyz(x, 2.0, y))
+    ///     .add_to_world()
+    ///     .spawn();
+    /// ```
+    fn spawn_blueprint(&mut self, name: impl Into<String>) -> BlueprintSpawnBuilder<'w, 's, '_>;
+
+    /// start building a new instance of the blueprint stored at the given path (relative to the assets folder)
+    /// the folder is used as the [`Library`] and the file name (without extension) as the [`BlueprintName`]
+    ///
+    /// ```ignore
+    /// let enemy = commands
+    ///     .spawn_blueprint_from_path("models/library/Enemy.glb")
+    ///     .at(Transform::from_xyz(x, 0.0, y))
+    ///     .hide_until_ready()
+    ///     .spawn();
+    /// ```
+    fn spawn_blueprint_from_path(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> BlueprintSpawnBuilder<'w, 's, '_>;
+}
+
+impl<'w, 's> SpawnBlueprintCommandsExt<'w, 's> for Commands<'w, 's> {
+    fn spawn_blueprint(&mut self, name: impl Into<String>) -> BlueprintSpawnBuilder<'w, 's, '_> {
+        BlueprintSpawnBuilder {
+            commands: self,
+            name: name.into(),
+            library: None,
+            transform: Transform::default(),
+            add_to_world: false,
+            target_world: None,
+            parent: None,
+            hide_until_ready: false,
+            pause_until_ready: false,
+            disabled: false,
+            proxy: None,
+            attach_to: None,
+        }
+    }
+
+    fn spawn_blueprint_from_path(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> BlueprintSpawnBuilder<'w, 's, '_> {
+        let path = path.as_ref();
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let builder = self.spawn_blueprint(name);
+        match path.parent() {
+            Some(library_folder) => builder.library(library_folder),
+            None => builder,
+        }
+    }
+}
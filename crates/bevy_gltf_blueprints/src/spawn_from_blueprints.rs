@@ -1,13 +1,52 @@
-use std::path::{Path, PathBuf};
-
-use bevy::{gltf::Gltf, prelude::*, utils::HashMap};
-
-use crate::{Animations, BluePrintsConfig};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{
+    asset::RecursiveDependencyLoadState,
+    gltf::Gltf,
+    prelude::*,
+    utils::{HashMap, HashSet, Instant},
+};
+
+use bevy_gltf_components::{GltfComponentError, Strictness};
+
+use crate::{
+    teardown_blueprint_instance, Animations, AwaitingComponentInjection, BluePrintsConfig,
+    BlueprintAssetInfo, BlueprintAssetKind, BlueprintAssetsCache, BlueprintEvent,
+    BlueprintInstanceAssets, BlueprintReadyForFinalizing, BlueprintSpawnError,
+    BlueprintSpawnFailed,
+};
 
 /// this is a flag component for our levels/game world
 #[derive(Component)]
 pub struct GameWorldTag;
 
+#[derive(Component, Reflect, Default, Debug, Clone, PartialEq, Eq)]
+#[reflect(Component)]
+/// name of a game world, to have multiple game worlds (main world + interior cell, split-screen arenas...)
+/// use [`TargetGameWorld`] on instances to pick the game world they get added to
+pub struct GameWorldName(pub String);
+
+#[derive(Component, Reflect, Default, Debug, Clone, PartialEq, Eq)]
+#[reflect(Component)]
+/// name of the game world (see [`GameWorldName`]) that an instance with [`AddToGameWorld`] gets added to
+/// (not needed if there is only one game world)
+pub struct TargetGameWorld(pub String);
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+/// explicit parent of a blueprint instance: the instance is added as a child of that entity once its scene is spawned
+/// (takes precedence over [`AddToGameWorld`])
+pub struct SpawnParent(pub Entity);
+
+#[derive(Component, Reflect, Default, Debug, Clone, PartialEq, Eq)]
+#[reflect(Component)]
+/// name of a marker entity (ie an empty exported from Blender, like `PlayerStart_01`) to spawn the blueprint instance at:
+/// the instance is placed at the global transform of the marker, instead of using its own transform
+/// * the marker is looked up in the game world of the instance (or in the only game world, for instances outside of game worlds)
+pub struct SpawnAtMarker(pub String);
+
 /// Main component for the blueprints
 #[derive(Component, Reflect, Default, Debug)]
 #[reflect(Component)]
@@ -42,10 +81,44 @@ pub struct Library(pub PathBuf);
 /// flag component to force adding newly spawned entity as child of game world
 pub struct AddToGameWorld;
 
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+/// flag component to keep the blueprint instance hidden until it is fully spawned & post processed
+pub struct HideUntilReady;
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+/// flag component to keep the root node of the blueprint scene as a child of the instance, instead of flattening it
+/// (no transfer of the root's components, the exported hierarchy & local transforms are kept as is)
+pub struct KeepBlueprintRoot;
+
+#[derive(Component, Reflect, Default, Debug, Clone, PartialEq, Eq)]
+#[reflect(Component)]
+/// name of the scene (inside the blueprint's gltf file) to spawn, for gltf files containing multiple scenes
+/// (without it, the first named scene is used)
+pub struct BlueprintScene(pub String);
+
+#[derive(Component, Reflect, Default, Debug, Clone, PartialEq, Eq)]
+#[reflect(Component)]
+/// variant of a blueprint to spawn ("Intact", "Damaged", "Destroyed" ...), each variant being a named scene inside the blueprint's gltf file
+/// * takes precedence over `BlueprintScene`
+/// * changing it on a spawned instance swaps the scene, while keeping the instance entity, its components & original children
+pub struct BlueprintVariant(pub String);
+
+#[derive(Component, Reflect, Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[reflect(Component)]
+/// priority of a blueprint instance in the spawn queue: higher priorities get spawned first when there is a per frame spawn budget
+/// (instances without this component have a priority of 0)
+pub struct SpawnPriority(pub i32);
+
 #[derive(Component)]
 /// helper component, just to transfer child data
 pub(crate) struct OriginalChildren(pub Vec<Entity>);
 
+/// name of the blueprint the instance was last requested to spawn with, to detect changes of its `BlueprintName`
+#[derive(Component, Debug)]
+pub(crate) struct RequestedBlueprint(pub String);
+
 /// helper component, is used to store the list of sub blueprints to enable automatic loading of dependend blueprints
 #[derive(Component, Reflect, Default, Debug)]
 #[reflect(Component)]
@@ -54,10 +127,15 @@ pub struct BlueprintsList(pub HashMap<String, Vec<String>>);
 /// helper component, for tracking loaded assets's loading state, id , handle etc
 #[derive(Default, Debug)]
 pub(crate) struct AssetLoadTracker<T: bevy::prelude::Asset> {
-    #[allow(dead_code)]
     pub name: String,
+    pub kind: BlueprintAssetKind,
     pub id: AssetId<T>,
     pub loaded: bool,
+    pub failed: bool,
+    /// amount of times loading the asset was retried after a failure
+    pub retries: u32,
+    /// when to retry loading the asset next, after a failure
+    pub retry_at: Option<Instant>,
     #[allow(dead_code)]
     pub handle: Handle<T>,
 }
@@ -68,6 +146,8 @@ pub(crate) struct AssetsToLoad<T: bevy::prelude::Asset> {
     pub all_loaded: bool,
     pub asset_infos: Vec<AssetLoadTracker<T>>,
     pub progress: f32,
+    pub started_at: Instant,
+    pub timed_out: bool,
 }
 impl<T: bevy::prelude::Asset> Default for AssetsToLoad<T> {
     fn default() -> Self {
@@ -75,10 +155,35 @@ impl<T: bevy::prelude::Asset> Default for AssetsToLoad<T> {
             all_loaded: Default::default(),
             asset_infos: Default::default(),
             progress: Default::default(),
+            started_at: Instant::now(),
+            timed_out: false,
         }
     }
 }
 
+#[derive(Component, Reflect, Default, Debug, Clone, Copy, PartialEq)]
+#[reflect(Component)]
+/// per instance override of the `asset_load_timeout` of the plugin
+pub struct AssetLoadTimeout(pub Duration);
+
+#[derive(Component, Reflect, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[reflect(Component)]
+/// the current stage of spawning of a blueprint instance, maintained alongside the internal flag components,
+/// so that tooling & user code can easily inspect where an instance is at
+pub enum BlueprintSpawnState {
+    /// the assets needed by the blueprint are being loaded
+    #[default]
+    LoadingAssets,
+    /// the assets are loaded, and the blueprint's scene is being spawned
+    SpawningScene,
+    /// the scene is spawned, and the instance is being post processed
+    PostProcessing,
+    /// the instance is fully spawned
+    Ready,
+    /// spawning failed, see [`BlueprintSpawnFailed`]
+    Failed,
+}
+
 /// flag component, usually added when a blueprint is loaded
 #[derive(Component)]
 pub(crate) struct BlueprintAssetsLoaded;
@@ -104,6 +209,8 @@ pub(crate) fn prepare_blueprints(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     blueprints_config: Res<BluePrintsConfig>,
+    mut blueprints_cache: ResMut<BlueprintAssetsCache>,
+    mut blueprint_events: EventWriter<BlueprintEvent>,
 ) {
     for (entity, blupeprint_name, original_parent, library_override, name, blueprints_list) in
         spawn_placeholders.iter()
@@ -112,30 +219,48 @@ pub(crate) fn prepare_blueprints(
             "requesting to spawn {:?} for entity {:?}, id: {:?}, parent:{:?}",
             blupeprint_name.0, name, entity, original_parent
         );
+        commands.entity(entity).insert((
+            BlueprintSpawnState::LoadingAssets,
+            RequestedBlueprint(blupeprint_name.0.clone()),
+        ));
 
         // println!("main model path {:?}", model_path);
         if blueprints_list.is_some() {
             let blueprints_list = blueprints_list.unwrap();
             // println!("blueprints list {:?}", blueprints_list.0.keys());
             let mut asset_infos: Vec<AssetLoadTracker<Gltf>> = vec![];
+            let mut instance_assets: Vec<BlueprintAssetInfo> = vec![];
             let library_path =
                 library_override.map_or_else(|| &blueprints_config.library_folder, |l| &l.0);
             for (blueprint_name, _) in blueprints_list.0.iter() {
                 let model_file_name = format!("{}.{}", &blueprint_name, &blueprints_config.format);
                 let model_path = Path::new(&library_path).join(Path::new(model_file_name.as_str()));
 
-                let model_handle: Handle<Gltf> = asset_server.load(model_path.clone());
+                let model_handle = blueprints_cache.gltf(&model_path, &asset_server);
                 let model_id = model_handle.id();
                 let loaded = asset_server.is_loaded_with_dependencies(model_id);
+                let kind = BlueprintAssetKind::from_path(&model_path);
+                instance_assets.push(BlueprintAssetInfo {
+                    path: model_path.to_string_lossy().into(),
+                    kind,
+                    handle: model_handle.clone().untyped(),
+                });
                 if !loaded {
                     asset_infos.push(AssetLoadTracker {
                         name: model_path.to_string_lossy().into(),
+                        kind,
                         id: model_id,
                         loaded: false,
+                        failed: false,
+                        retries: 0,
+                        retry_at: None,
                         handle: model_handle.clone(),
                     });
                 }
             }
+            commands
+                .entity(entity)
+                .insert(BlueprintInstanceAssets(instance_assets.clone()));
             // if not all assets are already loaded, inject a component to signal that we need them to be loaded
             if !asset_infos.is_empty() {
                 commands
@@ -148,31 +273,108 @@ pub(crate) fn prepare_blueprints(
                     .insert(BlueprintAssetsNotLoaded);
             } else {
                 commands.entity(entity).insert(BlueprintAssetsLoaded);
+                blueprint_events.send(BlueprintEvent::AssetsLoaded {
+                    entity,
+                    blueprint_name: blupeprint_name.0.clone(),
+                    assets: instance_assets,
+                });
             }
         } else {
             // in case there are no blueprintsList, we revert back to the old behaviour
             commands.entity(entity).insert(BlueprintAssetsLoaded);
+            blueprint_events.send(BlueprintEvent::AssetsLoaded {
+                entity,
+                blueprint_name: blupeprint_name.0.clone(),
+                assets: vec![],
+            });
         }
     }
 }
 
 pub(crate) fn check_for_loaded(
     mut blueprint_assets_to_load: Query<
-        (Entity, &mut AssetsToLoad<Gltf>),
+        (
+            Entity,
+            &BlueprintName,
+            &mut AssetsToLoad<Gltf>,
+            Option<&BlueprintInstanceAssets>,
+            Option<&AssetLoadTimeout>,
+        ),
         With<BlueprintAssetsNotLoaded>,
     >,
     asset_server: Res<AssetServer>,
+    blueprints_config: Res<BluePrintsConfig>,
     mut commands: Commands,
+    mut blueprint_events: EventWriter<BlueprintEvent>,
 ) {
-    for (entity, mut assets_to_load) in blueprint_assets_to_load.iter_mut() {
+    // many instances usually share the same assets: only query the state of each asset once
+    let mut load_states: HashMap<AssetId<Gltf>, (bool, bool)> = HashMap::new();
+    let mut reloaded: HashSet<AssetId<Gltf>> = HashSet::new();
+    for (entity, blueprint_name, mut assets_to_load, instance_assets, load_timeout) in
+        blueprint_assets_to_load.iter_mut()
+    {
         let mut all_loaded = true;
         let mut loaded_amount = 0;
+        let mut failed_asset: Option<String> = None;
         let total = assets_to_load.asset_infos.len();
         for tracker in assets_to_load.asset_infos.iter_mut() {
             let asset_id = tracker.id;
-            let loaded = asset_server.is_loaded_with_dependencies(asset_id);
+            let (loaded, failed) = *load_states.entry(asset_id).or_insert_with(|| {
+                (
+                    asset_server.is_loaded_with_dependencies(asset_id),
+                    asset_server.get_recursive_dependency_load_state(asset_id)
+                        == Some(RecursiveDependencyLoadState::Failed),
+                )
+            });
             tracker.loaded = loaded;
-            if loaded {
+
+            if failed && !tracker.failed {
+                // transient failures (network asset sources, file locks during export...) get retried first
+                if let Some(retry_policy) = blueprints_config
+                    .asset_retry
+                    .filter(|retry_policy| tracker.retries < retry_policy.attempts)
+                {
+                    let retry_at = *tracker.retry_at.get_or_insert_with(|| {
+                        Instant::now() + retry_policy.backoff * (tracker.retries + 1)
+                    });
+                    if Instant::now() >= retry_at {
+                        tracker.retries += 1;
+                        tracker.retry_at = None;
+                        if reloaded.insert(asset_id) {
+                            asset_server.reload(tracker.name.clone());
+                        }
+                        blueprint_events.send(BlueprintEvent::AssetLoadRetry {
+                            entity,
+                            blueprint_name: blueprint_name.0.clone(),
+                            asset_path: tracker.name.clone(),
+                            attempt: tracker.retries,
+                        });
+                    }
+                    all_loaded = false;
+                    continue;
+                }
+                match blueprints_config.strictness {
+                    Strictness::Panic => panic!(
+                        "failed to load asset {} of blueprint {} for entity {:?}",
+                        tracker.name, blueprint_name.0, entity
+                    ),
+                    Strictness::SkipAndWarn => warn!(
+                        "failed to load asset {} of blueprint {} for entity {:?}, skipping it",
+                        tracker.name, blueprint_name.0, entity
+                    ),
+                    Strictness::FailInstance => failed_asset = Some(tracker.name.clone()),
+                }
+                blueprint_events.send(BlueprintEvent::AssetLoadFailed {
+                    entity,
+                    blueprint_name: blueprint_name.0.clone(),
+                    asset_path: tracker.name.clone(),
+                });
+            }
+            tracker.failed = failed;
+
+            // in lenient mode, assets that failed to load do not hold back the spawning
+            let skipped = failed && blueprints_config.strictness == Strictness::SkipAndWarn;
+            if loaded || skipped {
                 loaded_amount += 1;
             } else {
                 all_loaded = false;
@@ -182,12 +384,65 @@ pub(crate) fn check_for_loaded(
         // println!("progress: {}",progress);
         assets_to_load.progress = progress;
 
+        if let Some(path) = failed_asset {
+            fail_blueprint_spawn(
+                &mut commands,
+                &mut blueprint_events,
+                entity,
+                BlueprintSpawnError::AssetLoadFailed { path: path.into() },
+            );
+            continue;
+        }
+
+        let timeout = load_timeout
+            .map(|timeout| timeout.0)
+            .or(blueprints_config.asset_load_timeout);
+        if let Some(timeout) = timeout {
+            if !all_loaded
+                && !assets_to_load.timed_out
+                && assets_to_load.started_at.elapsed() > timeout
+            {
+                assets_to_load.timed_out = true;
+                let pending_assets: Vec<String> = assets_to_load
+                    .asset_infos
+                    .iter()
+                    .filter(|tracker| !tracker.loaded)
+                    .map(|tracker| tracker.name.clone())
+                    .collect();
+                warn!(
+                    "assets of blueprint {} for entity {:?} are still not loaded after {:?}: {:?}",
+                    blueprint_name.0, entity, timeout, pending_assets
+                );
+                blueprint_events.send(BlueprintEvent::LoadTimeout {
+                    entity,
+                    blueprint_name: blueprint_name.0.clone(),
+                    pending_assets,
+                });
+                if blueprints_config.fail_on_asset_load_timeout {
+                    fail_blueprint_spawn(
+                        &mut commands,
+                        &mut blueprint_events,
+                        entity,
+                        BlueprintSpawnError::AssetLoadTimeout { timeout },
+                    );
+                    continue;
+                }
+            }
+        }
+
         if all_loaded {
             assets_to_load.all_loaded = true;
             commands
                 .entity(entity)
                 .insert(BlueprintAssetsLoaded)
                 .remove::<BlueprintAssetsNotLoaded>();
+            blueprint_events.send(BlueprintEvent::AssetsLoaded {
+                entity,
+                blueprint_name: blueprint_name.0.clone(),
+                assets: instance_assets
+                    .map(|instance_assets| instance_assets.0.clone())
+                    .unwrap_or_default(),
+            });
         }
     }
 }
@@ -201,24 +456,48 @@ pub(crate) fn spawn_from_blueprints(
             Option<&Parent>,
             Option<&Library>,
             Option<&AddToGameWorld>,
+            Option<&HideUntilReady>,
             Option<&Name>,
+            Option<&BlueprintScene>,
+            Option<&BlueprintVariant>,
+            Option<&TargetGameWorld>,
+            Option<&SpawnParent>,
+            Option<&SpawnAtMarker>,
+            Option<&SpawnPriority>,
         ),
         (
             With<BlueprintAssetsLoaded>,
-            Added<BlueprintAssetsLoaded>,
             Without<BlueprintAssetsNotLoaded>,
+            Without<Spawned>,
+            // instances whose scene was spawned & transfered, waiting for their components to be injected & to be finalized
+            Without<AwaitingComponentInjection>,
+            Without<BlueprintReadyForFinalizing>,
         ),
     >,
 
     mut commands: Commands,
-    mut game_world: Query<Entity, With<GameWorldTag>>,
+    game_worlds: Query<(Entity, Option<&GameWorldName>), With<GameWorldTag>>,
 
     assets_gltf: Res<Assets<Gltf>>,
     asset_server: Res<AssetServer>,
     blueprints_config: Res<BluePrintsConfig>,
+    mut blueprints_cache: ResMut<BlueprintAssetsCache>,
 
     children: Query<&Children>,
+    all_parents: Query<&Parent>,
+    blueprint_instances: Query<(&BlueprintName, Option<&Library>)>,
+    named_entities: Query<(&Name, &GlobalTransform)>,
+    global_transforms: Query<&GlobalTransform>,
+    mut blueprint_events: EventWriter<BlueprintEvent>,
 ) {
+    // blueprints whose assets are loaded act as a queue: anything over the per frame budget gets spawned in the next frames
+    let max_spawns = blueprints_config
+        .max_blueprint_spawns_per_frame
+        .unwrap_or(usize::MAX);
+    let mut spawn_queue: Vec<_> = spawn_placeholders.iter().collect();
+    spawn_queue.sort_by_key(|(.., priority)| std::cmp::Reverse(priority.map_or(0, |p| p.0)));
+
+    let start = Instant::now();
     for (
         entity,
         blupeprint_name,
@@ -226,46 +505,194 @@ pub(crate) fn spawn_from_blueprints(
         original_parent,
         library_override,
         add_to_world,
+        hide_until_ready,
         name,
-    ) in spawn_placeholders.iter()
+        blueprint_scene,
+        blueprint_variant,
+        target_world,
+        spawn_parent,
+        spawn_at_marker,
+        _,
+    ) in spawn_queue.into_iter().take(max_spawns)
     {
+        if let Some(time_budget) = blueprints_config.max_blueprint_spawn_time_per_frame {
+            if start.elapsed() > time_budget {
+                break;
+            }
+        }
         debug!(
             "attempting to spawn {:?} for entity {:?}, id: {:?}, parent:{:?}",
             blupeprint_name.0, name, entity, original_parent
         );
 
-        let what = &blupeprint_name.0;
-        let model_file_name = format!("{}.{}", &what, &blueprints_config.format);
-
         // library path is either defined at the plugin level or overriden by optional Library components
-        let library_path =
-            library_override.map_or_else(|| &blueprints_config.library_folder, |l| &l.0);
-        let model_path = Path::new(&library_path).join(Path::new(model_file_name.as_str()));
+        let model_path = blueprints_config.blueprint_path(&blupeprint_name.0, library_override);
+
+        // guard against blueprints containing themselves (directly or not), that would otherwise spawn forever
+        let mut chain: Vec<PathBuf> = all_parents
+            .iter_ancestors(entity)
+            .filter_map(|ancestor| blueprint_instances.get(ancestor).ok())
+            .map(|(ancestor_name, ancestor_library)| {
+                blueprints_config.blueprint_path(&ancestor_name.0, ancestor_library)
+            })
+            .collect();
+        chain.reverse();
+        if chain.contains(&model_path) {
+            chain.push(model_path);
+            fail_blueprint_spawn(
+                &mut commands,
+                &mut blueprint_events,
+                entity,
+                BlueprintSpawnError::BlueprintCycle { chain },
+            );
+            continue;
+        }
+        if let Some(max_depth) = blueprints_config.max_blueprint_nesting_depth {
+            if chain.len() >= max_depth {
+                fail_blueprint_spawn(
+                    &mut commands,
+                    &mut blueprint_events,
+                    entity,
+                    BlueprintSpawnError::NestingTooDeep {
+                        path: model_path,
+                        max_depth,
+                    },
+                );
+                continue;
+            }
+        }
 
         // info!("attempting to spawn {:?}", model_path);
-        let model_handle: Handle<Gltf> = asset_server.load(model_path.clone()); // FIXME: kinda weird now
-
-        let gltf = assets_gltf.get(&model_handle).unwrap_or_else(|| {
-            panic!(
-                "gltf file {:?} should have been loaded",
-                model_path.to_str()
-            )
-        });
-
-        // WARNING we work under the assumtion that there is ONLY ONE named scene, and that the first one is the right one
-        let main_scene_name = gltf
-            .named_scenes
-            .keys()
-            .next()
-            .expect("there should be at least one named scene in the gltf file to spawn");
-
-        let scene = &gltf.named_scenes[main_scene_name];
+        let model_handle = blueprints_cache.gltf(&model_path, &asset_server);
+
+        let Some(gltf) = assets_gltf.get(&model_handle) else {
+            fail_blueprint_spawn(
+                &mut commands,
+                &mut blueprint_events,
+                entity,
+                BlueprintSpawnError::GltfNotLoaded { path: model_path },
+            );
+            continue;
+        };
+
+        let requested_scene = blueprint_variant
+            .map(|variant| &variant.0)
+            .or(blueprint_scene.map(|scene| &scene.0));
+        let scene = if let Some(scene_name) = requested_scene {
+            let Some(scene) = gltf.named_scenes.get(scene_name) else {
+                fail_blueprint_spawn(
+                    &mut commands,
+                    &mut blueprint_events,
+                    entity,
+                    BlueprintSpawnError::SceneNotFound {
+                        path: model_path,
+                        scene_name: scene_name.clone(),
+                    },
+                );
+                continue;
+            };
+            scene
+        } else {
+            // WARNING without a BlueprintScene component, we work under the assumtion that there is ONLY ONE named scene, and that the first one is the right one
+            let Some(main_scene_name) = gltf.named_scenes.keys().next() else {
+                fail_blueprint_spawn(
+                    &mut commands,
+                    &mut blueprint_events,
+                    entity,
+                    BlueprintSpawnError::NoNamedScene { path: model_path },
+                );
+                continue;
+            };
+            &gltf.named_scenes[main_scene_name]
+        };
+
+        let mut new_parent = None;
+        if let Some(spawn_parent) = spawn_parent {
+            if commands.get_entity(spawn_parent.0).is_none() {
+                fail_blueprint_spawn(
+                    &mut commands,
+                    &mut blueprint_events,
+                    entity,
+                    BlueprintSpawnError::ParentNotFound {
+                        parent: spawn_parent.0,
+                    },
+                );
+                continue;
+            }
+            new_parent = Some(spawn_parent.0);
+        } else if add_to_world.is_some() {
+            let world_entity = match target_world {
+                Some(target_world) => game_worlds
+                    .iter()
+                    .find(|(_, world_name)| world_name.is_some_and(|n| n.0 == target_world.0))
+                    .map(|(world_entity, _)| world_entity)
+                    .ok_or_else(|| BlueprintSpawnError::GameWorldNotFound {
+                        name: target_world.0.clone(),
+                    }),
+                None => game_worlds
+                    .get_single()
+                    .map(|(world_entity, _)| world_entity)
+                    .map_err(|_| BlueprintSpawnError::GameWorldUnavailable),
+            };
+            match world_entity {
+                Ok(world_entity) => new_parent = Some(world_entity),
+                Err(error) => {
+                    fail_blueprint_spawn(&mut commands, &mut blueprint_events, entity, error);
+                    continue;
+                }
+            }
+        }
 
         // transforms are optional, but still deal with them correctly
         let mut transforms: Transform = Transform::default();
         if transform.is_some() {
             transforms = *transform.unwrap();
         }
+        if let Some(marker) = spawn_at_marker {
+            // markers are looked up in the game world the instance (or its future parent) belongs to,
+            // or in the only game world if it is not inside one
+            let marker_world = new_parent
+                .or(original_parent.map(|parent| parent.get()))
+                .and_then(|parent| {
+                    std::iter::once(parent)
+                        .chain(all_parents.iter_ancestors(parent))
+                        .find(|ancestor| game_worlds.contains(*ancestor))
+                })
+                .or_else(|| {
+                    game_worlds
+                        .get_single()
+                        .ok()
+                        .map(|(world_entity, _)| world_entity)
+                });
+            let is_marker =
+                |(marker_name, _): &(&Name, &GlobalTransform)| marker_name.as_str() == marker.0;
+            let marker_transform = match marker_world {
+                Some(marker_world) => children
+                    .iter_descendants(marker_world)
+                    .filter_map(|descendant| named_entities.get(descendant).ok())
+                    .find(is_marker),
+                None => named_entities.iter().find(is_marker),
+            };
+            let Some((_, marker_transform)) = marker_transform else {
+                fail_blueprint_spawn(
+                    &mut commands,
+                    &mut blueprint_events,
+                    entity,
+                    BlueprintSpawnError::MarkerNotFound {
+                        marker: marker.0.clone(),
+                    },
+                );
+                continue;
+            };
+            // the transform of the instance is relative to its (future) parent
+            let parent_transform = new_parent
+                .or(original_parent.map(|parent| parent.get()))
+                .and_then(|parent| global_transforms.get(parent).ok());
+            transforms = match parent_transform {
+                Some(parent_transform) => marker_transform.reparented_to(parent_transform),
+                None => marker_transform.compute_transform(),
+            };
+        }
 
         let mut original_children: Vec<Entity> = vec![];
         if let Ok(c) = children.get(entity) {
@@ -277,6 +704,11 @@ pub(crate) fn spawn_from_blueprints(
             SceneBundle {
                 scene: scene.clone(),
                 transform: transforms,
+                visibility: if hide_until_ready.is_some() {
+                    Visibility::Hidden
+                } else {
+                    Visibility::Inherited
+                },
                 ..Default::default()
             },
             Animations {
@@ -284,13 +716,118 @@ pub(crate) fn spawn_from_blueprints(
             },
             Spawned,
             OriginalChildren(original_children),
+            BlueprintSpawnState::SpawningScene,
         ));
 
-        if add_to_world.is_some() {
-            let world = game_world
-                .get_single_mut()
-                .expect("there should be a game world present");
-            commands.entity(world).add_child(entity);
+        if let Some(new_parent) = new_parent {
+            commands.entity(new_parent).add_child(entity);
         }
     }
 }
+
+/// if a fallback blueprint is configured, replaces blueprints that failed to spawn with it
+/// * the fallback itself is never replaced, to avoid endlessly retrying a broken fallback
+pub(crate) fn spawn_fallback_blueprints(
+    failed_blueprints: Query<
+        (Entity, &BlueprintName, &BlueprintSpawnFailed),
+        Added<BlueprintSpawnFailed>,
+    >,
+    blueprints_config: Res<BluePrintsConfig>,
+    mut commands: Commands,
+    mut blueprint_events: EventWriter<BlueprintEvent>,
+) {
+    let Some(fallback) = &blueprints_config.fallback_blueprint else {
+        return;
+    };
+    for (entity, blueprint_name, failed) in failed_blueprints.iter() {
+        if &blueprint_name.0 == fallback {
+            continue;
+        }
+        warn!(
+            "spawning fallback blueprint {} instead of {} for entity {:?}",
+            fallback, blueprint_name.0, entity
+        );
+        commands
+            .entity(entity)
+            .remove::<(BlueprintSpawnFailed, BlueprintName, Library, BlueprintsList)>()
+            .insert((
+                BlueprintName(fallback.clone()),
+                // so that the fallback gets loaded before spawning, like any other blueprint
+                BlueprintsList(HashMap::from([(fallback.clone(), vec![])])),
+                SpawnHere,
+            ));
+        blueprint_events.send(BlueprintEvent::FallbackSpawned {
+            entity,
+            blueprint_name: blueprint_name.0.clone(),
+            error: failed.0.clone(),
+        });
+    }
+}
+
+/// in `Strictness::FailInstance` mode, blueprint instances whose scene contains components that could not be parsed are marked as failed
+pub(crate) fn fail_instances_with_invalid_components(
+    mut component_errors: EventReader<GltfComponentError>,
+    spawning_instances: Query<(Option<&Children>, Option<&OriginalChildren>), With<SpawnHere>>,
+    all_parents: Query<&Parent>,
+    blueprints_config: Res<BluePrintsConfig>,
+    mut commands: Commands,
+    mut blueprint_events: EventWriter<BlueprintEvent>,
+) {
+    if blueprints_config.strictness != Strictness::FailInstance {
+        component_errors.clear();
+        return;
+    }
+    let mut failed_instances: HashSet<Entity> = HashSet::new();
+    for component_error in component_errors.read() {
+        // the components belong to the closest instance whose scene contains the entity
+        let Some(instance) = all_parents
+            .iter_ancestors(component_error.entity)
+            .find(|ancestor| spawning_instances.contains(*ancestor))
+        else {
+            continue;
+        };
+        if !failed_instances.insert(instance) {
+            continue;
+        }
+        let Ok((children, original_children)) = spawning_instances.get(instance) else {
+            continue;
+        };
+        teardown_blueprint_instance(&mut commands, instance, children, original_children);
+        fail_blueprint_spawn(
+            &mut commands,
+            &mut blueprint_events,
+            instance,
+            BlueprintSpawnError::InvalidComponent {
+                object_name: component_error.object_name.clone(),
+                component_type: component_error.error.component_type.clone(),
+                error: component_error.error.error.clone(),
+            },
+        );
+    }
+}
+
+/// tags the entity as failed & notifies the rest of the app, instead of panicking
+pub(crate) fn fail_blueprint_spawn(
+    commands: &mut Commands,
+    blueprint_events: &mut EventWriter<BlueprintEvent>,
+    entity: Entity,
+    error: BlueprintSpawnError,
+) {
+    error!(
+        "failed to spawn blueprint for entity {:?}: {}",
+        entity, error
+    );
+    commands
+        .entity(entity)
+        .insert((
+            BlueprintSpawnFailed(error.clone()),
+            BlueprintSpawnState::Failed,
+        ))
+        .remove::<(
+            SpawnHere,
+            BlueprintAssetsLoaded,
+            BlueprintAssetsNotLoaded,
+            AssetsToLoad<Gltf>,
+        )>();
+    blueprint_events.send(BlueprintEvent::SpawnFailed { entity, error });
+}
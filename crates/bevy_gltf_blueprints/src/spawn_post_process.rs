@@ -7,75 +7,148 @@ use bevy::scene::SceneInstance;
 
 use super::{AnimationPlayerLink, Animations};
 use super::{SpawnHere, Spawned};
+use crate::{fail_blueprint_spawn, teardown_blueprint_instance};
 use crate::{
-    AssetsToLoad, BlueprintAssetsLoaded, CopyComponents, InBlueprint, NoInBlueprint,
-    OriginalChildren,
+    AssetsToLoad, BluePrintsConfig, BlueprintAssetsLoaded, BlueprintConstructors, BlueprintEvent,
+    BlueprintName, BlueprintPostProcessors, BlueprintSpawnError, BlueprintSpawnState,
+    ComponentAssetsToLoad, CopyComponents, HideUntilReady, InBlueprint, KeepBlueprintRoot,
+    NoInBlueprint, OriginalChildren, PendingPostProcessors, ReadyGate,
 };
 
+/// flag component, added once the components & children of a blueprint instance have been transfered:
+/// the components of their gltf extras only get injected in the next `GltfComponentsSet::Injection`
+#[derive(Component)]
+pub(crate) struct AwaitingComponentInjection;
+
+/// flag component, added once the components of a blueprint instance have been injected,
+/// and removed once the instance is finalized
+#[derive(Component)]
+pub(crate) struct BlueprintReadyForFinalizing;
+
+/// how many frames the component transfer of a blueprint instance was retried, because its scene had no root node yet
+#[derive(Component, Debug, Default)]
+pub(crate) struct ComponentTransferRetries(pub(crate) u32);
+
 /// this system is in charge of doing any necessary post processing after a blueprint scene has been spawned
 /// - it removes one level of useless nesting
 /// - it copies the blueprint's root components to the entity it was spawned on (original entity)
 /// - it copies the children of the blueprint scene into the original entity
 /// - it add `AnimationLink` components so that animations can be controlled from the original entity
-/// - it cleans up/ removes a few , by then uneeded components
-pub(crate) fn spawned_blueprint_post_process(
-    unprocessed_entities: Query<
+///
+/// for instances with `KeepBlueprintRoot` (or if `keep_blueprint_roots` is enabled), the nesting is kept:
+/// the root node simply stays a child of the original entity, and its components are not transfered
+pub(crate) fn transfer_blueprint_components(
+    mut unprocessed_entities: Query<
         (
             Entity,
-            &Children,
+            Option<&Children>,
             &OriginalChildren,
             &Animations,
             Option<&NoInBlueprint>,
             Option<&Name>,
+            Option<&mut BlueprintSpawnState>,
+            Option<&KeepBlueprintRoot>,
+            Option<&mut ComponentTransferRetries>,
         ),
         (With<SpawnHere>, With<SceneInstance>, With<Spawned>),
     >,
     added_animation_players: Query<(Entity, &Parent), Added<AnimationPlayer>>,
     all_children: Query<&Children>,
+    blueprints_config: Res<BluePrintsConfig>,
 
     mut commands: Commands,
+    mut blueprint_events: EventWriter<BlueprintEvent>,
 ) {
-    for (original, children, original_children, animations, no_inblueprint, name) in
-        unprocessed_entities.iter()
+    for (
+        original,
+        children,
+        original_children,
+        animations,
+        no_inblueprint,
+        name,
+        spawn_state,
+        keep_root,
+        retries,
+    ) in unprocessed_entities.iter_mut()
     {
         debug!("post processing blueprint for entity {:?}", name);
-
-        if children.len() == 0 {
-            warn!("timing issue ! no children found, please restart your bevy app (bug being investigated)");
-            continue;
+        if let Some(mut spawn_state) = spawn_state {
+            spawn_state.set_if_neq(BlueprintSpawnState::PostProcessing);
         }
+
         // the root node is the first & normally only child inside a scene, it is the one that has all relevant components
-        let mut root_entity = Entity::PLACEHOLDER; //FIXME: and what about childless ones ?? => should not be possible normally
-                                                   // let diff = HashSet::from_iter(original_children.0).difference(HashSet::from_iter(children));
-                                                   // we find the first child that was not in the entity before (aka added during the scene spawning)
-        for c in children.iter() {
-            if !original_children.0.contains(c) {
-                root_entity = *c;
-                break;
+        // we find the first child that was not in the entity before (aka added during the scene spawning)
+        // (no children at all means the scene's hierarchy is not there yet either)
+        let Some(root_entity) = children
+            .into_iter()
+            .flatten()
+            .find(|c| !original_children.0.contains(*c))
+            .copied()
+        else {
+            // timing issue: the scene's hierarchy is not there yet, we retry next frame (up to a limit)
+            let retry_count = retries.as_ref().map_or(0, |r| r.0) + 1;
+            if retry_count > blueprints_config.max_component_transfer_retries {
+                teardown_blueprint_instance(
+                    &mut commands,
+                    original,
+                    children,
+                    Some(original_children),
+                );
+                commands
+                    .entity(original)
+                    .remove::<ComponentTransferRetries>();
+                fail_blueprint_spawn(
+                    &mut commands,
+                    &mut blueprint_events,
+                    original,
+                    BlueprintSpawnError::NoSceneRoot {
+                        retries: retry_count - 1,
+                    },
+                );
+            } else {
+                debug!(
+                    "no root node found yet for blueprint instance {:?}, retrying next frame ({}/{})",
+                    original, retry_count, blueprints_config.max_component_transfer_retries
+                );
+                match retries {
+                    Some(mut retries) => retries.0 = retry_count,
+                    None => {
+                        commands
+                            .entity(original)
+                            .insert(ComponentTransferRetries(retry_count));
+                    }
+                }
             }
-        }
+            continue;
+        };
 
         // we flag all children of the blueprint instance with 'InBlueprint'
         // can be usefull to filter out anything that came from blueprints vs normal children
+        let keep_root = keep_root.is_some() || blueprints_config.keep_blueprint_roots;
         if no_inblueprint.is_none() {
+            if keep_root {
+                commands.entity(root_entity).insert(InBlueprint);
+            }
             for child in all_children.iter_descendants(root_entity) {
                 commands.entity(child).insert(InBlueprint);
             }
         }
 
-        // copy components into from blueprint instance's root_entity to original entity
-        commands.add(CopyComponents {
-            source: root_entity,
-            destination: original,
-            exclude: vec![TypeId::of::<Parent>(), TypeId::of::<Children>()],
-            stringent: false,
-        });
+        if !keep_root {
+            // copy components into from blueprint instance's root_entity to original entity
+            commands.add(CopyComponents {
+                source: root_entity,
+                destination: original,
+                exclude: vec![TypeId::of::<Parent>(), TypeId::of::<Children>()],
+                stringent: false,
+            });
 
-        // we move all of children of the blueprint instance one level to the original entity
-        if let Ok(root_entity_children) = all_children.get(root_entity) {
-            for child in root_entity_children.iter() {
-                // info!("copying child {:?} upward from {:?} to {:?}", names.get(*child), root_entity, original);
-                commands.entity(original).add_child(*child);
+            // we move all of children of the blueprint instance one level to the original entity
+            if let Ok(root_entity_children) = all_children.get(root_entity) {
+                for child in root_entity_children.iter() {
+                    // info!("copying child {:?} upward from {:?} to {:?}", names.get(*child), root_entity, original);
+                    commands.entity(original).add_child(*child);
+                }
             }
         }
 
@@ -90,11 +163,117 @@ pub(crate) fn spawned_blueprint_post_process(
             }
         }
 
+        commands
+            .entity(original)
+            .remove::<(Spawned, ComponentTransferRetries)>();
+        commands.entity(original).insert(AwaitingComponentInjection);
+        if !keep_root {
+            commands.entity(root_entity).despawn_recursive();
+        }
+    }
+}
+
+/// once the components of the transfered blueprint instances (& of their children) have been injected,
+/// makes them ready for post processing & finalizing
+pub(crate) fn mark_ready_for_finalizing(
+    injected_entities: Query<Entity, With<AwaitingComponentInjection>>,
+    post_processors: Res<BlueprintPostProcessors>,
+    mut commands: Commands,
+) {
+    for original in injected_entities.iter() {
+        commands
+            .entity(original)
+            .remove::<AwaitingComponentInjection>()
+            .insert(BlueprintReadyForFinalizing);
+        if !post_processors.0.is_empty() {
+            commands.entity(original).insert(PendingPostProcessors(
+                post_processors.0.iter().copied().collect(),
+            ));
+        }
+    }
+}
+
+/// this system finalizes blueprint instances, once their components have been transfered & they have been post processed
+/// (ie all registered post processors have completed, all the conditions of its `ReadyGate` if any, are cleared,
+/// and the assets referenced by its components are loaded)
+/// - it cleans up/ removes a few , by then uneeded components
+/// - it runs the constructors registered for the blueprint (see `BlueprintConstructorAppExt`)
+/// - it reveals instances flagged with `HideUntilReady`
+/// - it sends a `BlueprintEvent::InstanceReady` event (and a `BlueprintEvent::SubInstanceReady` one for blueprints nested inside other blueprints)
+pub(crate) fn finalize_blueprint_instances(
+    unfinalized_entities: Query<
+        (
+            Entity,
+            &BlueprintName,
+            Option<&HideUntilReady>,
+            Option<&PendingPostProcessors>,
+            Option<&ReadyGate>,
+            Option<&ComponentAssetsToLoad>,
+        ),
+        With<BlueprintReadyForFinalizing>,
+    >,
+    all_parents: Query<&Parent>,
+    blueprint_instances: Query<(), With<BlueprintName>>,
+    constructors: Res<BlueprintConstructors>,
+
+    mut commands: Commands,
+    mut blueprint_events: EventWriter<BlueprintEvent>,
+) {
+    for (
+        original,
+        blueprint_name,
+        hide_until_ready,
+        pending_post_processors,
+        ready_gate,
+        component_assets,
+    ) in unfinalized_entities.iter()
+    {
+        if pending_post_processors.is_some_and(|pending| !pending.is_empty()) {
+            continue;
+        }
+        if ready_gate.is_some_and(|gate| !gate.is_open()) {
+            continue;
+        }
+        if component_assets.is_some_and(|assets| !assets.0.is_empty()) {
+            continue;
+        }
+        debug!("finalizing blueprint instance {:?}", original);
+
         commands.entity(original).remove::<SpawnHere>();
-        commands.entity(original).remove::<Spawned>();
         commands.entity(original).remove::<Handle<Scene>>();
         commands.entity(original).remove::<AssetsToLoad<Gltf>>(); // also clear the sub assets tracker to free up handles, perhaps just freeing up the handles and leave the rest would be better ?
         commands.entity(original).remove::<BlueprintAssetsLoaded>();
-        commands.entity(root_entity).despawn_recursive();
+        commands.entity(original).remove::<(
+            BlueprintReadyForFinalizing,
+            PendingPostProcessors,
+            ReadyGate,
+            ComponentAssetsToLoad,
+        )>();
+
+        // constructors run (when the commands are applied) before anything reacts to the instance being ready
+        for constructor in constructors.get(&blueprint_name.0) {
+            commands.run_system_with_input(*constructor, original);
+        }
+
+        if hide_until_ready.is_some() {
+            commands.entity(original).insert(Visibility::Inherited);
+        }
+        commands.entity(original).insert(BlueprintSpawnState::Ready);
+
+        blueprint_events.send(BlueprintEvent::InstanceReady {
+            entity: original,
+            blueprint_name: blueprint_name.0.clone(),
+        });
+        // notify about progressive assembly of nested blueprints
+        if let Some(parent_instance) = all_parents
+            .iter_ancestors(original)
+            .find(|ancestor| blueprint_instances.contains(*ancestor))
+        {
+            blueprint_events.send(BlueprintEvent::SubInstanceReady {
+                parent: parent_instance,
+                child: original,
+                blueprint_name: blueprint_name.0.clone(),
+            });
+        }
     }
 }
@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+
+use crate::{
+    BluePrintsConfig, BlueprintAabbCache, BlueprintName, BlueprintSpawnState, BlueprintVariant,
+    Library,
+};
+
+/// a cheap stand-in shown at the transform of a blueprint instance while its assets load, removed once the instance is ready (or failed to spawn)
+#[derive(Component, Reflect, Default, Debug, Clone, PartialEq, Eq)]
+#[reflect(Component)]
+pub enum SpawnProxy {
+    /// a box with the size of the blueprint (known once an instance of the blueprint & variant has been spawned with `aabbs` enabled in the plugin, a unit cube otherwise)
+    #[default]
+    Aabb,
+    /// a (low poly) mesh, ie "models/library/Tree.glb#Mesh1/Primitive0"
+    Mesh(String),
+}
+
+/// the proxy entity of a blueprint instance, with its offset from the instance
+#[derive(Component, Debug)]
+pub(crate) struct SpawnProxyOf {
+    pub(crate) instance: Entity,
+    pub(crate) offset: Vec3,
+}
+
+fn proxy_transform(instance_transform: &GlobalTransform, offset: Vec3) -> Transform {
+    let mut transform = instance_transform.compute_transform();
+    transform.translation += transform.rotation * (transform.scale * offset);
+    transform
+}
+
+/// flag component for instances whose proxy has been spawned
+#[derive(Component, Debug)]
+pub(crate) struct SpawnProxySpawned;
+
+/// spawns the proxies of blueprint instances that are still loading
+pub(crate) fn spawn_blueprint_proxies(
+    instances: Query<
+        (
+            Entity,
+            &SpawnProxy,
+            &BlueprintName,
+            Option<&Library>,
+            Option<&BlueprintVariant>,
+            &BlueprintSpawnState,
+            &GlobalTransform,
+        ),
+        Without<SpawnProxySpawned>,
+    >,
+    blueprints_config: Res<BluePrintsConfig>,
+    aabb_cache: Res<BlueprintAabbCache>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    // the material shared by all proxies
+    mut proxy_material: Local<Option<Handle<StandardMaterial>>>,
+    mut commands: Commands,
+) {
+    for (entity, proxy, blueprint_name, library, variant, spawn_state, transform) in
+        instances.iter()
+    {
+        if matches!(
+            spawn_state,
+            BlueprintSpawnState::Ready | BlueprintSpawnState::Failed
+        ) {
+            continue;
+        }
+        let (mesh, offset) = match proxy {
+            SpawnProxy::Aabb => {
+                let (size, center) = aabb_cache
+                    .aabbs
+                    .get(&blueprints_config.aabb_cache_key(&blueprint_name.0, library, variant))
+                    .map_or((Vec3::ONE, Vec3::ZERO), |cached| {
+                        (
+                            Vec3::from(cached.aabb.half_extents * 2.0),
+                            Vec3::from(cached.aabb.center),
+                        )
+                    });
+                (meshes.add(Cuboid::from_size(size)), center)
+            }
+            SpawnProxy::Mesh(path) => (asset_server.load(path.clone()), Vec3::ZERO),
+        };
+        commands.spawn((
+            PbrBundle {
+                mesh,
+                material: proxy_material
+                    .get_or_insert_with(|| {
+                        materials.add(StandardMaterial {
+                            base_color: Color::rgba(0.5, 0.5, 0.5, 0.5),
+                            alpha_mode: AlphaMode::Blend,
+                            unlit: true,
+                            ..Default::default()
+                        })
+                    })
+                    .clone(),
+                transform: proxy_transform(transform, offset),
+                ..Default::default()
+            },
+            SpawnProxyOf {
+                instance: entity,
+                offset,
+            },
+            Name::from(format!("{}_proxy", blueprint_name.0)),
+        ));
+        commands.entity(entity).insert(SpawnProxySpawned);
+    }
+}
+
+/// keeps the proxies of blueprint instances in place, and despawns them once the instances are ready, failed to spawn or were despawned
+pub(crate) fn update_blueprint_proxies(
+    mut proxies: Query<(Entity, &SpawnProxyOf, &mut Transform)>,
+    instances: Query<(&BlueprintSpawnState, &GlobalTransform)>,
+    mut commands: Commands,
+) {
+    for (proxy, proxy_of, mut transform) in proxies.iter_mut() {
+        if let Ok((spawn_state, instance_transform)) = instances.get(proxy_of.instance) {
+            if !matches!(
+                spawn_state,
+                BlueprintSpawnState::Ready | BlueprintSpawnState::Failed
+            ) {
+                *transform = proxy_transform(instance_transform, proxy_of.offset);
+                continue;
+            }
+        }
+        commands.entity(proxy).despawn_recursive();
+        if let Some(mut instance_commands) = commands.get_entity(proxy_of.instance) {
+            instance_commands.remove::<SpawnProxySpawned>();
+        }
+    }
+}
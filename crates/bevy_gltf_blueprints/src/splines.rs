@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+
+/// a control point of a spline, with its bezier handles (equal to the position for straight segments)
+#[derive(Reflect, Default, Debug, Clone, Copy, PartialEq)]
+pub struct SplinePoint {
+    pub position: Vec3,
+    pub handle_left: Vec3,
+    pub handle_right: Vec3,
+}
+
+/// a spline of a Blender curve object
+#[derive(Reflect, Default, Debug, Clone, PartialEq)]
+pub struct Spline {
+    pub points: Vec<SplinePoint>,
+    /// the last point connects back to the first one
+    pub cyclic: bool,
+}
+
+impl Spline {
+    /// the bezier curve going through the points of the spline (in the local space of the entity), None if it has less than 2 points
+    pub fn to_curve(&self) -> Option<CubicCurve<Vec3>> {
+        if self.points.len() < 2 {
+            return None;
+        }
+        let mut segments: Vec<[Vec3; 4]> = self
+            .points
+            .windows(2)
+            .map(|pair| {
+                [
+                    pair[0].position,
+                    pair[0].handle_right,
+                    pair[1].handle_left,
+                    pair[1].position,
+                ]
+            })
+            .collect();
+        if self.cyclic {
+            let (first, last) = (self.points[0], self.points[self.points.len() - 1]);
+            segments.push([
+                last.position,
+                last.handle_right,
+                first.handle_left,
+                first.position,
+            ]);
+        }
+        Some(CubicBezier::new(segments).to_curve())
+    }
+}
+
+/// the splines of a curve object exported from Blender (see the `gltf_auto_export` add-on): usable for patrol paths, camera rails, roads etc,
+/// the matching [`SplineCurves`] are added alongside
+#[derive(Component, Reflect, Default, Debug, Clone, PartialEq)]
+#[reflect(Component)]
+pub struct Splines(pub Vec<Spline>);
+
+/// the bevy curves of the [`Splines`] of an entity, in its local space: use `position(t)`, `velocity(t)` or `iter_positions(subdivisions)` to follow them
+#[derive(Component, Debug, Clone)]
+pub struct SplineCurves(pub Vec<CubicCurve<Vec3>>);
+
+/// builds the [`SplineCurves`] of entities with [`Splines`]
+pub(crate) fn build_spline_curves(
+    splines: Query<(Entity, &Splines), Changed<Splines>>,
+    mut commands: Commands,
+) {
+    for (entity, splines) in splines.iter() {
+        let curves = splines.0.iter().filter_map(Spline::to_curve).collect();
+        commands.entity(entity).insert(SplineCurves(curves));
+    }
+}
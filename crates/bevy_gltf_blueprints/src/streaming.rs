@@ -0,0 +1,272 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{DespawnBlueprint, Library, SpawnBlueprintCommandsExt, SpawnPriority};
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+/// flag component for the entities (camera, player...) that blueprint streamers react to
+pub struct BlueprintStreamingTarget;
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+/// streaming volume: the blueprint is spawned (as a child of this entity) when a [`BlueprintStreamingTarget`] comes within `radius`,
+/// and despawned (or hidden) once all targets are further away than `radius + hysteresis`
+/// * the entity needs a transform & visibility (ie a `SpatialBundle`)
+/// * use a [`Library`] component to load the blueprint from another folder than the default library folder
+pub struct BlueprintStreamer {
+    /// name of the blueprint to stream in
+    pub blueprint: String,
+    pub radius: f32,
+    /// extra distance before streaming out, to avoid spawning/despawning repeatedly at the edge of the radius
+    pub hysteresis: f32,
+    /// hide the instance when out of range instead of despawning it
+    pub hide_when_out_of_range: bool,
+}
+
+/// the blueprint instance spawned by a [`BlueprintStreamer`]
+#[derive(Component, Debug)]
+pub struct StreamedInstance(pub Entity);
+
+pub(crate) fn stream_blueprints(
+    streamers: Query<(
+        Entity,
+        &BlueprintStreamer,
+        &GlobalTransform,
+        Option<&StreamedInstance>,
+        Option<&Library>,
+    )>,
+    targets: Query<&GlobalTransform, With<BlueprintStreamingTarget>>,
+    mut visibilities: Query<&mut Visibility>,
+    mut commands: Commands,
+) {
+    for (streamer_entity, streamer, streamer_transform, streamed_instance, library) in
+        streamers.iter()
+    {
+        let streamer_position = streamer_transform.translation();
+        let closest_distance = targets
+            .iter()
+            .map(|target| target.translation().distance(streamer_position))
+            .fold(f32::INFINITY, f32::min);
+
+        match streamed_instance {
+            None if closest_distance <= streamer.radius => {
+                debug!("streaming in blueprint {}", streamer.blueprint);
+                let mut builder = commands
+                    .spawn_blueprint(streamer.blueprint.clone())
+                    .child_of(streamer_entity);
+                if let Some(library) = library {
+                    builder = builder.library(library.0.clone());
+                }
+                let instance = builder.spawn();
+                commands
+                    .entity(streamer_entity)
+                    .insert(StreamedInstance(instance));
+            }
+            Some(streamed_instance) => {
+                let in_range = closest_distance <= streamer.radius + streamer.hysteresis;
+                if streamer.hide_when_out_of_range {
+                    if let Ok(mut visibility) = visibilities.get_mut(streamed_instance.0) {
+                        visibility.set_if_neq(if in_range {
+                            Visibility::Inherited
+                        } else {
+                            Visibility::Hidden
+                        });
+                    }
+                } else if !in_range {
+                    debug!("streaming out blueprint {}", streamer.blueprint);
+                    if let Some(mut instance) = commands.get_entity(streamed_instance.0) {
+                        instance.insert(DespawnBlueprint);
+                    }
+                    commands
+                        .entity(streamer_entity)
+                        .remove::<StreamedInstance>();
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+/// world partition: a level split into multiple blueprints, one per cell of a grid (on the XZ plane)
+/// the cells around the [`BlueprintStreamingTarget`]s are spawned (as children of this entity) and the ones out of range despawned
+/// * the blueprint of each cell is named after `cell_blueprint_pattern`, with `{x}` & `{z}` replaced by the cell coordinates
+///   ie `World_{x}_{z}` => `World_0_0`, `World_-1_2` etc
+/// * the entity needs a transform & visibility (ie a `SpatialBundle`)
+pub struct BlueprintStreamingGrid {
+    pub cell_blueprint_pattern: String,
+    pub cell_size: f32,
+    /// cells whose center is within this distance of a target are loaded
+    pub load_radius: f32,
+    /// extra distance before unloading cells, to avoid loading/unloading repeatedly at the edge of the radius
+    pub hysteresis: f32,
+    /// maximum amount of cells requested per frame, the closest ones first
+    pub max_cell_loads_per_frame: usize,
+}
+
+impl Default for BlueprintStreamingGrid {
+    fn default() -> Self {
+        Self {
+            cell_blueprint_pattern: "World_{x}_{z}".into(),
+            cell_size: 100.0,
+            load_radius: 150.0,
+            hysteresis: 20.0,
+            max_cell_loads_per_frame: 2,
+        }
+    }
+}
+
+impl BlueprintStreamingGrid {
+    /// name of the blueprint of the given cell
+    pub fn cell_blueprint(&self, cell: IVec2) -> String {
+        self.cell_blueprint_pattern
+            .replace("{x}", &cell.x.to_string())
+            .replace("{z}", &cell.y.to_string())
+    }
+
+    /// corner of the given cell, relative to the grid: cell instances are placed there
+    pub fn cell_origin(&self, cell: IVec2) -> Vec3 {
+        Vec3::new(
+            cell.x as f32 * self.cell_size,
+            0.0,
+            cell.y as f32 * self.cell_size,
+        )
+    }
+
+    /// center of the given cell, relative to the grid
+    pub fn cell_center(&self, cell: IVec2) -> Vec3 {
+        Vec3::new(
+            (cell.x as f32 + 0.5) * self.cell_size,
+            0.0,
+            (cell.y as f32 + 0.5) * self.cell_size,
+        )
+    }
+}
+
+/// the cells of a [`BlueprintStreamingGrid`] that are currently loaded (or loading), with their blueprint instance
+#[derive(Component, Debug, Default)]
+pub struct LoadedGridCells(pub HashMap<IVec2, Entity>);
+
+/// events sent when the cells of a [`BlueprintStreamingGrid`] are streamed in & out
+/// (use the `BlueprintEvent::InstanceReady` of the cell instance to know when it is fully spawned)
+#[derive(Event, Debug, Clone)]
+pub enum GridCellEvent {
+    Requested {
+        grid: Entity,
+        cell: IVec2,
+        instance: Entity,
+    },
+    Unloaded {
+        grid: Entity,
+        cell: IVec2,
+    },
+}
+
+pub(crate) fn stream_grid_cells(
+    mut grids: Query<(
+        Entity,
+        &BlueprintStreamingGrid,
+        &GlobalTransform,
+        Option<&mut LoadedGridCells>,
+        Option<&Library>,
+    )>,
+    targets: Query<&GlobalTransform, With<BlueprintStreamingTarget>>,
+    mut commands: Commands,
+    mut cell_events: EventWriter<GridCellEvent>,
+) {
+    for (grid_entity, grid, grid_transform, loaded_cells, library) in grids.iter_mut() {
+        if grid.cell_size <= 0.0 {
+            continue;
+        }
+        let mut new_loaded_cells = LoadedGridCells::default();
+        let loaded_cells = match loaded_cells {
+            Some(loaded_cells) => loaded_cells.into_inner(),
+            None => &mut new_loaded_cells,
+        };
+
+        // positions of the targets, in the grid's space
+        let to_grid = grid_transform.compute_matrix().inverse();
+        let target_positions: Vec<Vec3> = targets
+            .iter()
+            .map(|target| to_grid.transform_point3(target.translation()))
+            .collect();
+        let distance_to_targets = |cell: IVec2| {
+            let center = grid.cell_center(cell);
+            target_positions
+                .iter()
+                .map(|position| Vec2::new(position.x - center.x, position.z - center.z).length())
+                .fold(f32::INFINITY, f32::min)
+        };
+
+        // unload cells that are out of range
+        loaded_cells.0.retain(|cell, instance| {
+            if distance_to_targets(*cell) <= grid.load_radius + grid.hysteresis {
+                return true;
+            }
+            debug!("unloading grid cell {:?}", cell);
+            if let Some(mut instance) = commands.get_entity(*instance) {
+                instance.insert(DespawnBlueprint);
+            }
+            cell_events.send(GridCellEvent::Unloaded {
+                grid: grid_entity,
+                cell: *cell,
+            });
+            false
+        });
+
+        // request the closest missing cells, within the per frame budget
+        let cells_radius = (grid.load_radius / grid.cell_size).ceil() as i32 + 1;
+        let mut missing_cells: Vec<(IVec2, f32)> = vec![];
+        for position in target_positions.iter() {
+            let target_cell = IVec2::new(
+                (position.x / grid.cell_size).floor() as i32,
+                (position.z / grid.cell_size).floor() as i32,
+            );
+            for x in -cells_radius..=cells_radius {
+                for z in -cells_radius..=cells_radius {
+                    let cell = target_cell + IVec2::new(x, z);
+                    if loaded_cells.0.contains_key(&cell)
+                        || missing_cells.iter().any(|(missing, _)| *missing == cell)
+                    {
+                        continue;
+                    }
+                    let distance = distance_to_targets(cell);
+                    if distance <= grid.load_radius {
+                        missing_cells.push((cell, distance));
+                    }
+                }
+            }
+        }
+        missing_cells.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        for (cell, distance) in missing_cells
+            .into_iter()
+            .take(grid.max_cell_loads_per_frame)
+        {
+            debug!("loading grid cell {:?}", cell);
+            let mut builder = commands
+                .spawn_blueprint(grid.cell_blueprint(cell))
+                .at(Transform::from_translation(grid.cell_origin(cell)))
+                .child_of(grid_entity);
+            if let Some(library) = library {
+                builder = builder.library(library.0.clone());
+            }
+            let instance = builder.spawn();
+            // closest cells get spawned first
+            commands
+                .entity(instance)
+                .insert(SpawnPriority(-(distance as i32)));
+            loaded_cells.0.insert(cell, instance);
+            cell_events.send(GridCellEvent::Requested {
+                grid: grid_entity,
+                cell,
+                instance,
+            });
+        }
+
+        if !new_loaded_cells.0.is_empty() {
+            commands.entity(grid_entity).insert(new_loaded_cells);
+        }
+    }
+}
@@ -0,0 +1,742 @@
+use std::path::Path;
+
+use bevy::{
+    gltf::{Gltf, GltfExtras, GltfMesh},
+    hierarchy::WorldChildBuilder,
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    render::{mesh::skinning::SkinnedMeshInverseBindposes, view::RenderLayers},
+    scene::ScenePlugin,
+    utils::HashMap,
+};
+use bevy_gltf_components::GltfComponentError;
+
+use crate::{
+    Animations, AssetRef, BluePrintsConfig, BlueprintConstructorAppExt, BlueprintName,
+    BlueprintOverrides, BlueprintPostProcessorAppExt, BlueprintSpawnError, BlueprintSpawnFailed,
+    BlueprintSpawnState, BlueprintsPlugin, CollisionMesh, CollisionOnly, LightConversion,
+    NamedEntityRef, PendingPostProcessors, ReadyGate, SpawnAtMarker, SpawnHere, Strictness,
+};
+
+/// a component of the blueprints, set in their gltf extras
+#[derive(Component, Reflect, Default, Debug, PartialEq)]
+#[reflect(Component)]
+struct Health {
+    points: f32,
+}
+
+/// a headless app with the blueprints plugin & just enough of bevy to spawn scenes
+fn test_app(plugin: BlueprintsPlugin) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        HierarchyPlugin,
+        TransformPlugin,
+        ScenePlugin,
+    ))
+    .init_asset::<Gltf>()
+    .init_asset::<GltfMesh>()
+    .init_asset::<Mesh>()
+    .init_asset::<StandardMaterial>()
+    .init_asset::<Image>()
+    .init_asset::<AnimationClip>()
+    .init_asset::<SkinnedMeshInverseBindposes>()
+    // registered by the gltf plugin
+    .register_type::<GltfExtras>()
+    .register_type::<Health>()
+    .add_plugins(plugin);
+    app
+}
+
+fn extras(value: &str) -> GltfExtras {
+    GltfExtras {
+        value: value.to_string(),
+    }
+}
+
+/// the scene of a blueprint: a root node named after the blueprint (with the given gltf extras), with a child named "child"
+/// and whatever other children `children` adds
+fn blueprint_scene(
+    name: &str,
+    root_extras: Option<&str>,
+    children: impl FnOnce(&mut WorldChildBuilder),
+) -> World {
+    let mut scene_world = World::new();
+    let mut root = scene_world.spawn((Name::new(name.to_string()), TransformBundle::default()));
+    if let Some(root_extras) = root_extras {
+        root.insert(extras(root_extras));
+    }
+    root.with_children(|parent| {
+        parent.spawn((Name::new("child"), TransformBundle::default()));
+        children(parent);
+    });
+    scene_world
+}
+
+/// adds a blueprint to the app as if its gltf file was loaded, with the given scene as its single named scene (`<blueprint path>#Scene0`)
+/// * keep the returned handle around, the blueprint is only "loaded" as long as it is alive
+fn add_blueprint(app: &mut App, name: &str, scene_world: World) -> Handle<Gltf> {
+    let blueprints_config = app.world.resource::<BluePrintsConfig>();
+    let path = Path::new(&blueprints_config.library_folder)
+        .join(format!("{}.{}", name, blueprints_config.format));
+    let asset_server = app.world.resource::<AssetServer>();
+    let gltf: Handle<Gltf> = asset_server.load(path.clone());
+    let scene: Handle<Scene> = asset_server.load(format!("{}#Scene0", path.display()));
+    app.world
+        .resource_mut::<Assets<Scene>>()
+        .insert(scene.id(), Scene::new(scene_world));
+
+    app.world.resource_mut::<Assets<Gltf>>().insert(
+        gltf.id(),
+        Gltf {
+            scenes: vec![scene.clone()],
+            named_scenes: HashMap::from([("Scene".to_string(), scene.clone())]),
+            meshes: vec![],
+            named_meshes: HashMap::default(),
+            materials: vec![],
+            named_materials: HashMap::default(),
+            nodes: vec![],
+            named_nodes: HashMap::default(),
+            default_scene: Some(scene),
+            animations: vec![],
+            named_animations: HashMap::default(),
+        },
+    );
+    gltf
+}
+
+/// spawns an instance of the blueprint inside a level, like the instances placed in Blender
+fn spawn_instance(app: &mut App, name: &str) -> Entity {
+    let level = app
+        .world
+        .spawn((Name::new("Level"), TransformBundle::default()))
+        .id();
+    app.world
+        .spawn((
+            BlueprintName(name.to_string()),
+            SpawnHere,
+            TransformBundle::default(),
+        ))
+        .set_parent(level)
+        .id()
+}
+
+fn update(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        app.update();
+    }
+}
+
+fn spawn_state(app: &App, entity: Entity) -> Option<BlueprintSpawnState> {
+    app.world.get::<BlueprintSpawnState>(entity).copied()
+}
+
+/// the entities named `name`, anywhere in the world
+fn named(app: &mut App, name: &str) -> Vec<Entity> {
+    app.world
+        .query::<(Entity, &Name)>()
+        .iter(&app.world)
+        .filter(|(_, entity_name)| entity_name.as_str() == name)
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+#[test]
+fn spawns_at_most_the_budget_per_frame() {
+    let mut app = test_app(BlueprintsPlugin {
+        max_blueprint_spawns_per_frame: Some(1),
+        ..Default::default()
+    });
+    let _crate = add_blueprint(&mut app, "Crate", blueprint_scene("Crate", None, |_| {}));
+    let instances: Vec<Entity> = (0..3).map(|_| spawn_instance(&mut app, "Crate")).collect();
+
+    let mut started = 0;
+    for _ in 0..10 {
+        app.update();
+        // the scene of an instance gets requested along with its animations
+        let now_started = instances
+            .iter()
+            .filter(|instance| app.world.get::<Animations>(**instance).is_some())
+            .count();
+        assert!(now_started - started <= 1);
+        started = now_started;
+    }
+    for instance in instances {
+        assert!(app.world.get::<SpawnHere>(instance).is_none());
+    }
+    assert_eq!(named(&mut app, "child").len(), 3);
+}
+
+#[test]
+fn spawns_the_blueprint_scene_once() {
+    let mut app = test_app(BlueprintsPlugin::default());
+    let _crate = add_blueprint(&mut app, "Crate", blueprint_scene("Crate", None, |_| {}));
+    let instance = spawn_instance(&mut app, "Crate");
+
+    update(&mut app, 10);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::Ready)
+    );
+    let children = named(&mut app, "child");
+    assert_eq!(children.len(), 1);
+    assert_eq!(
+        app.world.get::<Parent>(children[0]).map(Parent::get),
+        Some(instance)
+    );
+
+    // once ready, the scene is not spawned again
+    update(&mut app, 10);
+    assert_eq!(named(&mut app, "child").len(), 1);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::Ready)
+    );
+}
+
+/// lets the `double_health` post processor complete
+#[derive(Resource, Default)]
+struct HealthDoublingDone(bool);
+
+fn double_health(
+    mut instances: Query<(&mut PendingPostProcessors, &mut Health)>,
+    done: Res<HealthDoublingDone>,
+) {
+    for (mut pending, mut health) in instances.iter_mut() {
+        if pending.is_pending("health") && done.0 {
+            health.points *= 2.0;
+            pending.complete("health");
+        }
+    }
+}
+
+#[test]
+fn waits_for_post_processors_without_respawning() {
+    let mut app = test_app(BlueprintsPlugin::default());
+    app.init_resource::<HealthDoublingDone>()
+        .add_blueprint_post_processor("health", double_health);
+    let _crate = add_blueprint(
+        &mut app,
+        "Crate",
+        blueprint_scene("Crate", Some(r#"{"Health":"(points: 10.0)"}"#), |_| {}),
+    );
+    let instance = spawn_instance(&mut app, "Crate");
+
+    // held back by the post processor: the scene is spawned once, and stays spawned while waiting
+    for _ in 0..10 {
+        app.update();
+        assert!(named(&mut app, "child").len() <= 1);
+    }
+    assert_eq!(named(&mut app, "child").len(), 1);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::PostProcessing)
+    );
+
+    app.world.resource_mut::<HealthDoublingDone>().0 = true;
+    update(&mut app, 2);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::Ready)
+    );
+    // the components of the blueprint were injected before post processing
+    assert_eq!(
+        app.world.get::<Health>(instance),
+        Some(&Health { points: 20.0 })
+    );
+    assert_eq!(named(&mut app, "child").len(), 1);
+}
+
+#[test]
+fn waits_for_the_ready_gate_without_respawning() {
+    let mut app = test_app(BlueprintsPlugin::default());
+    let _crate = add_blueprint(&mut app, "Crate", blueprint_scene("Crate", None, |_| {}));
+    let instance = spawn_instance(&mut app, "Crate");
+    app.world
+        .entity_mut(instance)
+        .insert(ReadyGate::new(["navmesh baked"]));
+
+    // held back by the gate: the scene is spawned once, and stays spawned while waiting
+    for _ in 0..10 {
+        app.update();
+        assert!(named(&mut app, "child").len() <= 1);
+    }
+    assert_eq!(named(&mut app, "child").len(), 1);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::PostProcessing)
+    );
+
+    app.world
+        .get_mut::<ReadyGate>(instance)
+        .unwrap()
+        .clear("navmesh baked");
+    update(&mut app, 2);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::Ready)
+    );
+    assert!(app.world.get::<ReadyGate>(instance).is_none());
+    assert_eq!(named(&mut app, "child").len(), 1);
+}
+
+#[test]
+fn fails_once_component_transfer_retries_are_exhausted() {
+    let mut app = test_app(BlueprintsPlugin {
+        max_component_transfer_retries: 3,
+        ..Default::default()
+    });
+    // the scene never gets a root node to transfer the components from
+    let _empty = add_blueprint(&mut app, "Empty", World::new());
+    let instance = spawn_instance(&mut app, "Empty");
+
+    update(&mut app, 10);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::Failed)
+    );
+    assert_eq!(
+        app.world
+            .get::<BlueprintSpawnFailed>(instance)
+            .map(|failed| &failed.0),
+        Some(&BlueprintSpawnError::NoSceneRoot { retries: 3 })
+    );
+    assert!(app.world.get::<SpawnHere>(instance).is_none());
+}
+
+#[test]
+fn spawns_at_the_marker_set_in_the_gltf_extras() {
+    let mut app = test_app(BlueprintsPlugin::default());
+    let _crate = add_blueprint(&mut app, "Crate", blueprint_scene("Crate", None, |_| {}));
+    // an unnamed level, with a marker placed inside of it
+    let level = app
+        .world
+        .spawn(TransformBundle::from_transform(Transform::from_xyz(
+            1.0, 0.0, 0.0,
+        )))
+        .with_children(|level| {
+            level.spawn((
+                Name::new("PlayerStart"),
+                TransformBundle::from_transform(Transform::from_xyz(5.0, 2.0, 0.0)),
+            ));
+        })
+        .id();
+    // lets the global transforms of the level & marker get computed
+    app.update();
+
+    let instance = app
+        .world
+        .spawn((
+            Name::new("Crate"),
+            BlueprintName("Crate".to_string()),
+            SpawnHere,
+            TransformBundle::default(),
+            extras(r#"{"SpawnAtMarker":"\"PlayerStart\""}"#),
+        ))
+        .set_parent(level)
+        .id();
+
+    update(&mut app, 10);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::Ready)
+    );
+    assert_eq!(
+        app.world.get::<SpawnAtMarker>(instance),
+        Some(&SpawnAtMarker("PlayerStart".to_string()))
+    );
+    // placed at the marker, relative to the level it is in
+    assert_eq!(
+        app.world.get::<Transform>(instance).map(|t| t.translation),
+        Some(Vec3::new(5.0, 2.0, 0.0))
+    );
+}
+
+/// the component errors sent so far
+#[derive(Resource, Default)]
+struct ComponentErrors(Vec<GltfComponentError>);
+
+fn collect_component_errors(
+    mut errors: EventReader<GltfComponentError>,
+    mut collected: ResMut<ComponentErrors>,
+) {
+    collected.0.extend(errors.read().cloned());
+}
+
+#[test]
+fn reports_component_errors_with_the_blueprint_scene_path() {
+    let mut app = test_app(BlueprintsPlugin::default());
+    app.init_resource::<ComponentErrors>()
+        .add_systems(Update, collect_component_errors);
+    let _crate = add_blueprint(
+        &mut app,
+        "Crate",
+        blueprint_scene("Crate", Some(r#"{"Health":"(points: oops)"}"#), |_| {}),
+    );
+    let instance = spawn_instance(&mut app, "Crate");
+
+    update(&mut app, 10);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::Ready)
+    );
+    let errors = &app.world.resource::<ComponentErrors>().0;
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].entity, instance);
+    assert_eq!(errors[0].object_name, "Crate");
+    // the scene handle of the instance is gone once it is ready, but not the path of its scene
+    assert!(errors[0]
+        .scene_path
+        .as_ref()
+        .is_some_and(|scene_path| scene_path.ends_with("Crate.glb#Scene0")));
+    assert!(app.world.get::<Health>(instance).is_none());
+}
+
+/// a component of the blueprints, pointing to another object of the blueprint
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+struct OpensDoor {
+    target: NamedEntityRef,
+}
+
+#[test]
+fn resolves_the_entity_refs_set_in_the_gltf_extras() {
+    let mut app = test_app(BlueprintsPlugin::default());
+    app.register_type::<OpensDoor>();
+    let _switch = add_blueprint(
+        &mut app,
+        "Switch",
+        blueprint_scene(
+            "Switch",
+            Some(r#"{"OpensDoor":"(target: (\"Door_01\"))"}"#),
+            |parent| {
+                parent.spawn((Name::new("Door_01"), TransformBundle::default()));
+            },
+        ),
+    );
+    let instance = spawn_instance(&mut app, "Switch");
+
+    update(&mut app, 10);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::Ready)
+    );
+    let doors = named(&mut app, "Door_01");
+    assert_eq!(doors.len(), 1);
+    let opens_door = app
+        .world
+        .get::<OpensDoor>(instance)
+        .expect("the component should have been injected from the gltf extras");
+    assert_eq!(opens_door.target.0, "Door_01");
+    assert_eq!(opens_door.target.get(), Some(doors[0]));
+}
+
+/// a component of the blueprints, referencing an asset
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+struct Icon {
+    image: AssetRef<Image>,
+}
+
+#[test]
+fn loads_the_asset_refs_set_in_the_gltf_extras() {
+    let mut app = test_app(BlueprintsPlugin {
+        strictness: Strictness::FailInstance,
+        ..Default::default()
+    });
+    app.register_type::<Icon>();
+    let _crate = add_blueprint(
+        &mut app,
+        "Crate",
+        blueprint_scene(
+            "Crate",
+            Some(r#"{"Icon":"(image: (\"textures/missing.png\"))"}"#),
+            |_| {},
+        ),
+    );
+    let instance = spawn_instance(&mut app, "Crate");
+
+    // the asset only gets requested once the component is injected, & fails to load asynchronously
+    for _ in 0..100 {
+        app.update();
+        if spawn_state(&app, instance) == Some(BlueprintSpawnState::Failed) {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    assert_eq!(
+        app.world
+            .get::<BlueprintSpawnFailed>(instance)
+            .map(|failed| &failed.0),
+        Some(&BlueprintSpawnError::AssetLoadFailed {
+            path: "textures/missing.png".into()
+        })
+    );
+    assert!(named(&mut app, "child").is_empty());
+}
+
+#[test]
+fn overrides_the_components_set_in_the_gltf_extras() {
+    let mut app = test_app(BlueprintsPlugin::default());
+    let _crate = add_blueprint(
+        &mut app,
+        "Crate",
+        blueprint_scene("Crate", Some(r#"{"Health":"(points: 10.0)"}"#), |_| {}),
+    );
+    let instance = spawn_instance(&mut app, "Crate");
+    app.world
+        .entity_mut(instance)
+        .insert(BlueprintOverrides(HashMap::from([(
+            "Health.points".to_string(),
+            "25.0".to_string(),
+        )])));
+
+    update(&mut app, 10);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::Ready)
+    );
+    assert_eq!(
+        app.world.get::<Health>(instance),
+        Some(&Health { points: 25.0 })
+    );
+}
+
+/// the health of the instances, as seen by the `record_health` constructor
+#[derive(Resource, Default)]
+struct ConstructedHealth(Vec<f32>);
+
+fn record_health(
+    In(entity): In<Entity>,
+    instances: Query<&Health>,
+    mut constructed: ResMut<ConstructedHealth>,
+) {
+    if let Ok(health) = instances.get(entity) {
+        constructed.0.push(health.points);
+    }
+}
+
+#[test]
+fn runs_constructors_with_the_components_set_in_the_gltf_extras() {
+    let mut app = test_app(BlueprintsPlugin::default());
+    app.init_resource::<ConstructedHealth>()
+        .add_blueprint_constructor("Crate", record_health);
+    let _crate = add_blueprint(
+        &mut app,
+        "Crate",
+        blueprint_scene("Crate", Some(r#"{"Health":"(points: 10.0)"}"#), |_| {}),
+    );
+    let instance = spawn_instance(&mut app, "Crate");
+
+    update(&mut app, 10);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::Ready)
+    );
+    assert_eq!(app.world.resource::<ConstructedHealth>().0, vec![10.0]);
+}
+
+#[test]
+fn converts_lights_with_the_settings_set_in_the_gltf_extras() {
+    let mut app = test_app(BlueprintsPlugin {
+        light_conversion: LightConversion {
+            point_intensity: 10.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    app.register_type::<PointLight>();
+    let _lamp = add_blueprint(
+        &mut app,
+        "Lamp",
+        blueprint_scene("Lamp", None, |parent| {
+            parent.spawn((
+                Name::new("Lamp_light"),
+                PointLight {
+                    intensity: 100.0,
+                    shadows_enabled: false,
+                    ..Default::default()
+                },
+                TransformBundle::default(),
+                extras(
+                    r#"{"LightSettings":"(intensity_scale: Some(2.0), shadows_enabled: Some(true), shadow_depth_bias: None, shadow_normal_bias: None)"}"#,
+                ),
+            ));
+        }),
+    );
+    let instance = spawn_instance(&mut app, "Lamp");
+
+    update(&mut app, 10);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::Ready)
+    );
+    let lights = named(&mut app, "Lamp_light");
+    assert_eq!(lights.len(), 1);
+    let light = app.world.get::<PointLight>(lights[0]).unwrap();
+    assert_eq!(light.intensity, 2000.0);
+    assert!(light.shadows_enabled);
+}
+
+#[test]
+fn hides_the_collision_only_objects_set_in_the_gltf_extras() {
+    let mut app = test_app(BlueprintsPlugin::default());
+    app.register_type::<Handle<Mesh>>()
+        .register_type::<Handle<StandardMaterial>>();
+    let _wall = add_blueprint(
+        &mut app,
+        "Wall",
+        blueprint_scene("Wall", None, |parent| {
+            parent
+                .spawn((
+                    Name::new("Wall_collider"),
+                    TransformBundle::default(),
+                    extras(r#"{"CollisionOnly":""}"#),
+                ))
+                .with_children(|object| {
+                    // the primitive of the mesh of the object
+                    object.spawn((
+                        Name::new("Wall_collider.0"),
+                        TransformBundle::default(),
+                        Handle::<Mesh>::default(),
+                        Handle::<StandardMaterial>::default(),
+                    ));
+                });
+        }),
+    );
+    let instance = spawn_instance(&mut app, "Wall");
+
+    update(&mut app, 10);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::Ready)
+    );
+    let object = named(&mut app, "Wall_collider")[0];
+    assert!(app.world.get::<CollisionOnly>(object).is_some());
+    let primitive = named(&mut app, "Wall_collider.0")[0];
+    assert!(app.world.get::<CollisionMesh>(primitive).is_some());
+    assert!(app.world.get::<Handle<Mesh>>(primitive).is_none());
+    assert!(app
+        .world
+        .get::<Handle<StandardMaterial>>(primitive)
+        .is_none());
+}
+
+/// a blueprint with a sensor ball collider described in the gltf extras of one of its objects
+#[cfg(any(feature = "avian", feature = "rapier"))]
+fn add_collider_blueprint(app: &mut App) -> Handle<Gltf> {
+    add_blueprint(
+        app,
+        "Ball",
+        blueprint_scene("Ball", None, |parent| {
+            parent.spawn((
+                Name::new("Ball_collider"),
+                TransformBundle::default(),
+                extras(
+                    r#"{"ColliderDescription":"(shape: Ball(0.5), friction: Some(0.3), restitution: None, sensor: true)"}"#,
+                ),
+            ));
+        }),
+    )
+}
+
+#[cfg(feature = "avian")]
+#[test]
+fn builds_avian_colliders_described_in_the_gltf_extras() {
+    use bevy_xpbd_3d::prelude::{Collider, Friction, Sensor};
+
+    let mut app = test_app(BlueprintsPlugin::default());
+    let _ball = add_collider_blueprint(&mut app);
+    let instance = spawn_instance(&mut app, "Ball");
+
+    update(&mut app, 10);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::Ready)
+    );
+    let object = named(&mut app, "Ball_collider")[0];
+    assert!(app.world.get::<Collider>(object).is_some());
+    assert!(app.world.get::<Sensor>(object).is_some());
+    assert_eq!(
+        app.world
+            .get::<Friction>(object)
+            .map(|friction| friction.dynamic_coefficient),
+        Some(0.3)
+    );
+}
+
+#[cfg(feature = "rapier")]
+#[test]
+fn builds_rapier_colliders_described_in_the_gltf_extras() {
+    use bevy_rapier3d::prelude::{Collider, Friction, Sensor};
+
+    let mut app = test_app(BlueprintsPlugin::default());
+    let _ball = add_collider_blueprint(&mut app);
+    let instance = spawn_instance(&mut app, "Ball");
+
+    update(&mut app, 10);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::Ready)
+    );
+    let object = named(&mut app, "Ball_collider")[0];
+    assert!(app.world.get::<Collider>(object).is_some());
+    assert!(app.world.get::<Sensor>(object).is_some());
+    assert_eq!(
+        app.world
+            .get::<Friction>(object)
+            .map(|friction| friction.coefficient),
+        Some(0.3)
+    );
+}
+
+#[test]
+fn applies_the_render_flags_set_in_the_gltf_extras() {
+    let mut app = test_app(BlueprintsPlugin::default());
+    app.register_type::<Handle<Mesh>>();
+    let _tree = add_blueprint(
+        &mut app,
+        "Tree",
+        blueprint_scene("Tree", None, |parent| {
+            parent
+                .spawn((
+                    Name::new("Leaves"),
+                    TransformBundle::default(),
+                    extras(r#"{"render_layers":[1, 2],"not_shadow_caster":true}"#),
+                ))
+                .with_children(|object| {
+                    // the primitive of the mesh of the object
+                    object.spawn((
+                        Name::new("Leaves.0"),
+                        TransformBundle::default(),
+                        Handle::<Mesh>::default(),
+                    ));
+                });
+            parent.spawn((
+                Name::new("Gizmo"),
+                TransformBundle::default(),
+                extras(r#"{"hide_render":true}"#),
+            ));
+        }),
+    );
+    let instance = spawn_instance(&mut app, "Tree");
+
+    update(&mut app, 10);
+    assert_eq!(
+        spawn_state(&app, instance),
+        Some(BlueprintSpawnState::Ready)
+    );
+    let primitive = named(&mut app, "Leaves.0")[0];
+    assert_eq!(
+        app.world.get::<RenderLayers>(primitive),
+        Some(&RenderLayers::from_layers(&[1, 2]))
+    );
+    assert!(app.world.get::<NotShadowCaster>(primitive).is_some());
+    assert!(app.world.get::<NotShadowReceiver>(primitive).is_none());
+    let gizmo = named(&mut app, "Gizmo")[0];
+    assert_eq!(
+        app.world.get::<Visibility>(gizmo),
+        Some(&Visibility::Hidden)
+    );
+}
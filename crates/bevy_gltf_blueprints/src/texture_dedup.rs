@@ -0,0 +1,140 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use bevy::{prelude::*, utils::HashMap};
+
+/// textures that have the same content (ie the same texture embedded in several blueprint gltf files)
+/// are only kept once, when `deduplicate_textures` is enabled in the plugin:
+/// materials using a duplicate are switched to the first loaded copy, and the duplicate is dropped
+#[derive(Resource, Debug, Default)]
+pub struct TextureDeduplication {
+    /// the first loaded copy of each texture, by content hash
+    pub(crate) by_hash: HashMap<u64, AssetId<Image>>,
+    pub(crate) hashes: HashMap<AssetId<Image>, u64>,
+    /// duplicates => the copy used instead
+    pub(crate) remapped: HashMap<AssetId<Image>, Handle<Image>>,
+    pub(crate) deduplicated: usize,
+}
+
+impl TextureDeduplication {
+    /// amount of duplicate textures dropped so far
+    pub fn deduplicated(&self) -> usize {
+        self.deduplicated
+    }
+}
+
+fn image_hash(image: &Image) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    image.data.hash(&mut hasher);
+    image.texture_descriptor.size.hash(&mut hasher);
+    image.texture_descriptor.format.hash(&mut hasher);
+    image.texture_descriptor.mip_level_count.hash(&mut hasher);
+    format!("{:?}", image.sampler).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// the textures of a material, that can be switched to a deduplicated copy
+fn material_textures(material: &mut StandardMaterial) -> [&mut Option<Handle<Image>>; 6] {
+    [
+        &mut material.base_color_texture,
+        &mut material.emissive_texture,
+        &mut material.metallic_roughness_texture,
+        &mut material.normal_map_texture,
+        &mut material.occlusion_texture,
+        &mut material.depth_map,
+    ]
+}
+
+pub(crate) fn deduplicate_textures(
+    mut image_events: EventReader<AssetEvent<Image>>,
+    mut material_events: EventReader<AssetEvent<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut texture_deduplication: ResMut<TextureDeduplication>,
+) {
+    let mut duplicates: Vec<AssetId<Image>> = vec![];
+    for event in image_events.read() {
+        match event {
+            AssetEvent::Added { id } | AssetEvent::LoadedWithDependencies { id } => {
+                if texture_deduplication.hashes.contains_key(id)
+                    || texture_deduplication.remapped.contains_key(id)
+                {
+                    continue;
+                }
+                let Some(image) = images.get(*id) else {
+                    continue;
+                };
+                let hash = image_hash(image);
+                // the first copy might be gone already, in which case this one takes over
+                let original = texture_deduplication
+                    .by_hash
+                    .get(&hash)
+                    .and_then(|original| asset_server.get_id_handle(*original));
+                match original {
+                    Some(original) => {
+                        texture_deduplication.remapped.insert(*id, original);
+                        duplicates.push(*id);
+                    }
+                    None => {
+                        texture_deduplication.by_hash.insert(hash, *id);
+                        texture_deduplication.hashes.insert(*id, hash);
+                    }
+                }
+            }
+            AssetEvent::Removed { id } => {
+                if let Some(hash) = texture_deduplication.hashes.remove(id) {
+                    texture_deduplication.by_hash.remove(&hash);
+                }
+            }
+            _ => {}
+        }
+    }
+    let materials_changed = material_events.read().any(|event| {
+        matches!(
+            event,
+            AssetEvent::Added { .. } | AssetEvent::LoadedWithDependencies { .. }
+        )
+    });
+    if duplicates.is_empty() && !materials_changed {
+        return;
+    }
+
+    // only touch the materials that actually use duplicates, to avoid triggering needless change events
+    let to_update: Vec<AssetId<StandardMaterial>> = materials
+        .iter()
+        .filter(|(_, material)| {
+            [
+                &material.base_color_texture,
+                &material.emissive_texture,
+                &material.metallic_roughness_texture,
+                &material.normal_map_texture,
+                &material.occlusion_texture,
+                &material.depth_map,
+            ]
+            .into_iter()
+            .flatten()
+            .any(|texture| texture_deduplication.remapped.contains_key(&texture.id()))
+        })
+        .map(|(id, _)| id)
+        .collect();
+    for id in to_update {
+        let Some(material) = materials.get_mut(id) else {
+            continue;
+        };
+        for texture in material_textures(material) {
+            if let Some(original) = texture
+                .as_ref()
+                .and_then(|texture| texture_deduplication.remapped.get(&texture.id()))
+            {
+                *texture = Some(original.clone());
+            }
+        }
+    }
+
+    // the duplicates are not used by any material anymore
+    for id in duplicates {
+        debug!("dropping duplicate texture {:?}", id);
+        images.remove(id);
+        texture_deduplication.deduplicated += 1;
+    }
+}
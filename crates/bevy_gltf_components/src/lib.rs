@@ -7,6 +7,12 @@ pub use ronstring_to_reflect_component::*;
 pub mod process_gltfs;
 pub use process_gltfs::*;
 
+pub mod validation;
+pub use validation::*;
+
+pub mod mesh_material_extras;
+pub use mesh_material_extras::*;
+
 pub mod blender_settings;
 
 use bevy::{
@@ -17,7 +23,7 @@ use bevy::{
         system::{Res, Resource},
     },
     log::warn,
-    prelude::{App, IntoSystemConfigs, Plugin, SystemSet, Update},
+    prelude::{apply_deferred, App, IntoSystemConfigs, Plugin, SystemSet, Update},
     reflect::Reflect,
 };
 
@@ -68,18 +74,32 @@ pub enum GltfComponentsSet {
 #[derive(Clone, Resource)]
 pub struct GltfComponentsConfig {
     pub(crate) legacy_mode: bool,
+    pub(crate) validate: bool,
+    pub(crate) strictness: Strictness,
 }
 
 pub struct ComponentsFromGltfPlugin {
     pub legacy_mode: bool,
+    /// validate the components of every loaded gltf file against the type registry, and log the issues (see [`GltfValidationReports`])
+    pub validate: bool,
+    /// what to do with components that cannot be parsed (see [`GltfComponentError`])
+    pub strictness: Strictness,
 }
 
 impl Default for ComponentsFromGltfPlugin {
     fn default() -> Self {
-        Self { legacy_mode: true }
+        Self {
+            legacy_mode: true,
+            validate: false,
+            strictness: Strictness::SkipAndWarn,
+        }
     }
 }
 
+fn validation_enabled(gltf_components_config: Res<GltfComponentsConfig>) -> bool {
+    gltf_components_config.validate
+}
+
 fn check_for_legacy_mode(gltf_components_config: Res<GltfComponentsConfig>) {
     if gltf_components_config.legacy_mode {
         warn!("using simplified component definitions is deprecated since 0.3, prefer defining components with real ron values (use the bevy_components tool for Blender for simplicity) ");
@@ -92,11 +112,30 @@ impl Plugin for ComponentsFromGltfPlugin {
             .register_type::<GltfProcessed>()
             .insert_resource(GltfComponentsConfig {
                 legacy_mode: self.legacy_mode,
+                validate: self.validate,
+                strictness: self.strictness,
             })
+            .init_resource::<GltfExtrasCache>()
+            .init_resource::<GltfMeshMaterialExtras>()
+            .add_event::<GltfComponentError>()
+            .init_resource::<GltfValidationReports>()
             .add_systems(Startup, check_for_legacy_mode)
             .add_systems(
                 Update,
-                (add_components_from_gltf_extras).in_set(GltfComponentsSet::Injection),
-            );
+                (
+                    track_gltf_scene_sources,
+                    invalidate_gltf_extras_cache,
+                    apply_deferred,
+                    add_components_from_gltf_extras,
+                    read_mesh_material_extras,
+                    add_components_from_mesh_material_extras,
+                )
+                    .chain()
+                    .in_set(GltfComponentsSet::Injection),
+            )
+            .add_systems(Update, validate_loaded_gltfs.run_if(validation_enabled));
     }
 }
+
+#[cfg(test)]
+mod tests;
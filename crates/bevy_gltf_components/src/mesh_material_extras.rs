@@ -0,0 +1,341 @@
+use bevy::{
+    asset::{AssetEvent, AssetId, AssetPath, AssetServer, Assets, Handle},
+    core::Name,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::EventReader,
+        query::Without,
+        reflect::{AppTypeRegistry, ReflectComponent},
+        system::{Res, ResMut, Resource},
+        world::World,
+    },
+    gltf::{Gltf, GltfMesh},
+    hierarchy::Parent,
+    log::{debug, error, warn},
+    pbr::StandardMaterial,
+    render::mesh::Mesh,
+    tasks::{
+        block_on,
+        futures_lite::{future, AsyncReadExt},
+        IoTaskPool, Task,
+    },
+    utils::HashMap,
+};
+use serde_json::Value;
+
+use crate::{
+    clone_components, scene_path, try_ronstring_to_reflect_component, GltfComponentError,
+    GltfComponentsConfig, GltfExtrasCache, GltfSceneSource, ParsedExtras, Strictness,
+};
+
+/// the raw `gltf_extras` of the meshes & materials of a gltf file, keyed by mesh/material name,
+/// as well as its material variants & animation markers (bevy only exposes the extras of nodes, and does not support `KHR_materials_variants`)
+#[derive(Debug, Clone, Default)]
+pub struct MeshMaterialExtras {
+    pub meshes: HashMap<String, String>,
+    pub materials: HashMap<String, String>,
+    /// names of the material variants of the file
+    pub material_variants: Vec<String>,
+    /// per (mesh index, primitive index): the material index to use for each variant index
+    pub primitive_variants: HashMap<(usize, usize), HashMap<usize, usize>>,
+    /// the markers (name & time in seconds, sorted by time) of each animation, keyed by animation name (exported from the markers of Blender actions)
+    pub animation_markers: HashMap<String, Vec<(String, f32)>>,
+}
+
+/// the mesh & material extras of the loaded gltf files, read in the background as the files get loaded
+#[derive(Resource, Default)]
+pub struct GltfMeshMaterialExtras {
+    pub(crate) loaded: HashMap<AssetId<Gltf>, MeshMaterialExtras>,
+    pub(crate) pending: HashMap<AssetId<Gltf>, Task<Option<MeshMaterialExtras>>>,
+}
+
+impl GltfMeshMaterialExtras {
+    /// the mesh & material extras of the given gltf file, None if it is not (yet) loaded
+    pub fn get(&self, gltf: AssetId<Gltf>) -> Option<&MeshMaterialExtras> {
+        self.loaded.get(&gltf)
+    }
+}
+
+/// flag component for mesh entities whose mesh & material extras have been processed
+#[derive(Component)]
+pub(crate) struct MeshMaterialExtrasProcessed;
+
+/// the json of a glb file (its first chunk), or of a gltf file
+fn parse_extras(bytes: &[u8]) -> Result<MeshMaterialExtras, String> {
+    let json = if bytes.starts_with(b"glTF") {
+        let chunk_length = bytes
+            .get(12..16)
+            .map(|length| u32::from_le_bytes([length[0], length[1], length[2], length[3]]))
+            .ok_or("truncated glb file")? as usize;
+        bytes
+            .get(20..20 + chunk_length)
+            .ok_or("truncated glb file")?
+    } else {
+        bytes
+    };
+    let gltf: Value = serde_json::from_slice(json).map_err(|error| error.to_string())?;
+
+    let named_extras = |collection: &str| -> HashMap<String, String> {
+        gltf.get(collection)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|item| {
+                let name = item.get("name")?.as_str()?;
+                let extras = item.get("extras").filter(|extras| extras.is_object())?;
+                Some((name.to_string(), extras.to_string()))
+            })
+            .collect()
+    };
+
+    let material_variants = gltf
+        .pointer("/extensions/KHR_materials_variants/variants")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|variant| {
+            variant
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        })
+        .collect();
+    let mut primitive_variants = HashMap::new();
+    let meshes = gltf.get("meshes").and_then(Value::as_array);
+    for (mesh_index, mesh) in meshes.into_iter().flatten().enumerate() {
+        let primitives = mesh.get("primitives").and_then(Value::as_array);
+        for (primitive_index, primitive) in primitives.into_iter().flatten().enumerate() {
+            let mappings = primitive
+                .pointer("/extensions/KHR_materials_variants/mappings")
+                .and_then(Value::as_array);
+            let mut variants: HashMap<usize, usize> = HashMap::new();
+            for mapping in mappings.into_iter().flatten() {
+                let Some(material) = mapping.get("material").and_then(Value::as_u64) else {
+                    continue;
+                };
+                let mapping_variants = mapping.get("variants").and_then(Value::as_array);
+                for variant in mapping_variants
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_u64)
+                {
+                    variants.insert(variant as usize, material as usize);
+                }
+            }
+            if !variants.is_empty() {
+                primitive_variants.insert((mesh_index, primitive_index), variants);
+            }
+        }
+    }
+
+    let animation_markers = gltf
+        .get("animations")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|animation| {
+            let name = animation.get("name")?.as_str()?;
+            let markers: Vec<(String, f32)> = animation
+                .pointer("/extras/markers")?
+                .as_array()?
+                .iter()
+                .filter_map(|marker| {
+                    let marker_name = marker.get("name")?.as_str()?;
+                    let time = marker.get("time")?.as_f64()?;
+                    Some((marker_name.to_string(), time as f32))
+                })
+                .collect();
+            Some((name.to_string(), markers))
+        })
+        .collect();
+
+    Ok(MeshMaterialExtras {
+        meshes: named_extras("meshes"),
+        materials: named_extras("materials"),
+        material_variants,
+        primitive_variants,
+        animation_markers,
+    })
+}
+
+/// reads the mesh & material extras of gltf files in the background, as they get loaded (or modified)
+pub fn read_mesh_material_extras(
+    mut gltf_events: EventReader<AssetEvent<Gltf>>,
+    asset_server: Res<AssetServer>,
+    mut extras: ResMut<GltfMeshMaterialExtras>,
+) {
+    for event in gltf_events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        let Some(path) = asset_server.get_path(*id) else {
+            continue;
+        };
+        let path: AssetPath<'static> = path.without_label().into_owned();
+        let asset_server = asset_server.clone();
+        let task = IoTaskPool::get().spawn(async move {
+            let source = asset_server.get_source(path.source().clone()).ok()?;
+            let mut reader = source.reader().read(path.path()).await.ok()?;
+            let mut bytes = vec![];
+            AsyncReadExt::read_to_end(&mut reader, &mut bytes)
+                .await
+                .ok()?;
+            parse_extras(&bytes)
+                .map_err(|error| warn!("could not read the extras of {}: {}", path, error))
+                .ok()
+        });
+        extras.pending.insert(*id, task);
+    }
+
+    let GltfMeshMaterialExtras { loaded, pending } = &mut *extras;
+    pending.retain(|id, task| match block_on(future::poll_once(task)) {
+        Some(result) => {
+            loaded.insert(*id, result.unwrap_or_default());
+            false
+        }
+        None => true,
+    });
+}
+
+/// the gltf file the entity was spawned from: the one of the closest ancestor with a [`GltfSceneSource`]
+pub(crate) fn source_gltf(world: &World, entity: Entity) -> Option<AssetId<Gltf>> {
+    let asset_server = world.get_resource::<AssetServer>()?;
+    let mut current = Some(entity);
+    while let Some(ancestor) = current {
+        if let Some(source) = world.get::<GltfSceneSource>(ancestor) {
+            return asset_server
+                .get_handle::<Gltf>(source.0.without_label())
+                .map(|handle| handle.id());
+        }
+        current = world.get::<Parent>(ancestor).map(|parent| parent.get());
+    }
+    None
+}
+
+/// injects the components defined in the `gltf_extras` of meshes & materials into the spawned mesh entities using them
+/// (ie `Slippery` on a material, `NoDecimate` on a mesh), as soon as the extras of their gltf file are available
+pub fn add_components_from_mesh_material_extras(world: &mut World) {
+    let mut unprocessed =
+        world.query_filtered::<(Entity, &Handle<Mesh>), Without<MeshMaterialExtrasProcessed>>();
+    let unprocessed: Vec<(Entity, Handle<Mesh>)> = unprocessed
+        .iter(world)
+        .map(|(entity, mesh)| (entity, mesh.clone()))
+        .collect();
+    if unprocessed.is_empty() {
+        return;
+    }
+
+    // the raw extras of each entity, with the name of the mesh/material they come from
+    let mut entity_extras: Vec<(Entity, AssetId<Gltf>, Vec<(String, String)>)> = vec![];
+    let mut processed: Vec<Entity> = vec![];
+    {
+        let extras = world.resource::<GltfMeshMaterialExtras>();
+        let gltfs = world.resource::<Assets<Gltf>>();
+        let gltf_meshes = world.resource::<Assets<GltfMesh>>();
+        for (entity, mesh) in unprocessed {
+            let Some(gltf_id) = source_gltf(world, entity) else {
+                // not spawned from a gltf file
+                processed.push(entity);
+                continue;
+            };
+            let (Some(gltf), Some(gltf_extras)) = (gltfs.get(gltf_id), extras.get(gltf_id)) else {
+                // the extras are still being read
+                continue;
+            };
+            processed.push(entity);
+
+            let mut found = vec![];
+            let mesh_name = gltf.named_meshes.iter().find_map(|(name, gltf_mesh)| {
+                gltf_meshes
+                    .get(gltf_mesh)?
+                    .primitives
+                    .iter()
+                    .any(|primitive| primitive.mesh.id() == mesh.id())
+                    .then(|| name.to_string())
+            });
+            if let Some((mesh_name, raw)) = mesh_name.and_then(|mesh_name| {
+                let raw = gltf_extras.meshes.get(&mesh_name)?.clone();
+                Some((mesh_name, raw))
+            }) {
+                found.push((mesh_name, raw));
+            }
+            if let Some(material) = world.get::<Handle<StandardMaterial>>(entity) {
+                let material_name = gltf
+                    .named_materials
+                    .iter()
+                    .find(|(_, handle)| handle.id() == material.id())
+                    .map(|(name, _)| name.to_string());
+                if let Some((material_name, raw)) = material_name.and_then(|material_name| {
+                    let raw = gltf_extras.materials.get(&material_name)?.clone();
+                    Some((material_name, raw))
+                }) {
+                    found.push((material_name, raw));
+                }
+            }
+            if !found.is_empty() {
+                entity_extras.push((entity, gltf_id, found));
+            }
+        }
+    }
+    for entity in processed {
+        world.entity_mut(entity).insert(MeshMaterialExtrasProcessed);
+    }
+
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = type_registry.read();
+    let gltf_components_config = world.resource::<GltfComponentsConfig>();
+    let legacy_mode = gltf_components_config.legacy_mode;
+    let strictness = gltf_components_config.strictness;
+
+    for (entity, gltf_id, extras) in entity_extras {
+        for (object_name, raw) in extras {
+            let mut extras_cache = world.resource_mut::<GltfExtrasCache>();
+            let (components, errors) = match extras_cache.get(gltf_id, &raw) {
+                Some(cached) => (clone_components(&cached.components), cached.errors.clone()),
+                None => {
+                    let (components, errors) =
+                        try_ronstring_to_reflect_component(&raw, &type_registry, legacy_mode);
+                    extras_cache.insert(
+                        gltf_id,
+                        raw.clone(),
+                        ParsedExtras {
+                            components: clone_components(&components),
+                            errors: errors.clone(),
+                        },
+                    );
+                    (components, errors)
+                }
+            };
+            for error in errors {
+                let component_error = GltfComponentError {
+                    entity,
+                    object_name: object_name.clone(),
+                    scene_path: scene_path(world, entity),
+                    error,
+                };
+                if strictness == Strictness::Panic {
+                    panic!("{}", component_error);
+                }
+                error!("{}", component_error);
+                world.send_event(component_error);
+            }
+
+            debug!(
+                "adding {} components from the extras of {} to {:?} ({:?})",
+                components.len(),
+                object_name,
+                entity,
+                world.get::<Name>(entity)
+            );
+            let mut entity_mut = world.entity_mut(entity);
+            for (component, type_registration) in components {
+                if let Some(reflect_component) = type_registration.data::<ReflectComponent>() {
+                    reflect_component.insert(&mut entity_mut, &*component, &type_registry);
+                }
+            }
+        }
+    }
+}
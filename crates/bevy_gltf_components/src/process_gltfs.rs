@@ -1,19 +1,146 @@
+use core::fmt;
+
 use bevy::{
+    asset::{AssetEvent, AssetId, AssetPath, AssetServer, Handle},
     core::Name,
     ecs::{
+        component::Component,
         entity::Entity,
-        query::{Added, Without},
+        event::{Event, EventReader},
+        query::{Added, Changed, Without},
         reflect::{AppTypeRegistry, ReflectComponent},
+        system::{Commands, Query, Res, ResMut, Resource},
         world::World,
     },
-    gltf::GltfExtras,
+    gltf::{Gltf, GltfExtras},
     hierarchy::Parent,
-    log::debug,
+    log::{debug, error},
     reflect::{Reflect, TypeRegistration},
+    scene::Scene,
     utils::HashMap,
 };
 
-use crate::{ronstring_to_reflect_component, GltfComponentsConfig, GltfProcessed};
+use crate::{
+    source_gltf, try_ronstring_to_reflect_component, ComponentParseError, GltfComponentsConfig,
+    GltfProcessed,
+};
+
+/// the components parsed from a set of `gltf_extras`, along with the errors of the ones that could not be parsed
+pub(crate) struct ParsedExtras {
+    pub(crate) components: Vec<(Box<dyn Reflect>, TypeRegistration)>,
+    pub(crate) errors: Vec<ComponentParseError>,
+}
+
+/// cache of the components parsed from `gltf_extras`, keyed by gltf file & raw extras string:
+/// spawning the same gltf scene many times (ie blueprints) only parses each set of extras once
+/// * the entries of a gltf file are dropped when it changes on disk or gets unloaded, extras of entities that were not spawned from a gltf file are not cached
+#[derive(Resource, Default)]
+pub struct GltfExtrasCache {
+    pub(crate) gltfs: HashMap<AssetId<Gltf>, HashMap<String, ParsedExtras>>,
+}
+
+impl GltfExtrasCache {
+    pub(crate) fn get(&self, gltf: AssetId<Gltf>, raw: &str) -> Option<&ParsedExtras> {
+        self.gltfs.get(&gltf)?.get(raw)
+    }
+
+    pub(crate) fn insert(&mut self, gltf: AssetId<Gltf>, raw: String, parsed: ParsedExtras) {
+        self.gltfs.entry(gltf).or_default().insert(raw, parsed);
+    }
+}
+
+/// what to do with components (& assets) that cannot be loaded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strictness {
+    /// panic, to catch issues as early as possible during development
+    Panic,
+    /// skip the broken components/assets with a warning, and keep spawning
+    #[default]
+    SkipAndWarn,
+    /// mark the blueprint instance containing the broken components/assets as failed
+    /// (see `bevy_gltf_blueprints`, same as `SkipAndWarn` for gltf files spawned without it)
+    FailInstance,
+}
+
+/// sent for each component of a spawned gltf object that could not be parsed (the component is skipped)
+#[derive(Event, Debug, Clone)]
+pub struct GltfComponentError {
+    /// the entity of the object
+    pub entity: Entity,
+    /// the name of the (Blender) object
+    pub object_name: String,
+    /// path of the gltf scene (ie the blueprint) the object was spawned from, if known
+    pub scene_path: Option<String>,
+    pub error: ComponentParseError,
+}
+
+impl fmt::Display for GltfComponentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (object {}", self.error, self.object_name)?;
+        if let Some(scene_path) = &self.scene_path {
+            write!(f, " in {}", scene_path)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// path of the gltf scene an entity was spawned from (ie `models/library/Crate.glb#Scene0`), recorded when its scene handle is set:
+/// the handle itself does not necessarily stay around (ie blueprint instances drop it once they are spawned)
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct GltfSceneSource(pub AssetPath<'static>);
+
+/// records the path of the gltf scenes being spawned, see [`GltfSceneSource`]
+pub fn track_gltf_scene_sources(
+    scenes: Query<(Entity, &Handle<Scene>), Changed<Handle<Scene>>>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    for (entity, scene) in scenes.iter() {
+        if let Some(path) = asset_server.get_path(scene.id()) {
+            commands
+                .entity(entity)
+                .insert(GltfSceneSource(path.into_owned()));
+        }
+    }
+}
+
+/// path of the scene the entity was spawned from: the closest ancestor with a [`GltfSceneSource`]
+pub(crate) fn scene_path(world: &World, entity: Entity) -> Option<String> {
+    let mut current = Some(entity);
+    while let Some(ancestor) = current {
+        if let Some(source) = world.get::<GltfSceneSource>(ancestor) {
+            return Some(source.0.to_string());
+        }
+        current = world.get::<Parent>(ancestor).map(|parent| parent.get());
+    }
+    None
+}
+
+pub(crate) fn clone_components(
+    components: &[(Box<dyn Reflect>, TypeRegistration)],
+) -> Vec<(Box<dyn Reflect>, TypeRegistration)> {
+    components
+        .iter()
+        .map(|(component, type_registration)| (component.clone_value(), type_registration.clone()))
+        .collect()
+}
+
+/// drops the parsed extras of gltf files that changed (so that the new values are used) or got unloaded
+pub fn invalidate_gltf_extras_cache(
+    mut gltf_events: EventReader<AssetEvent<Gltf>>,
+    mut extras_cache: ResMut<GltfExtrasCache>,
+) {
+    for event in gltf_events.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = event {
+            if extras_cache.gltfs.remove(id).is_some() {
+                debug!(
+                    "gltf file {:?} modified or removed, clearing its cached extras",
+                    id
+                );
+            }
+        }
+    }
+}
 
 /// main function: injects components into each entity in gltf files that have `gltf_extras`, using reflection
 pub fn add_components_from_gltf_extras(world: &mut World) {
@@ -21,8 +148,11 @@ pub fn add_components_from_gltf_extras(world: &mut World) {
         world.query_filtered::<(Entity, &Name, &GltfExtras, &Parent), (Added<GltfExtras>, Without<GltfProcessed>)>();
     let mut entity_components: HashMap<Entity, Vec<(Box<dyn Reflect>, TypeRegistration)>> =
         HashMap::new();
+    let mut newly_parsed: HashMap<(AssetId<Gltf>, String), ParsedExtras> = HashMap::new();
+    let mut component_errors: Vec<(Entity, String, ComponentParseError)> = vec![];
 
     let gltf_components_config = world.resource::<GltfComponentsConfig>();
+    let extras_cache = world.resource::<GltfExtrasCache>();
 
     for (entity, name, extra, parent) in extras.iter(world) {
         debug!(
@@ -30,14 +160,40 @@ pub fn add_components_from_gltf_extras(world: &mut World) {
             name, entity, parent, extra
         );
 
-        let type_registry: &AppTypeRegistry = world.resource();
-        let type_registry = type_registry.read();
+        let source = source_gltf(world, entity);
+        let cached = source.and_then(|gltf| {
+            extras_cache
+                .get(gltf, &extra.value)
+                .or_else(|| newly_parsed.get(&(gltf, extra.value.clone())))
+        });
+        let reflect_components = if let Some(cached) = cached {
+            for error in cached.errors.iter() {
+                component_errors.push((entity, name.to_string(), error.clone()));
+            }
+            clone_components(&cached.components)
+        } else {
+            let type_registry: &AppTypeRegistry = world.resource();
+            let type_registry = type_registry.read();
 
-        let reflect_components = ronstring_to_reflect_component(
-            &extra.value,
-            &type_registry,
-            gltf_components_config.legacy_mode,
-        );
+            let (reflect_components, errors) = try_ronstring_to_reflect_component(
+                &extra.value,
+                &type_registry,
+                gltf_components_config.legacy_mode,
+            );
+            for error in errors.iter() {
+                component_errors.push((entity, name.to_string(), error.clone()));
+            }
+            if let Some(gltf) = source {
+                newly_parsed.insert(
+                    (gltf, extra.value.clone()),
+                    ParsedExtras {
+                        components: clone_components(&reflect_components),
+                        errors,
+                    },
+                );
+            }
+            reflect_components
+        };
 
         // we assign the components specified /xxx_components objects to their parent node
         let mut target_entity = entity;
@@ -68,6 +224,25 @@ pub fn add_components_from_gltf_extras(world: &mut World) {
         }
     }
 
+    let mut extras_cache = world.resource_mut::<GltfExtrasCache>();
+    for ((gltf, raw), parsed) in newly_parsed {
+        extras_cache.insert(gltf, raw, parsed);
+    }
+
+    for (entity, object_name, error) in component_errors {
+        let component_error = GltfComponentError {
+            entity,
+            scene_path: scene_path(world, entity),
+            object_name,
+            error,
+        };
+        if world.resource::<GltfComponentsConfig>().strictness == Strictness::Panic {
+            panic!("{}", component_error);
+        }
+        error!("{}", component_error);
+        world.send_event(component_error);
+    }
+
     for (entity, components) in entity_components {
         let type_registry: &AppTypeRegistry = world.resource();
         let type_registry = type_registry.clone();
@@ -1,3 +1,5 @@
+use core::fmt;
+
 use bevy::log::{debug, warn};
 use bevy::reflect::serde::UntypedReflectDeserializer;
 use bevy::reflect::{Reflect, TypeInfo, TypeRegistration, TypeRegistry};
@@ -7,100 +9,198 @@ use serde::de::DeserializeSeed;
 
 use super::capitalize_first_letter;
 
-pub fn ronstring_to_reflect_component(
+/// name of the component type, as used to look it up in the type registry, from a key of the `gltf_extras`
+pub(crate) fn component_type_name(key: &str) -> String {
+    let type_string = key.replace("component: ", "").trim().to_string();
+    capitalize_first_letter(type_string.as_str())
+}
+
+/// ron string of the component (ie `{ "my_crate::Health": (100.0) }`), ready to be deserialized with reflection,
+/// from the raw value of a `gltf_extras` entry
+pub(crate) fn component_ron_string(
+    value: &Value,
+    type_registration: &TypeRegistration,
+    simplified_types: bool,
+) -> Result<String, String> {
+    let mut parsed_value: String = match value.clone() {
+        Value::String(str) => str,
+        _ => ron::to_string(&value).map_err(|error| error.to_string())?,
+    };
+
+    if simplified_types {
+        if let TypeInfo::TupleStruct(info) = type_registration.type_info() {
+            // we handle tupple strucs with only one field differently, as Blender's custom properties with custom ui (float, int, bool, etc) always give us a tupple struct
+            if info.field_len() == 1 {
+                let field = info
+                    .field_at(0)
+                    .expect("we should always have at least one field here");
+                let field_name = field.type_path();
+                let mut formated = parsed_value.clone();
+                let parse_error = |error: &dyn std::fmt::Display| {
+                    format!("{parsed_value:?} is not a valid {field_name}: {error}")
+                };
+                match field_name {
+                    "f32" => {
+                        formated = parsed_value
+                            .parse::<f32>()
+                            .map_err(|e| parse_error(&e))?
+                            .to_string();
+                    }
+                    "f64" => {
+                        formated = parsed_value
+                            .parse::<f64>()
+                            .map_err(|e| parse_error(&e))?
+                            .to_string();
+                    }
+                    "u8" => {
+                        formated = parsed_value
+                            .parse::<u8>()
+                            .map_err(|e| parse_error(&e))?
+                            .to_string();
+                    }
+                    "u16" => {
+                        formated = parsed_value
+                            .parse::<u16>()
+                            .map_err(|e| parse_error(&e))?
+                            .to_string();
+                    }
+                    "u32" => {
+                        formated = parsed_value
+                            .parse::<u32>()
+                            .map_err(|e| parse_error(&e))?
+                            .to_string();
+                    }
+                    "u64" => {
+                        formated = parsed_value
+                            .parse::<u64>()
+                            .map_err(|e| parse_error(&e))?
+                            .to_string();
+                    }
+                    "u128" => {
+                        formated = parsed_value
+                            .parse::<u128>()
+                            .map_err(|e| parse_error(&e))?
+                            .to_string();
+                    }
+                    "glam::Vec2" => {
+                        let parsed: Vec<f32> =
+                            ron::from_str(&parsed_value).map_err(|e| parse_error(&e))?;
+                        if parsed.len() < 2 {
+                            return Err(parse_error(&"expected 2 values"));
+                        }
+                        formated = format!("(x:{},y:{})", parsed[0], parsed[1]);
+                    }
+                    "glam::Vec3" => {
+                        let parsed: Vec<f32> =
+                            ron::from_str(&parsed_value).map_err(|e| parse_error(&e))?;
+                        if parsed.len() < 3 {
+                            return Err(parse_error(&"expected 3 values"));
+                        }
+                        formated = format!("(x:{},y:{},z:{})", parsed[0], parsed[1], parsed[2]);
+                    }
+                    "bevy_render::color::Color" => {
+                        let parsed: Vec<f32> =
+                            ron::from_str(&parsed_value).map_err(|e| parse_error(&e))?;
+                        if parsed.len() == 3 {
+                            formated = format!(
+                                "Rgba(red:{},green:{},blue:{}, alpha: 1.0)",
+                                parsed[0], parsed[1], parsed[2]
+                            );
+                        }
+                        if parsed.len() == 4 {
+                            formated = format!(
+                                "Rgba(red:{},green:{},blue:{}, alpha:{})",
+                                parsed[0], parsed[1], parsed[2], parsed[3]
+                            );
+                        }
+                    }
+                    _ => {}
+                }
+
+                parsed_value = format!("({formated})");
+            }
+        }
+
+        if parsed_value.is_empty() {
+            parsed_value = "()".to_string();
+        }
+    }
+
+    Ok(format!(
+        "{{ \"{}\":{} }}",
+        type_registration.type_info().type_path(),
+        parsed_value
+    ))
+}
+
+/// deserializes a component from its ron string (see [`component_ron_string`])
+pub(crate) fn deserialize_component(
+    ron_string: &str,
+    type_registry: &TypeRegistry,
+) -> Result<Box<dyn Reflect>, String> {
+    let mut deserializer =
+        ron::Deserializer::from_str(ron_string).map_err(|error| error.to_string())?;
+    let reflect_deserializer = UntypedReflectDeserializer::new(type_registry);
+    reflect_deserializer
+        .deserialize(&mut deserializer)
+        .map_err(|error| error.to_string())
+}
+
+/// a component of the `gltf_extras` of an object that could not be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentParseError {
+    /// type of the component, None if the `gltf_extras` themselves are not valid ron
+    pub component_type: Option<String>,
+    /// raw value of the component (or of the whole `gltf_extras`)
+    pub raw_value: String,
+    /// the underlying ron/serde error
+    pub error: String,
+}
+
+impl fmt::Display for ComponentParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.component_type {
+            Some(component_type) => write!(
+                f,
+                "failed to deserialize component {} with value {}: {}",
+                component_type, self.raw_value, self.error
+            ),
+            None => write!(f, "invalid gltf extras {}: {}", self.raw_value, self.error),
+        }
+    }
+}
+
+/// same as [`ronstring_to_reflect_component`], but returns the components that could not be parsed instead of panicking
+pub fn try_ronstring_to_reflect_component(
     ron_string: &str,
     type_registry: &TypeRegistry,
     simplified_types: bool,
-) -> Vec<(Box<dyn Reflect>, TypeRegistration)> {
-    let lookup: HashMap<String, Value> = ron::from_str(ron_string).unwrap();
+) -> (
+    Vec<(Box<dyn Reflect>, TypeRegistration)>,
+    Vec<ComponentParseError>,
+) {
+    let lookup: HashMap<String, Value> = match ron::from_str(ron_string) {
+        Ok(lookup) => lookup,
+        Err(error) => {
+            return (
+                vec![],
+                vec![ComponentParseError {
+                    component_type: None,
+                    raw_value: ron_string.to_string(),
+                    error: error.to_string(),
+                }],
+            )
+        }
+    };
     let mut components: Vec<(Box<dyn Reflect>, TypeRegistration)> = Vec::new();
+    let mut errors: Vec<ComponentParseError> = Vec::new();
     for (key, value) in lookup.into_iter() {
-        let type_string = key.replace("component: ", "").trim().to_string();
-        let capitalized_type_name = capitalize_first_letter(type_string.as_str());
-
-        let mut parsed_value: String;
-        match value.clone() {
-            Value::String(str) => {
-                parsed_value = str;
-            }
-            _ => parsed_value = ron::to_string(&value).unwrap().to_string(),
-        }
+        let capitalized_type_name = component_type_name(&key);
 
         if let Some(type_registration) =
             type_registry.get_with_short_type_path(capitalized_type_name.as_str())
         {
             debug!("TYPE INFO {:?}", type_registration.type_info());
-            if simplified_types {
-                if let TypeInfo::TupleStruct(info) = type_registration.type_info() {
-                    // we handle tupple strucs with only one field differently, as Blender's custom properties with custom ui (float, int, bool, etc) always give us a tupple struct
-                    if info.field_len() == 1 {
-                        let field = info
-                            .field_at(0)
-                            .expect("we should always have at least one field here");
-                        let field_name = field.type_path();
-                        let mut formated = parsed_value.clone();
-                        match field_name {
-                            "f32" => {
-                                formated = parsed_value.parse::<f32>().unwrap().to_string();
-                            }
-                            "f64" => {
-                                formated = parsed_value.parse::<f64>().unwrap().to_string();
-                            }
-                            "u8" => {
-                                formated = parsed_value.parse::<u8>().unwrap().to_string();
-                            }
-                            "u16" => {
-                                formated = parsed_value.parse::<u16>().unwrap().to_string();
-                            }
-                            "u32" => {
-                                formated = parsed_value.parse::<u32>().unwrap().to_string();
-                            }
-                            "u64" => {
-                                formated = parsed_value.parse::<u64>().unwrap().to_string();
-                            }
-                            "u128" => {
-                                formated = parsed_value.parse::<u128>().unwrap().to_string();
-                            }
-                            "glam::Vec2" => {
-                                let parsed: Vec<f32> = ron::from_str(&parsed_value).unwrap();
-                                formated = format!("(x:{},y:{})", parsed[0], parsed[1]);
-                            }
-                            "glam::Vec3" => {
-                                let parsed: Vec<f32> = ron::from_str(&parsed_value).unwrap();
-                                formated =
-                                    format!("(x:{},y:{},z:{})", parsed[0], parsed[1], parsed[2]);
-                            }
-                            "bevy_render::color::Color" => {
-                                let parsed: Vec<f32> = ron::from_str(&parsed_value).unwrap();
-                                if parsed.len() == 3 {
-                                    formated = format!(
-                                        "Rgba(red:{},green:{},blue:{}, alpha: 1.0)",
-                                        parsed[0], parsed[1], parsed[2]
-                                    );
-                                }
-                                if parsed.len() == 4 {
-                                    formated = format!(
-                                        "Rgba(red:{},green:{},blue:{}, alpha:{})",
-                                        parsed[0], parsed[1], parsed[2], parsed[3]
-                                    );
-                                }
-                            }
-                            _ => {}
-                        }
-
-                        parsed_value = format!("({formated})");
-                    }
-                }
-
-                if parsed_value.is_empty() {
-                    parsed_value = "()".to_string();
-                }
-            }
-            let ron_string = format!(
-                "{{ \"{}\":{} }}",
-                type_registration.type_info().type_path(),
-                parsed_value
-            );
 
             // usefull to determine what an entity looks like Serialized
             /*let test_struct = CameraRenderGraph::new("name");
@@ -109,26 +209,44 @@ pub fn ronstring_to_reflect_component(
                 ron::ser::to_string_pretty(&serializer, ron::ser::PrettyConfig::default()).unwrap();
             println!("serialized Component {}", serialized);*/
 
-            debug!("component data ron string {}", ron_string);
-            let mut deserializer = ron::Deserializer::from_str(ron_string.as_str())
-                .expect("deserialzer should have been generated from string");
-            let reflect_deserializer = UntypedReflectDeserializer::new(type_registry);
-            let component = reflect_deserializer
-                .deserialize(&mut deserializer)
-                .unwrap_or_else(|_| {
-                    panic!(
-                        "failed to deserialize component {} with value: {:?}",
-                        key, value
-                    )
+            let component = component_ron_string(&value, type_registration, simplified_types)
+                .and_then(|ron_string| {
+                    debug!("component data ron string {}", ron_string);
+                    deserialize_component(&ron_string, type_registry)
                 });
-
-            debug!("component {:?}", component);
-            debug!("real type {:?}", component.get_represented_type_info());
-            components.push((component, type_registration.clone()));
-            debug!("found type registration for {}", capitalized_type_name);
+            match component {
+                Ok(component) => {
+                    debug!("component {:?}", component);
+                    debug!("real type {:?}", component.get_represented_type_info());
+                    components.push((component, type_registration.clone()));
+                    debug!("found type registration for {}", capitalized_type_name);
+                }
+                Err(error) => errors.push(ComponentParseError {
+                    component_type: Some(type_registration.type_info().type_path().to_string()),
+                    raw_value: match value {
+                        Value::String(raw_value) => raw_value,
+                        _ => ron::to_string(&value).unwrap_or_default(),
+                    },
+                    error,
+                }),
+            }
         } else {
             warn!("no type registration for {}", capitalized_type_name);
         }
     }
+    (components, errors)
+}
+
+/// parses the components of `gltf_extras`, panics if any of them cannot be parsed (see [`try_ronstring_to_reflect_component`])
+pub fn ronstring_to_reflect_component(
+    ron_string: &str,
+    type_registry: &TypeRegistry,
+    simplified_types: bool,
+) -> Vec<(Box<dyn Reflect>, TypeRegistration)> {
+    let (components, errors) =
+        try_ronstring_to_reflect_component(ron_string, type_registry, simplified_types);
+    if let Some(error) = errors.first() {
+        panic!("{}", error);
+    }
     components
 }
@@ -0,0 +1,151 @@
+use bevy::{
+    gltf::{Gltf, GltfExtras, GltfMesh},
+    prelude::*,
+    utils::HashMap,
+};
+
+use crate::{
+    ComponentsFromGltfPlugin, GltfExtrasCache, GltfMeshMaterialExtras, MeshMaterialExtras,
+};
+
+/// a component of a material, set in its gltf extras
+#[derive(Component, Reflect, Default, Debug, PartialEq)]
+#[reflect(Component)]
+struct Slippery {
+    friction: f32,
+}
+
+/// a headless app with the components plugin & the asset types of gltf files
+fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), HierarchyPlugin))
+        .init_asset::<Gltf>()
+        .init_asset::<GltfMesh>()
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<Scene>()
+        .register_type::<Slippery>()
+        .add_plugins(ComponentsFromGltfPlugin::default());
+    app
+}
+
+/// adds a gltf file to the app as if it was loaded, with a single material named `material_name`
+/// * keep the returned handles around, the gltf file is only "loaded" as long as they are alive
+fn add_gltf(
+    app: &mut App,
+    path: &str,
+    material_name: &str,
+) -> (Handle<Gltf>, Handle<Scene>, Handle<StandardMaterial>) {
+    let asset_server = app.world.resource::<AssetServer>();
+    let gltf: Handle<Gltf> = asset_server.load(path.to_string());
+    let scene: Handle<Scene> = asset_server.load(format!("{}#Scene0", path));
+    let material = app
+        .world
+        .resource_mut::<Assets<StandardMaterial>>()
+        .add(StandardMaterial::default());
+    app.world.resource_mut::<Assets<Gltf>>().insert(
+        gltf.id(),
+        Gltf {
+            scenes: vec![scene.clone()],
+            named_scenes: HashMap::default(),
+            meshes: vec![],
+            named_meshes: HashMap::default(),
+            materials: vec![material.clone()],
+            named_materials: HashMap::from([(material_name.to_string(), material.clone())]),
+            nodes: vec![],
+            named_nodes: HashMap::default(),
+            default_scene: Some(scene.clone()),
+            animations: vec![],
+            named_animations: HashMap::default(),
+        },
+    );
+    (gltf, scene, material)
+}
+
+#[test]
+fn adds_material_components_once_the_scene_handle_is_gone() {
+    let mut app = test_app();
+    let (gltf, scene, material) = add_gltf(&mut app, "models/Level.glb", "Ice");
+    let mut floor = Entity::PLACEHOLDER;
+    let level = app
+        .world
+        .spawn(scene)
+        .with_children(|level| {
+            floor = level
+                .spawn((Name::new("Floor"), Handle::<Mesh>::default(), material))
+                .id();
+        })
+        .id();
+    app.update();
+
+    // the scene handle does not necessarily stay around (ie blueprint instances drop it once they are ready),
+    // while the extras of meshes & materials are still being read
+    app.world.entity_mut(level).remove::<Handle<Scene>>();
+    app.update();
+    assert!(app.world.get::<Slippery>(floor).is_none());
+
+    app.world
+        .resource_mut::<GltfMeshMaterialExtras>()
+        .loaded
+        .insert(
+            gltf.id(),
+            MeshMaterialExtras {
+                meshes: HashMap::default(),
+                materials: HashMap::from([(
+                    "Ice".to_string(),
+                    r#"{"Slippery":"(friction: 0.1)"}"#.to_string(),
+                )]),
+            },
+        );
+    app.update();
+    assert_eq!(
+        app.world.get::<Slippery>(floor),
+        Some(&Slippery { friction: 0.1 })
+    );
+}
+
+/// spawns the scene of a gltf file with a single child carrying the given extras, returns that child
+fn spawn_scene_with_extras(app: &mut App, scene: Handle<Scene>, extras: &str) -> Entity {
+    let mut child = Entity::PLACEHOLDER;
+    app.world.spawn(scene).with_children(|level| {
+        child = level
+            .spawn((
+                Name::new("Crate"),
+                GltfExtras {
+                    value: extras.to_string(),
+                },
+            ))
+            .id();
+    });
+    child
+}
+
+#[test]
+fn reuses_the_cached_extras_when_spawning_the_same_gltf_again() {
+    let mut app = test_app();
+    let (gltf, scene, _material) = add_gltf(&mut app, "models/Crate.glb", "Wood");
+    let extras = r#"{"Slippery":"(friction: 0.1)"}"#;
+
+    let first = spawn_scene_with_extras(&mut app, scene.clone(), extras);
+    app.update();
+    assert_eq!(
+        app.world.get::<Slippery>(first),
+        Some(&Slippery { friction: 0.1 })
+    );
+
+    // tamper with the cached entry: the second spawn only gets this value if it does not parse the extras again
+    let mut extras_cache = app.world.resource_mut::<GltfExtrasCache>();
+    let cached = extras_cache
+        .gltfs
+        .get_mut(&gltf.id())
+        .and_then(|parsed| parsed.get_mut(extras))
+        .expect("the extras of the gltf file should be cached after the first spawn");
+    cached.components[0].0 = Box::new(Slippery { friction: 0.5 });
+
+    let second = spawn_scene_with_extras(&mut app, scene, extras);
+    app.update();
+    assert_eq!(
+        app.world.get::<Slippery>(second),
+        Some(&Slippery { friction: 0.5 })
+    );
+}
@@ -0,0 +1,250 @@
+use core::fmt;
+
+use bevy::{
+    asset::{AssetEvent, AssetServer, Assets},
+    core::Name,
+    ecs::{
+        event::EventReader,
+        reflect::AppTypeRegistry,
+        system::{Res, ResMut, Resource},
+    },
+    gltf::{Gltf, GltfExtras},
+    log::{debug, warn},
+    reflect::TypeRegistry,
+    scene::Scene,
+    utils::HashMap,
+};
+use ron::Value;
+
+use crate::{
+    component_ron_string, component_type_name, deserialize_component, GltfComponentsConfig,
+};
+
+/// a problem with the data of a component embedded in the `gltf_extras` of an object
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComponentIssue {
+    /// the `gltf_extras` are not valid ron
+    InvalidExtras { error: String },
+    /// there is no type with that name in the type registry of the app (not registered, renamed...)
+    UnknownType { type_name: String },
+    /// a field of the component is missing from the data
+    MissingField {
+        type_path: String,
+        field: String,
+        value: String,
+    },
+    /// the data does not match the type of the component (wrong field types, unknown fields, etc)
+    TypeMismatch {
+        type_path: String,
+        value: String,
+        error: String,
+    },
+}
+
+impl fmt::Display for ComponentIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentIssue::InvalidExtras { error } => {
+                write!(f, "invalid gltf extras: {}", error)
+            }
+            ComponentIssue::UnknownType { type_name } => {
+                write!(
+                    f,
+                    "unknown component type {}, is it registered ?",
+                    type_name
+                )
+            }
+            ComponentIssue::MissingField {
+                type_path,
+                field,
+                value,
+            } => write!(
+                f,
+                "component {}: missing field {} in {}",
+                type_path, field, value
+            ),
+            ComponentIssue::TypeMismatch {
+                type_path,
+                value,
+                error,
+            } => write!(
+                f,
+                "component {}: {} does not match: {}",
+                type_path, value, error
+            ),
+        }
+    }
+}
+
+/// the issues found in the components of a single object (Blender object name)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectValidationReport {
+    pub object_name: String,
+    pub issues: Vec<ComponentIssue>,
+}
+
+/// the issues found in the components of all the objects of a gltf file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GltfValidationReport {
+    pub path: String,
+    /// only contains the objects with issues
+    pub objects: Vec<ObjectValidationReport>,
+}
+
+impl GltfValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.objects.is_empty()
+    }
+}
+
+impl fmt::Display for GltfValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_valid() {
+            return write!(f, "{}: ok", self.path);
+        }
+        writeln!(f, "{}:", self.path)?;
+        for object in self.objects.iter() {
+            writeln!(f, "  {}:", object.object_name)?;
+            for issue in object.issues.iter() {
+                writeln!(f, "    - {}", issue)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// validation reports of all the gltf files loaded so far, keyed by path
+/// (only filled if validation is enabled, see [`crate::ComponentsFromGltfPlugin`])
+#[derive(Resource, Debug, Default)]
+pub struct GltfValidationReports(pub HashMap<String, GltfValidationReport>);
+
+/// checks the components embedded in a `gltf_extras` string against the type registry, without panicking
+pub fn validate_extras(
+    ron_string: &str,
+    type_registry: &TypeRegistry,
+    simplified_types: bool,
+) -> Vec<ComponentIssue> {
+    let lookup: HashMap<String, Value> = match ron::from_str(ron_string) {
+        Ok(lookup) => lookup,
+        Err(error) => {
+            return vec![ComponentIssue::InvalidExtras {
+                error: error.to_string(),
+            }]
+        }
+    };
+
+    let mut issues = vec![];
+    for (key, value) in lookup.into_iter() {
+        let type_name = component_type_name(&key);
+        let Some(type_registration) = type_registry.get_with_short_type_path(type_name.as_str())
+        else {
+            issues.push(ComponentIssue::UnknownType { type_name });
+            continue;
+        };
+        let type_path = type_registration.type_info().type_path().to_string();
+        let raw_value = ron::to_string(&value).unwrap_or_default();
+
+        let result = component_ron_string(&value, type_registration, simplified_types)
+            .and_then(|ron_string| deserialize_component(&ron_string, type_registry));
+        if let Err(error) = result {
+            // serde reports missing fields as "missing field `name`"
+            let missing_field = error
+                .split("missing field `")
+                .nth(1)
+                .and_then(|rest| rest.split('`').next());
+            issues.push(match missing_field {
+                Some(field) => ComponentIssue::MissingField {
+                    type_path,
+                    field: field.to_string(),
+                    value: raw_value,
+                },
+                None => ComponentIssue::TypeMismatch {
+                    type_path,
+                    value: raw_value,
+                    error,
+                },
+            });
+        }
+    }
+    issues
+}
+
+/// checks the components of all the objects in the scenes of a (loaded) gltf file against the type registry
+pub fn validate_gltf(
+    path: &str,
+    gltf: &Gltf,
+    scenes: &Assets<Scene>,
+    type_registry: &TypeRegistry,
+    simplified_types: bool,
+) -> GltfValidationReport {
+    let mut objects: HashMap<String, Vec<ComponentIssue>> = HashMap::new();
+    for scene in gltf.scenes.iter().filter_map(|scene| scenes.get(scene)) {
+        for entity in scene.world.iter_entities() {
+            let Some(extras) = entity.get::<GltfExtras>() else {
+                continue;
+            };
+            let issues = validate_extras(&extras.value, type_registry, simplified_types);
+            if issues.is_empty() {
+                continue;
+            }
+            let object_name = entity
+                .get::<Name>()
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| format!("{:?}", entity.id()));
+            // the same object can be present in multiple scenes
+            objects.entry(object_name).or_insert(issues);
+        }
+    }
+
+    let mut objects: Vec<ObjectValidationReport> = objects
+        .into_iter()
+        .map(|(object_name, issues)| ObjectValidationReport {
+            object_name,
+            issues,
+        })
+        .collect();
+    objects.sort_by(|a, b| a.object_name.cmp(&b.object_name));
+    GltfValidationReport {
+        path: path.to_string(),
+        objects,
+    }
+}
+
+/// opt-in system validating every gltf file as it gets loaded, logging the issues & storing the reports in [`GltfValidationReports`]
+pub fn validate_loaded_gltfs(
+    mut gltf_events: EventReader<AssetEvent<Gltf>>,
+    gltfs: Res<Assets<Gltf>>,
+    scenes: Res<Assets<Scene>>,
+    asset_server: Res<AssetServer>,
+    type_registry: Res<AppTypeRegistry>,
+    gltf_components_config: Res<GltfComponentsConfig>,
+    mut reports: ResMut<GltfValidationReports>,
+) {
+    for event in gltf_events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        let Some(gltf) = gltfs.get(*id) else {
+            continue;
+        };
+        let path = asset_server
+            .get_path(*id)
+            .map(|path| path.to_string())
+            .unwrap_or_else(|| format!("{:?}", id));
+
+        let report = validate_gltf(
+            &path,
+            gltf,
+            &scenes,
+            &type_registry.read(),
+            gltf_components_config.legacy_mode,
+        );
+        if report.is_valid() {
+            debug!("gltf validation: {}", report);
+        } else {
+            warn!("gltf validation found issues in {}", report);
+        }
+        reports.0.insert(path, report);
+    }
+}
@@ -63,6 +63,52 @@ classes = [
     AutoExportTracker
 ]
 
+class glTF2ExportUserExtension:
+    """ hooks into the gltf exporter (it looks for a class with this name in enabled addons) """
+
+    # exports the (pose) markers of actions into the extras of their gltf animation, ie "footstep", "hit_frame"
+    # fired as events during playback by bevy_gltf_blueprints
+    def gather_animation_hook(self, gltf2_animation, blender_action, *args):
+        if blender_action is None or len(blender_action.pose_markers) == 0:
+            return
+        render = bpy.context.scene.render
+        fps = render.fps / render.fps_base
+        markers = [{"name": marker.name, "time": marker.frame / fps} for marker in blender_action.pose_markers]
+        markers.sort(key=lambda marker: marker["time"])
+        if gltf2_animation.extras is None:
+            gltf2_animation.extras = {}
+        gltf2_animation.extras["markers"] = markers
+
+    # exports the splines of curve objects as a "Splines" component (control points & handles), since gltf has no notion of curves
+    # turned into bevy CubicCurves by bevy_gltf_blueprints, ie for patrol paths & camera rails
+    def gather_node_hook(self, gltf2_node, blender_object, export_settings):
+        if blender_object is None or blender_object.type != 'CURVE':
+            return
+        yup = export_settings.get('gltf_yup', True)
+        def vec3(co):
+            (x, y, z) = (co[0], co[1], co[2])
+            if yup:
+                (y, z) = (z, -y)
+            return f"(x:{x},y:{y},z:{z})"
+
+        splines = []
+        for spline in blender_object.data.splines:
+            points = []
+            if spline.type == 'BEZIER':
+                for point in spline.bezier_points:
+                    points.append(f"(position:{vec3(point.co)},handle_left:{vec3(point.handle_left)},handle_right:{vec3(point.handle_right)})")
+            else:
+                # poly & nurbs splines: straight segments between the control points
+                for point in spline.points:
+                    points.append(f"(position:{vec3(point.co)},handle_left:{vec3(point.co)},handle_right:{vec3(point.co)})")
+            if len(points) > 1:
+                splines.append(f"(points:[{','.join(points)}],cyclic:{'true' if spline.use_cyclic_u else 'false'})")
+        if len(splines) == 0:
+            return
+        if gltf2_node.extras is None:
+            gltf2_node.extras = {}
+        gltf2_node.extras["Splines"] = f"[{','.join(splines)}]"
+
 def menu_func_import(self, context):
     self.layout.operator(AutoExportGLTF.bl_idname, text="glTF auto Export (.glb/gltf)")
 from bpy.app.handlers import persistent